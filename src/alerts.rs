@@ -0,0 +1,80 @@
+use macroquad::prelude::*;
+
+/// How long an incoming-UFO alert stays on screen, in seconds.
+pub const EDGE_ALERT_LIFETIME: f32 = 1.5;
+
+/// Unit vector pointing from the playfield towards the edge nearest `spawn_pos`.
+///
+/// A UFO entering on the left edge gives `(-1, 0)`, the top edge `(0, -1)`, and so on.
+pub fn edge_direction(spawn_pos: Vec2, bounds: Vec2) -> Vec2 {
+    let distances = [
+        (spawn_pos.x, vec2(-1., 0.)),
+        (bounds.x - spawn_pos.x, vec2(1., 0.)),
+        (spawn_pos.y, vec2(0., -1.)),
+        (bounds.y - spawn_pos.y, vec2(0., 1.)),
+    ];
+    distances
+        .iter()
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, dir)| *dir)
+        .unwrap_or(vec2(-1., 0.))
+}
+
+/// A fading arrow at the screen edge where an enemy just entered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EdgeAlert {
+    /// Point on the screen edge the arrow is drawn at.
+    pub anchor: Vec2,
+    /// Direction the arrow points (outwards, towards the threat).
+    pub direction: Vec2,
+    /// Seconds remaining.
+    pub timer: f32,
+}
+
+impl EdgeAlert {
+    pub fn from_spawn(spawn_pos: Vec2, bounds: Vec2) -> Self {
+        let anchor = vec2(spawn_pos.x.clamp(0., bounds.x), spawn_pos.y.clamp(0., bounds.y));
+        EdgeAlert {
+            anchor,
+            direction: edge_direction(spawn_pos, bounds),
+            timer: EDGE_ALERT_LIFETIME,
+        }
+    }
+
+    /// Ages the alert; returns false once it has faded out.
+    pub fn update(&mut self, dt: f32) -> bool {
+        self.timer -= dt;
+        self.timer > 0.0
+    }
+
+    /// Opacity, fading out over the alert's lifetime.
+    pub fn alpha(&self) -> f32 {
+        (self.timer / EDGE_ALERT_LIFETIME).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edge_direction_for_each_edge() {
+        let bounds = vec2(800., 600.);
+        assert_eq!(edge_direction(vec2(0., 300.), bounds), vec2(-1., 0.));
+        assert_eq!(edge_direction(vec2(800., 120.), bounds), vec2(1., 0.));
+        assert_eq!(edge_direction(vec2(400., 0.), bounds), vec2(0., -1.));
+        assert_eq!(edge_direction(vec2(400., 600.), bounds), vec2(0., 1.));
+        // Left edge near the top corner still counts as the left edge
+        assert_eq!(edge_direction(vec2(0., 5.), bounds), vec2(-1., 0.));
+    }
+
+    #[test]
+    fn test_alert_fades_out() {
+        let mut alert = EdgeAlert::from_spawn(vec2(0., 200.), vec2(800., 600.));
+        assert_eq!(alert.anchor, vec2(0., 200.));
+        assert_eq!(alert.alpha(), 1.0);
+        assert!(alert.update(EDGE_ALERT_LIFETIME / 2.));
+        assert!((alert.alpha() - 0.5).abs() < 1e-5);
+        assert!(!alert.update(EDGE_ALERT_LIFETIME));
+    }
+}
@@ -0,0 +1,83 @@
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+use crate::palette::ColorScheme;
+use crate::world::TARGET_FPS;
+
+/// Depth layers in the starfield.
+pub const STAR_LAYERS: usize = 3;
+/// Stars in each layer.
+pub const STARS_PER_LAYER: usize = 60;
+/// How fast the nearest layer drifts, as a fraction of the ship's velocity.
+pub const PARALLAX_STRENGTH: f32 = 0.3;
+
+/// How far a star at `depth` (0.0 far .. 1.0 near) drifts in `dt` seconds while
+/// the ship moves at `ship_vel` (pixels per physics step): against the ship's motion,
+/// and more for nearer layers.
+pub fn parallax_offset(ship_vel: Vec2, depth: f32, dt: f32) -> Vec2 {
+    -ship_vel * depth * PARALLAX_STRENGTH * dt * TARGET_FPS
+}
+
+/// Layers of background stars that drift against the ship's motion, wrapping
+/// around the playfield.
+pub struct Starfield {
+    /// Every star, layer by layer (farthest first); allocated once.
+    stars: Vec<Vec2>,
+    stars_per_layer: usize,
+    layer_count: usize,
+    size: Vec2,
+}
+
+impl Starfield {
+    /// Scatters `stars_per_layer` stars over each of `layer_count` layers across a `size` playfield.
+    pub fn new(layer_count: usize, stars_per_layer: usize, size: Vec2) -> Self {
+        let stars = (0..layer_count * stars_per_layer)
+            .map(|_| vec2(gen_range(0., size.x), gen_range(0., size.y)))
+            .collect();
+        Starfield { stars, stars_per_layer, layer_count, size }
+    }
+
+    /// Depth of the layer star `index` sits in: the last layer is nearest (1.0).
+    fn depth(&self, index: usize) -> f32 {
+        (index / self.stars_per_layer.max(1) + 1) as f32 / self.layer_count as f32
+    }
+
+    pub fn update(&mut self, ship_vel: Vec2, dt: f32) {
+        for i in 0..self.stars.len() {
+            let moved = self.stars[i] + parallax_offset(ship_vel, self.depth(i), dt);
+            self.stars[i] = vec2(moved.x.rem_euclid(self.size.x), moved.y.rem_euclid(self.size.y));
+        }
+    }
+
+    /// Draws the stars in playfield coordinates; nearer layers are bigger and brighter.
+    pub fn draw(&self, scheme: ColorScheme) {
+        for (i, star) in self.stars.iter().enumerate() {
+            let depth = self.depth(i);
+            let shade = 0.25 + 0.55 * depth;
+            draw_circle(star.x, star.y, 0.5 + depth, scheme.apply(Color::new(shade, shade, shade, 1.0)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_near_layers_drift_further() {
+        let vel = vec2(3., -1.);
+        let far = parallax_offset(vel, 1. / 3., 0.5);
+        let near = parallax_offset(vel, 1., 0.5);
+        assert!(near.length() > far.length());
+        // Both drift against the ship's motion
+        assert!(near.dot(vel) < 0. && far.dot(vel) < 0.);
+        assert_eq!(parallax_offset(Vec2::ZERO, 1., 0.5), Vec2::ZERO);
+    }
+
+    #[test]
+    fn test_stars_wrap_around_the_playfield() {
+        let mut field = Starfield::new(STAR_LAYERS, 4, vec2(200., 100.));
+        assert_eq!(field.stars.len(), STAR_LAYERS * 4);
+        field.update(vec2(50., 50.), 1.0);
+        assert!(field.stars.iter().all(|s| (0.0..200.).contains(&s.x) && (0.0..100.).contains(&s.y)));
+    }
+}
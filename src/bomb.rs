@@ -0,0 +1,89 @@
+use macroquad::prelude::*;
+use crate::physics::World;
+use crate::world::GameWorld;
+
+/// Bombs each ship starts a run with.
+pub const STARTING_BOMBS: u8 = 2;
+/// Most bombs a ship can carry; pickups beyond this are wasted.
+pub const MAX_BOMBS: u8 = 5;
+/// Reach of a bomb blast from the ship's centre, in world pixels.
+pub const BOMB_RADIUS: f32 = 220.0;
+
+/// What a bomb destroyed, for scoring and effects.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BlastReport {
+    /// Positions and radii of the asteroids destroyed.
+    pub asteroids: Vec<(Vec2, f32)>,
+    /// Positions and radii of the UFOs destroyed.
+    pub ufos: Vec<(Vec2, f32)>,
+}
+
+/// Indices of the `(position, radius)` bodies a blast of `blast_radius` at `center` reaches.
+///
+/// A body is caught when any part of it is inside the blast; distances are measured
+/// across the screen wrap, like `hazard_clearance`.
+pub fn blast_targets(center: Vec2, blast_radius: f32, bodies: &[(Vec2, f32)], world: &World) -> Vec<usize> {
+    bodies
+        .iter()
+        .enumerate()
+        .filter(|(_, (pos, radius))| {
+            let d = (center - *pos).abs();
+            let distance = vec2(d.x.min(world.width - d.x), d.y.min(world.height - d.y)).length();
+            distance < blast_radius + radius
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Destroys every active asteroid (without splitting) and UFO within `BOMB_RADIUS` of `center`.
+///
+/// Bosses shrug off bombs; the overdrive shockwave is the way to hurt them from afar.
+pub fn detonate(world: &mut GameWorld, center: Vec2) -> BlastReport {
+    let mut report = BlastReport::default();
+    let rocks: Vec<(Vec2, f32)> = world.asteroids.iter().map(|a| (a.pos, a.radius)).collect();
+    for i in blast_targets(center, BOMB_RADIUS, &rocks, &world.bounds) {
+        let asteroid = &mut world.asteroids[i];
+        if asteroid.active {
+            asteroid.active = false;
+            report.asteroids.push(rocks[i]);
+        }
+    }
+    let saucers: Vec<(Vec2, f32)> = world.ufos.iter().map(|u| (u.pos, u.radius)).collect();
+    for i in blast_targets(center, BOMB_RADIUS, &saucers, &world.bounds) {
+        let ufo = &mut world.ufos[i];
+        if ufo.active {
+            ufo.active = false;
+            report.ufos.push(saucers[i]);
+        }
+    }
+    world.asteroids.retain(|a| a.active);
+    world.ufos.retain(|u| u.active);
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blast_catches_bodies_just_inside() {
+        let world = World::new(800., 600.);
+        let center = vec2(400., 300.);
+        let bodies = [
+            // Edge just inside the blast
+            (center + vec2(BOMB_RADIUS + 9.9, 0.), 10.),
+            // Edge just outside
+            (center + vec2(0., BOMB_RADIUS + 10.1), 10.),
+            // Centre of the blast
+            (center, 5.),
+        ];
+        assert_eq!(blast_targets(center, BOMB_RADIUS, &bodies, &world), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_blast_reaches_across_the_wrap() {
+        let world = World::new(800., 600.);
+        let bodies = [(vec2(790., 300.), 10.), (vec2(400., 300.), 10.)];
+        assert_eq!(blast_targets(vec2(20., 300.), 100., &bodies, &world), vec![0]);
+    }
+}
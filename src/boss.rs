@@ -0,0 +1,93 @@
+use macroquad::prelude::*;
+
+/// Seconds a boss sweeps across the top of the screen before stopping to fire.
+pub const BOSS_SWEEP_TIME: f32 = 3.0;
+/// Seconds a boss holds position firing spreads.
+pub const BOSS_FIRE_TIME: f32 = 2.0;
+/// Bullets in each spread.
+pub const BOSS_SPREAD_SHOTS: usize = 5;
+/// Angle (radians) between the outermost bullets of a spread.
+pub const BOSS_SPREAD_ANGLE: f32 = std::f32::consts::FRAC_PI_3;
+
+/// Where a boss is in its attack cycle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BossPhase {
+    /// Sweeping side to side.
+    Sweeping,
+    /// Holding position and firing spreads at the nearest ship.
+    Firing,
+}
+
+impl BossPhase {
+    /// Seconds spent in this phase before moving on.
+    pub fn duration(self) -> f32 {
+        match self {
+            BossPhase::Sweeping => BOSS_SWEEP_TIME,
+            BossPhase::Firing => BOSS_FIRE_TIME,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            BossPhase::Sweeping => BossPhase::Firing,
+            BossPhase::Firing => BossPhase::Sweeping,
+        }
+    }
+}
+
+/// Runs the attack cycle on by `dt` from `timer` seconds into `phase`.
+///
+/// Returns the phase the boss is now in and how long it has been in it; time
+/// left over when a phase ends carries into the next one.
+pub fn advance_phase(phase: BossPhase, timer: f32, dt: f32) -> (BossPhase, f32) {
+    let timer = timer + dt;
+    if timer < phase.duration() {
+        (phase, timer)
+    } else {
+        (phase.next(), timer - phase.duration())
+    }
+}
+
+/// Directions of a `count`-bullet fan centred on `aim`, `spread` radians from edge to edge.
+pub fn spread_directions(aim: Vec2, count: usize, spread: f32) -> Vec<Vec2> {
+    if count <= 1 {
+        return vec![aim; count];
+    }
+    let step = spread / (count - 1) as f32;
+    (0..count)
+        .map(|i| Vec2::from_angle(-spread / 2. + step * i as f32).rotate(aim))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phases_alternate_and_carry_leftover_time() {
+        let (phase, timer) = advance_phase(BossPhase::Sweeping, 0.0, BOSS_SWEEP_TIME - 0.5);
+        assert_eq!((phase, timer), (BossPhase::Sweeping, BOSS_SWEEP_TIME - 0.5));
+        let (phase, timer) = advance_phase(phase, timer, 0.75);
+        assert_eq!(phase, BossPhase::Firing);
+        assert!((timer - 0.25).abs() < 1e-5);
+        let (phase, _) = advance_phase(phase, timer, BOSS_FIRE_TIME);
+        assert_eq!(phase, BossPhase::Sweeping);
+    }
+
+    #[test]
+    fn test_spread_fans_evenly_around_the_aim() {
+        let aim = vec2(0., 1.);
+        let dirs = spread_directions(aim, BOSS_SPREAD_SHOTS, BOSS_SPREAD_ANGLE);
+        assert_eq!(dirs.len(), BOSS_SPREAD_SHOTS);
+        let angles: Vec<f32> = dirs.iter().map(|d| aim.angle_between(*d)).collect();
+        let step = BOSS_SPREAD_ANGLE / (BOSS_SPREAD_SHOTS - 1) as f32;
+        for (i, angle) in angles.iter().enumerate() {
+            let expected = -BOSS_SPREAD_ANGLE / 2. + step * i as f32;
+            assert!((angle - expected).abs() < 1e-5, "shot {} at {} not {}", i, angle, expected);
+        }
+        // The middle shot flies straight at the target and every shot keeps full speed
+        assert!(dirs[BOSS_SPREAD_SHOTS / 2].distance(aim) < 1e-5);
+        assert!(dirs.iter().all(|d| (d.length() - 1.0).abs() < 1e-5));
+        assert_eq!(spread_directions(aim, 1, BOSS_SPREAD_ANGLE), vec![aim]);
+    }
+}
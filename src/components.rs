@@ -1,17 +1,32 @@
 use macroquad::prelude::*;
+use crate::boss::BossPhase;
+use crate::bomb::STARTING_BOMBS;
+use crate::physics::Body;
+use crate::world::PIERCE_SHOTS;
+use std::sync::atomic::{AtomicU32, Ordering};
 
 /// Enum indicating who owns a bullet (player or enemy).
-#[derive(PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum BulletOwner {
-    Player,
+    /// Fired by the player at this index in `GameWorld::players`.
+    Player(usize),
     Ufo,
 }
 
+/// Hull colors design mode cycles through; `Settings::ship_color` indexes this list.
+pub const SHIP_COLORS: [Color; 9] = [WHITE, RED, ORANGE, YELLOW, GREEN, SKYBLUE, BLUE, PURPLE, PINK];
+
+/// Fuel tank capacity of a new ship, unless `game.cfg` sets `max_fuel`.
+pub const DEFAULT_MAX_FUEL: f32 = 100.0;
+
 /// Represents the player's ship.
+#[derive(Clone)]
 pub struct Player {
     /// Position vector (x, y)
     pub pos: Vec2,
-    /// Velocity vector (dx, dy)
+    /// Position at the start of the last physics step (for interpolated drawing)
+    pub prev_pos: Vec2,
+    /// Velocity vector (dx, dy) in pixels per 1/60 s step (see `world::integrate`)
     pub vel: Vec2,
     /// Rotation angle in radians
     pub rotation: f32,
@@ -27,32 +42,270 @@ pub struct Player {
     pub invulnerable: bool,
     /// How long until invulnerability wears off
     pub invulnerable_timer: f32,
+    /// Engine firing this frame (set once by the input step; drives the engine flame)
+    pub thrusting: bool,
+    /// Seconds of lost control left after bouncing off an asteroid
+    pub stun_timer: f32,
+    /// Seconds until the ship can fire again
+    pub fire_cooldown: f32,
+    /// Seconds until the hyperspace drive can jump again
+    pub hyperspace_cooldown: f32,
+    /// Fuel left in the tank; thrusting burns it
+    pub fuel: f32,
+    /// Tank capacity
+    pub max_fuel: f32,
+    /// Ran dry: thrust is ignored until the tank refills past `FUEL_RESUME_FRACTION`
+    pub fuel_locked: bool,
+    /// Hits the ship can still absorb without losing a life
+    pub shield_charges: u32,
+    /// Screen bombs left to detonate
+    pub bombs: u8,
+    /// Seconds of piercing shots left from a pierce pickup. Not saved.
+    pub pierce_timer: f32,
+}
+
+impl Player {
+    /// A fresh ship at `pos`, at rest and pointing right: the default white triangle,
+    /// a full `DEFAULT_MAX_FUEL` tank and `STARTING_BOMBS` bombs.
+    pub fn new(pos: Vec2) -> Self {
+        Player {
+            pos,
+            prev_pos: pos,
+            vel: Vec2::ZERO,
+            rotation: 0.,
+            radius: 15.,
+            active: true,
+            sides: 3,
+            color: WHITE,
+            invulnerable: false,
+            invulnerable_timer: 0.,
+            thrusting: false,
+            stun_timer: 0.0,
+            fire_cooldown: 0.0,
+            hyperspace_cooldown: 0.0,
+            fuel: DEFAULT_MAX_FUEL,
+            max_fuel: DEFAULT_MAX_FUEL,
+            fuel_locked: false,
+            shield_charges: 0,
+            bombs: STARTING_BOMBS,
+            pierce_timer: 0.0,
+        }
+    }
+
+    /// Returns the ship as a physics body (mass scales with area, like asteroids).
+    pub fn body(&self) -> Body {
+        Body { pos: self.pos, vel: self.vel, radius: self.radius, mass: self.radius * self.radius }
+    }
+}
+
+/// Past positions kept per bullet for its trail.
+pub const TRAIL_LEN: usize = 6;
+
+/// Fixed-size ring buffer of recent positions, newest first when iterated.
+///
+/// Pushing past `TRAIL_LEN` overwrites the oldest point, so it never allocates.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Trail {
+    points: [Vec2; TRAIL_LEN],
+    /// Slot the next push writes to
+    head: usize,
+    len: usize,
+}
+
+impl Trail {
+    pub fn push(&mut self, pos: Vec2) {
+        self.points[self.head] = pos;
+        self.head = (self.head + 1) % TRAIL_LEN;
+        self.len = (self.len + 1).min(TRAIL_LEN);
+    }
+
+    /// Stored points from newest to oldest.
+    pub fn iter(&self) -> impl Iterator<Item = Vec2> + '_ {
+        (1..=self.len).map(move |age| self.points[(self.head + TRAIL_LEN - age) % TRAIL_LEN])
+    }
 }
 
 /// Represents a projectile fired by an entity.
+#[derive(Clone)]
 pub struct Bullet {
     pub pos: Vec2,
+    pub prev_pos: Vec2,
     pub vel: Vec2,
     /// Time in seconds before the bullet disappears
     pub lifetime: f32,
     pub active: bool,
     pub owner: BulletOwner,
+    /// Where the bullet was over the last few physics steps
+    pub trail: Trail,
+    /// Further asteroids the bullet can pass through before it's spent (0: stops at the first).
+    pub pierce: u8,
+    /// Ids of the rocks it has struck and passes through from then on. Not saved.
+    pub struck: [Option<u32>; PIERCE_SHOTS as usize],
 }
 
+impl Bullet {
+    /// A shot leaving `pos` that stops at the first rock it hits.
+    pub fn new(pos: Vec2, vel: Vec2, lifetime: f32, owner: BulletOwner) -> Self {
+        Bullet {
+            pos,
+            prev_pos: pos,
+            vel,
+            lifetime,
+            active: true,
+            owner,
+            trail: Trail::default(),
+            pierce: 0,
+            struck: [None; PIERCE_SHOTS as usize],
+        }
+    }
+
+    pub fn has_struck(&self, asteroid_id: u32) -> bool {
+        self.struck.contains(&Some(asteroid_id))
+    }
+
+    /// Remembers a rock the bullet went on through.
+    pub fn record_strike(&mut self, asteroid_id: u32) {
+        if let Some(slot) = self.struck.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(asteroid_id);
+        }
+    }
+}
+
+/// Next id `Asteroid::new` hands out.
+static NEXT_ASTEROID_ID: AtomicU32 = AtomicU32::new(1);
+
 /// Represents an asteroid obstacle.
 #[derive(Clone)]
 pub struct Asteroid {
     pub pos: Vec2,
+    pub prev_pos: Vec2,
     pub vel: Vec2,
     pub radius: f32,
     pub active: bool,
     /// Number of sides for drawing the polygon (visual variance)
     pub sides: u8,
+    /// Still flying in from off-screen: doesn't wrap or hit the player until fully inside.
+    pub entering: bool,
+    /// Bullet hits left before the asteroid breaks.
+    pub hp: u8,
+    /// Hits it took to break when spawned.
+    pub max_hp: u8,
+    /// Times it has wrapped around the screen edges.
+    pub wrap_count: u32,
+    /// Drawing angle in degrees (cosmetic only: collisions treat asteroids as circles).
+    pub rotation: f32,
+    /// Degrees per second the asteroid turns, negative for counter-clockwise.
+    pub spin: f32,
+    /// Fragments split off together share a family id (see `SweepTracker`); `None` for
+    /// rocks that weren't split from a player's kill. Not saved.
+    pub family: Option<u32>,
+    /// Tells rocks apart across frames so a bullet can skip the ones it has struck.
+    /// Fragments keep their parent's id. Not saved.
+    pub id: u32,
+}
+
+/// Asteroids at least this big are large (three hits to break).
+pub const LARGE_ASTEROID_RADIUS: f32 = 30.0;
+/// Asteroids bigger than this (but not large) are medium (two hits); the rest are small.
+pub const MEDIUM_ASTEROID_RADIUS: f32 = 15.0;
+
+/// Size class of an asteroid, which sets its hit points, score and whether it splits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AsteroidSize {
+    Large,
+    Medium,
+    Small,
+}
+
+impl AsteroidSize {
+    pub fn from_radius(radius: f32) -> Self {
+        if radius >= LARGE_ASTEROID_RADIUS {
+            AsteroidSize::Large
+        } else if radius > MEDIUM_ASTEROID_RADIUS {
+            AsteroidSize::Medium
+        } else {
+            AsteroidSize::Small
+        }
+    }
+
+    /// Bullet hits it takes to break: large 3, medium 2, small 1.
+    pub fn hp(self) -> u8 {
+        match self {
+            AsteroidSize::Large => 3,
+            AsteroidSize::Medium => 2,
+            AsteroidSize::Small => 1,
+        }
+    }
+
+    /// Points for breaking it at the default score table (see `ScoreTable::asteroid_points`).
+    pub fn score_value(self) -> u32 {
+        match self {
+            AsteroidSize::Large => 200,
+            AsteroidSize::Medium => 100,
+            AsteroidSize::Small => 50,
+        }
+    }
+
+    /// Fragments left behind when it breaks; small rocks are destroyed outright.
+    pub fn splits_into(self) -> usize {
+        match self {
+            AsteroidSize::Large | AsteroidSize::Medium => 2,
+            AsteroidSize::Small => 0,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AsteroidSize::Large => "Large Asteroid",
+            AsteroidSize::Medium => "Medium Asteroid",
+            AsteroidSize::Small => "Small Asteroid",
+        }
+    }
+}
+
+impl Asteroid {
+    /// A rock at rest at `pos`, drawn with `sides`, with the hit points for its size.
+    pub fn new(pos: Vec2, radius: f32, sides: u8) -> Self {
+        let hp = AsteroidSize::from_radius(radius).hp();
+        Asteroid {
+            pos,
+            prev_pos: pos,
+            vel: Vec2::ZERO,
+            radius,
+            active: true,
+            sides,
+            entering: false,
+            hp,
+            max_hp: hp,
+            wrap_count: 0,
+            rotation: 0.0,
+            spin: 0.0,
+            family: None,
+            id: NEXT_ASTEROID_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    /// Returns the asteroid as a physics body (mass scales with area).
+    pub fn body(&self) -> Body {
+        Body { pos: self.pos, vel: self.vel, radius: self.radius, mass: self.radius * self.radius }
+    }
+
+    pub fn size(&self) -> AsteroidSize {
+        AsteroidSize::from_radius(self.radius)
+    }
+
+    /// Takes one bullet hit; returns whether that broke the asteroid.
+    pub fn take_hit(&mut self) -> bool {
+        self.hp = self.hp.saturating_sub(1);
+        self.hp == 0
+    }
 }
 
 /// Represents an enemy UFO.
+#[derive(Clone)]
 pub struct Ufo {
     pub pos: Vec2,
+    pub prev_pos: Vec2,
     pub vel: Vec2,
     pub radius: f32,
     pub active: bool,
@@ -61,3 +314,98 @@ pub struct Ufo {
     /// Time accumulator for changing movement direction
     pub change_dir_timer: f32,
 }
+
+/// Represents a boss UFO that takes several hits to destroy.
+#[derive(Clone)]
+pub struct Boss {
+    pub pos: Vec2,
+    pub prev_pos: Vec2,
+    pub vel: Vec2,
+    pub radius: f32,
+    pub active: bool,
+    /// Remaining hit points
+    pub hp: u32,
+    /// Hit points at spawn (for health display)
+    pub max_hp: u32,
+    /// Time accumulator for shooting cooldown
+    pub shoot_timer: f32,
+    /// Current step of the sweep-then-fire attack cycle
+    pub phase: BossPhase,
+    /// Seconds spent in the current phase
+    pub phase_timer: f32,
+}
+
+/// Kinds of pickup dropped by destroyed asteroids and UFOs.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PowerUpKind {
+    /// Absorbs one hit.
+    Shield,
+    /// One more screen bomb.
+    Bomb,
+    /// Bonus points, left behind by every UFO shot down.
+    Points,
+    /// Shots pass through several asteroids for a while.
+    Pierce,
+}
+
+/// A pickup drifting through the field until a ship collects it.
+pub struct PowerUp {
+    pub pos: Vec2,
+    pub prev_pos: Vec2,
+    pub vel: Vec2,
+    pub kind: PowerUpKind,
+    pub radius: f32,
+    pub active: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trail_keeps_newest_points() {
+        let mut trail = Trail::default();
+        assert_eq!(trail.iter().count(), 0);
+        trail.push(vec2(1., 0.));
+        trail.push(vec2(2., 0.));
+        assert_eq!(trail.iter().collect::<Vec<_>>(), [vec2(2., 0.), vec2(1., 0.)]);
+
+        // Filling past capacity overwrites the oldest points
+        for x in 3..=(TRAIL_LEN as i32 + 3) {
+            trail.push(vec2(x as f32, 0.));
+        }
+        assert_eq!(trail.iter().count(), TRAIL_LEN);
+        let xs: Vec<f32> = trail.iter().map(|p| p.x).collect();
+        let expected: Vec<f32> = (4..=(TRAIL_LEN as i32 + 3)).rev().map(|x| x as f32).collect();
+        assert_eq!(xs, expected);
+    }
+
+    #[test]
+    fn test_size_class_boundaries() {
+        assert_eq!(AsteroidSize::from_radius(50.), AsteroidSize::Large);
+        assert_eq!(AsteroidSize::from_radius(LARGE_ASTEROID_RADIUS), AsteroidSize::Large);
+        assert_eq!(AsteroidSize::from_radius(LARGE_ASTEROID_RADIUS - 0.1), AsteroidSize::Medium);
+        assert_eq!(AsteroidSize::from_radius(MEDIUM_ASTEROID_RADIUS + 0.1), AsteroidSize::Medium);
+        assert_eq!(AsteroidSize::from_radius(MEDIUM_ASTEROID_RADIUS), AsteroidSize::Small);
+        assert_eq!(AsteroidSize::from_radius(8.), AsteroidSize::Small);
+        assert_eq!([AsteroidSize::Large, AsteroidSize::Medium, AsteroidSize::Small].map(AsteroidSize::hp), [3, 2, 1]);
+    }
+
+    #[test]
+    fn test_small_asteroids_do_not_split_and_large_ones_score_more() {
+        assert_eq!(AsteroidSize::Small.splits_into(), 0);
+        assert_eq!(AsteroidSize::Medium.splits_into(), 2);
+        assert!(AsteroidSize::Large.score_value() > AsteroidSize::Medium.score_value());
+        assert!(AsteroidSize::Medium.score_value() > AsteroidSize::Small.score_value());
+    }
+
+    #[test]
+    fn test_large_asteroid_takes_three_hits() {
+        let mut rock = Asteroid::new(Vec2::ZERO, 40., 6);
+        assert_eq!(rock.hp, AsteroidSize::Large.hp());
+        assert!(!rock.take_hit());
+        assert!(!rock.take_hit());
+        assert!(rock.take_hit());
+        assert_eq!(rock.hp, 0);
+    }
+}
@@ -1,4 +1,25 @@
+use macroquad::logging::{info, warn};
+use macroquad::prelude::vec2;
+use std::fs;
+use crate::components::{AsteroidSize, DEFAULT_MAX_FUEL};
+use crate::physics::{GravityWell, Zone, ZoneEffect};
+use crate::resources::{SoundId, DEFAULT_REPLAY_INTERVAL, SOUND_NAMES};
+use crate::settings::{AdvancedDifficulty, Difficulty, DifficultyPresets, SettingsDefaults, ShipTuning};
+
+/// Endless mode puts a boss in every this-many-th generated wave.
+pub const ENDLESS_BOSS_EVERY: usize = 5;
+
+/// The kind of encounter a level presents.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LevelKind {
+    /// A regular asteroid field.
+    Asteroids,
+    /// An asteroid field guarded by a boss UFO with `hp` hit points.
+    Boss { hp: u32 },
+}
+
 /// Configuration settings for a specific game level.
+#[derive(Clone, Debug, PartialEq)]
 pub struct LevelConfig {
     /// Number of asteroids to spawn initially.
     pub asteroid_count: usize,
@@ -6,10 +27,20 @@ pub struct LevelConfig {
     pub asteroid_speed_range: (f32, f32),
     /// Multiplier for asteroid size (1.0 = normal).
     pub asteroid_size_mult: f32,
+    /// Fewest bullet hits needed to break each starting asteroid (larger rocks may take more).
+    pub asteroid_hp: u8,
+    /// Screen wraps after which an asteroid despawns (`None` = wraps forever).
+    pub max_wraps: Option<u32>,
     /// Probability per frame of a UFO spawning.
     pub ufo_spawn_chance: f32,
     /// Movement speed of the UFO.
     pub ufo_speed: f32,
+    /// Regular field or boss encounter.
+    pub kind: LevelKind,
+    /// A star pulling the ship, asteroids and UFOs towards it (`None` = no gravity).
+    pub gravity: Option<GravityWell>,
+    /// Space-weather regions that alter physics locally (empty = open space).
+    pub zones: Vec<Zone>,
 }
 
 /// Returns the list of level configurations for the game.
@@ -22,29 +53,111 @@ pub fn get_levels() -> Vec<LevelConfig> {
             asteroid_count: 4,
             asteroid_speed_range: (1.0, 2.0),
             asteroid_size_mult: 1.0,
+            asteroid_hp: 1,
+            max_wraps: None,
             ufo_spawn_chance: 0.0, 
             ufo_speed: 0.0,
+            kind: LevelKind::Asteroids,
+            gravity: None,
+            zones: vec![],
         },
         // Level 2: Faster, occasional UFOs
         LevelConfig {
             asteroid_count: 6,
             asteroid_speed_range: (2.0, 3.5),
             asteroid_size_mult: 1.0,
+            asteroid_hp: 1,
+            max_wraps: None,
             ufo_spawn_chance: 0.002, 
             ufo_speed: 2.0,
+            kind: LevelKind::Asteroids,
+            gravity: None,
+            zones: vec![],
         },
-        // Level 3: Chaos
+        // Level 3: Chaos, guarded by the Sentinel boss
         LevelConfig {
             asteroid_count: 8,
             asteroid_speed_range: (3.0, 5.0),
             asteroid_size_mult: 1.2,
+            asteroid_hp: 1,
+            max_wraps: None,
             ufo_spawn_chance: 0.008, 
             ufo_speed: 3.5,
+            kind: LevelKind::Boss { hp: 10 },
+            gravity: None,
+            zones: vec![],
         },
     ]
 }
 
+impl Default for LevelConfig {
+    /// A gentle asteroid-only field; used to fill fields missing from config files.
+    fn default() -> Self {
+        LevelConfig {
+            asteroid_count: 4,
+            asteroid_speed_range: (1.0, 2.0),
+            asteroid_size_mult: 1.0,
+            asteroid_hp: 1,
+            max_wraps: None,
+            ufo_spawn_chance: 0.0,
+            ufo_speed: 0.0,
+            kind: LevelKind::Asteroids,
+            gravity: None,
+            zones: vec![],
+        }
+    }
+}
+
+/// Returns the index of the first level to play.
+///
+/// In boss-rush mode only boss levels are played.
+pub fn first_level_idx(levels: &[LevelConfig], boss_rush: bool) -> Option<usize> {
+    levels.iter().position(|l| !boss_rush || l.is_boss())
+}
+
+/// Returns the index of the level that follows `current`, or `None` when the run is complete.
+pub fn next_level_idx(levels: &[LevelConfig], current: usize, boss_rush: bool) -> Option<usize> {
+    levels
+        .iter()
+        .enumerate()
+        .skip(current + 1)
+        .find(|(_, l)| !boss_rush || l.is_boss())
+        .map(|(i, _)| i)
+}
+
 impl LevelConfig {
+    /// Whether this level is a boss encounter.
+    pub fn is_boss(&self) -> bool {
+        matches!(self.kind, LevelKind::Boss { .. })
+    }
+
+    /// An endless-mode wave, generated once the configured levels run out.
+    ///
+    /// Wave 0 is roughly the built-in "Chaos" level; every later wave adds asteroids,
+    /// speed and UFO pressure until each value reaches its cap. Every
+    /// `ENDLESS_BOSS_EVERY`th wave also brings a boss, tougher each time.
+    pub fn procedural(wave: usize) -> LevelConfig {
+        let w = wave as f32;
+        let kind = if (wave + 1).is_multiple_of(ENDLESS_BOSS_EVERY) {
+            LevelKind::Boss { hp: (20 + 2 * wave as u32).min(60) }
+        } else {
+            LevelKind::Asteroids
+        };
+        let min_speed = (3.0 + 0.15 * w).min(6.0);
+        LevelConfig {
+            asteroid_count: (8 + wave).min(24),
+            asteroid_speed_range: (min_speed, min_speed + 2.0),
+            asteroid_size_mult: 1.2,
+            asteroid_hp: 1,
+            max_wraps: None,
+            ufo_spawn_chance: (0.008 + 0.001 * w).min(0.05),
+            ufo_speed: (3.5 + 0.1 * w).min(6.0),
+            kind,
+            gravity: None,
+            zones: vec![],
+        }
+    }
+
     /// Returns a difficulty-scaled version of this config.
     pub fn scaled(&self, speed_mult: f32, spawn_mult: f32) -> LevelConfig {
         let mut cfg = LevelConfig {
@@ -54,8 +167,13 @@ impl LevelConfig {
                 self.asteroid_speed_range.1 * speed_mult,
             ),
             asteroid_size_mult: self.asteroid_size_mult,
+            asteroid_hp: self.asteroid_hp,
+            max_wraps: self.max_wraps,
             ufo_spawn_chance: (self.ufo_spawn_chance * spawn_mult).min(1.0),
             ufo_speed: self.ufo_speed * speed_mult,
+            kind: self.kind,
+            gravity: self.gravity,
+            zones: self.zones.clone(),
         };
 
         // Preserve validation expectations by clamping any negative multipliers
@@ -79,20 +197,475 @@ impl LevelConfig {
         if self.asteroid_size_mult <= 0.0 {
             return Err(format!("Level {}: Asteroid size multiplier must be > 0", level_idx + 1));
         }
+        if self.asteroid_hp == 0 {
+            return Err(format!("Level {}: Asteroid hit points must be greater than 0", level_idx + 1));
+        }
+        if self.max_wraps == Some(0) {
+            return Err(format!("Level {}: max_wraps must be greater than 0 (omit it for no limit)", level_idx + 1));
+        }
         if self.ufo_spawn_chance < 0.0 || self.ufo_spawn_chance > 1.0 {
             return Err(format!("Level {}: UFO spawn chance must be between 0.0 and 1.0", level_idx + 1));
         }
         if self.ufo_speed < 0.0 {
             return Err(format!("Level {}: UFO speed must be non-negative", level_idx + 1));
         }
+        for zone in &self.zones {
+            if zone.radius <= 0.0 {
+                return Err(format!("Level {}: Zone radius must be > 0", level_idx + 1));
+            }
+            if let ZoneEffect::Drag(drag) = zone.effect {
+                if !(0.0..=1.0).contains(&drag) {
+                    return Err(format!("Level {}: Zone drag must be between 0.0 and 1.0", level_idx + 1));
+                }
+            }
+        }
+        if let Some(well) = &self.gravity {
+            if !well.strength.is_finite() || well.strength < 0.0 {
+                return Err(format!("Level {}: Gravity strength must be non-negative", level_idx + 1));
+            }
+            if !well.radius.is_finite() || well.radius <= 0.0 {
+                return Err(format!("Level {}: Gravity radius must be > 0", level_idx + 1));
+            }
+        }
+        if self.kind == (LevelKind::Boss { hp: 0 }) {
+            return Err(format!("Level {}: Boss hit points must be greater than 0", level_idx + 1));
+        }
+        Ok(())
+    }
+}
+
+/// Ship handling constants.
+///
+/// Turn rate, thrust and friction are only the starting point for the player's
+/// Ship Feel settings (see `ShipTuning` in settings).
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayerTuning {
+    /// Rotation speed in radians per second.
+    pub rotation_speed: f32,
+    /// Velocity added per frame while thrusting.
+    pub thrust_power: f32,
+    /// Velocity kick per key press in the impulse thrust model.
+    pub impulse_power: f32,
+    /// Velocity multiplier applied every frame (1.0 = no friction).
+    pub friction: f32,
+    /// Fuel tank capacity.
+    pub max_fuel: f32,
+    /// Fuel burned per second of thrust (scaled by difficulty).
+    pub fuel_drain: f32,
+    /// Fuel regained per second while not thrusting.
+    pub fuel_regen: f32,
+}
+
+impl Default for PlayerTuning {
+    fn default() -> Self {
+        let ship = ShipTuning::default();
+        PlayerTuning {
+            rotation_speed: ship.rotation_speed,
+            thrust_power: ship.thrust_power,
+            impulse_power: 2.5,
+            friction: ship.friction,
+            max_fuel: DEFAULT_MAX_FUEL,
+            fuel_drain: 25.0,
+            fuel_regen: 12.0,
+        }
+    }
+}
+
+/// Sound mixing options that belong with the game rather than the player's settings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioTuning {
+    /// Seconds before each sound (in `SoundId::ALL` order) can retrigger; repeats sooner are dropped.
+    pub replay_intervals: [f32; SoundId::COUNT],
+}
+
+impl Default for AudioTuning {
+    fn default() -> Self {
+        AudioTuning { replay_intervals: [DEFAULT_REPLAY_INTERVAL; SoundId::COUNT] }
+    }
+}
+
+/// Points awarded for each scoring event.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScoreTable {
+    /// Total for breaking a medium asteroid (including any mining points earned on the way);
+    /// large and small rocks scale from it by `AsteroidSize::score_value`.
+    pub asteroid: u32,
+    /// Points per non-destroying hit on a multi-hit asteroid (0: only the breaking hit scores).
+    pub mining: u32,
+    pub ufo: u32,
+    pub boss: u32,
+    pub scan: u32,
+}
+
+impl Default for ScoreTable {
+    fn default() -> Self {
+        ScoreTable { asteroid: 100, mining: 0, ufo: 500, boss: 2000, scan: 500 }
+    }
+}
+
+impl ScoreTable {
+    /// Total points for breaking an asteroid of `size`.
+    pub fn asteroid_points(&self, size: AsteroidSize) -> u32 {
+        self.asteroid * size.score_value() / AsteroidSize::Medium.score_value()
+    }
+
+    /// Points for a bullet hit that leaves an asteroid of `size` with `remaining_hp`.
+    ///
+    /// Each non-destroying hit mines a few points; the breaking hit awards the
+    /// remainder so the total always matches `asteroid_points`.
+    pub fn asteroid_hit(&self, size: AsteroidSize, remaining_hp: u8, max_hp: u8) -> u32 {
+        let mining_hits = max_hp.saturating_sub(1) as u32;
+        if remaining_hp > 0 {
+            self.mining
+        } else {
+            self.asteroid_points(size).saturating_sub(self.mining * mining_hits)
+        }
+    }
+}
+
+/// All game tuning in one place, optionally loaded from `game.cfg`.
+///
+/// Any section missing from the file falls back to the built-in defaults.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameConfig {
+    pub player: PlayerTuning,
+    pub scores: ScoreTable,
+    pub audio: AudioTuning,
+    pub difficulty: DifficultyPresets,
+    pub levels: Vec<LevelConfig>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            player: PlayerTuning::default(),
+            scores: ScoreTable::default(),
+            audio: AudioTuning::default(),
+            difficulty: DifficultyPresets::default(),
+            levels: get_levels(),
+        }
+    }
+}
+
+/// A `[name]` block of `key=value` lines from a config file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigSection {
+    pub name: String,
+    /// (key, value, 1-based line number)
+    pub entries: Vec<(String, String, usize)>,
+}
+
+/// Splits `input` into `[section]` blocks of `key=value` entries.
+///
+/// Blank lines and `#` comments are skipped. Entries before the first header
+/// belong to a section with an empty name.
+pub fn parse_sections(input: &str) -> Result<Vec<ConfigSection>, String> {
+    let mut sections: Vec<ConfigSection> = Vec::new();
+    for (i, raw) in input.lines().enumerate() {
+        let line_no = i + 1;
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            sections.push(ConfigSection { name: name.trim().to_lowercase(), entries: Vec::new() });
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("Line {}: expected key=value or [section], got '{}'", line_no, line));
+        };
+        if sections.is_empty() {
+            sections.push(ConfigSection { name: String::new(), entries: Vec::new() });
+        }
+        if let Some(section) = sections.last_mut() {
+            section.entries.push((key.trim().to_string(), value.trim().to_string(), line_no));
+        }
+    }
+    Ok(sections)
+}
+
+fn parse_value<T: std::str::FromStr>(key: &str, value: &str, line: usize) -> Result<T, String> {
+    value.parse::<T>().map_err(|_| format!("Line {}: invalid value '{}' for {}", line, value, key))
+}
+
+fn parse_pair(key: &str, value: &str, line: usize) -> Result<(f32, f32), String> {
+    let Some((a, b)) = value.split_once(',') else {
+        return Err(format!("Line {}: {} must be 'a,b', got '{}'", line, key, value));
+    };
+    Ok((parse_value(key, a.trim(), line)?, parse_value(key, b.trim(), line)?))
+}
+
+/// Parses `strength,radius` (a star at the screen center) or `strength,radius,cx,cy`.
+fn parse_gravity(value: &str, line: usize) -> Result<GravityWell, String> {
+    let nums = value
+        .split(',')
+        .map(|p| parse_value::<f32>("gravity", p.trim(), line))
+        .collect::<Result<Vec<f32>, String>>()?;
+    match nums[..] {
+        [strength, radius] => Ok(GravityWell::centered(strength, radius)),
+        [strength, radius, cx, cy] => Ok(GravityWell { center: vec2(cx, cy), strength, radius }),
+        _ => Err(format!("Line {}: gravity must be 'strength,radius' or 'strength,radius,cx,cy'", line)),
+    }
+}
+
+/// Parses `drag,cx,cy,radius,amount` or `push,cx,cy,radius,fx,fy`.
+fn parse_zone(value: &str, line: usize) -> Result<Zone, String> {
+    let parts: Vec<&str> = value.split(',').map(|p| p.trim()).collect();
+    let nums = parts
+        .iter()
+        .skip(1)
+        .map(|p| parse_value::<f32>("zone", p, line))
+        .collect::<Result<Vec<f32>, String>>()?;
+    let effect = match (parts[0], nums.len()) {
+        ("drag", 4) => ZoneEffect::Drag(nums[3]),
+        ("push", 5) => ZoneEffect::Push(vec2(nums[3], nums[4])),
+        _ => return Err(format!("Line {}: zone must be 'drag,cx,cy,radius,amount' or 'push,cx,cy,radius,fx,fy'", line)),
+    };
+    Ok(Zone { center: vec2(nums[0], nums[1]), radius: nums[2], effect })
+}
+
+impl LevelConfig {
+    /// Builds a level from a `[level]` section; missing keys keep `LevelConfig::default()` values.
+    pub fn from_section(section: &ConfigSection) -> Result<LevelConfig, String> {
+        let mut cfg = LevelConfig::default();
+        let mut boss_hp = None;
+        let mut is_boss = false;
+        for (key, value, line) in &section.entries {
+            let line = *line;
+            match key.as_str() {
+                "asteroid_count" => cfg.asteroid_count = parse_value(key, value, line)?,
+                "asteroid_speed_range" => cfg.asteroid_speed_range = parse_pair(key, value, line)?,
+                "asteroid_size_mult" => cfg.asteroid_size_mult = parse_value(key, value, line)?,
+                "asteroid_hp" => cfg.asteroid_hp = parse_value(key, value, line)?,
+                "max_wraps" => cfg.max_wraps = Some(parse_value(key, value, line)?),
+                "ufo_spawn_chance" => cfg.ufo_spawn_chance = parse_value(key, value, line)?,
+                "ufo_speed" => cfg.ufo_speed = parse_value(key, value, line)?,
+                "kind" => match value.as_str() {
+                    "asteroids" => is_boss = false,
+                    "boss" => is_boss = true,
+                    _ => return Err(format!("Line {}: kind must be 'asteroids' or 'boss'", line)),
+                },
+                "boss_hp" => boss_hp = Some(parse_value(key, value, line)?),
+                "zone" => cfg.zones.push(parse_zone(value, line)?),
+                "gravity" => cfg.gravity = Some(parse_gravity(value, line)?),
+                _ => warn!("Line {}: unknown level key '{}' ignored", line, key),
+            }
+        }
+        if is_boss {
+            cfg.kind = LevelKind::Boss { hp: boss_hp.unwrap_or(10) };
+        }
+        Ok(cfg)
+    }
+
+    /// Parses a level list made of `[level]` blocks of `key=value` lines.
+    ///
+    /// Keys missing from a block keep `LevelConfig::default()` values. Every level
+    /// is validated and the first parse or validation problem is returned.
+    pub fn parse_levels(input: &str) -> Result<Vec<LevelConfig>, String> {
+        let mut levels = Vec::new();
+        for section in parse_sections(input)? {
+            if section.name != "level" {
+                warn!("Section [{}] in a levels file ignored", section.name);
+                continue;
+            }
+            let level = LevelConfig::from_section(&section)?;
+            level.validate(levels.len())?;
+            levels.push(level);
+        }
+        if levels.is_empty() {
+            return Err("Levels file must define at least one [level] block".to_string());
+        }
+        Ok(levels)
+    }
+}
+
+/// Loads the level list from `path`, falling back to `get_levels()` when the file is missing.
+///
+/// A file that exists but fails to parse or validate is reported as an error.
+pub fn load_levels_from_file(path: &str) -> Result<Vec<LevelConfig>, String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => {
+            info!("Levels loaded from {}", path);
+            LevelConfig::parse_levels(&contents).map_err(|e| format!("{}: {}", path, e))
+        }
+        Err(_) => {
+            info!("No levels file at {}; using built-in levels", path);
+            Ok(get_levels())
+        }
+    }
+}
+
+impl GameConfig {
+    /// Parses a whole config file; sections that are absent keep their defaults.
+    ///
+    /// If any `[level]` sections are present they replace the built-in level list.
+    pub fn from_str(input: &str) -> Result<GameConfig, String> {
+        let mut config = GameConfig::default();
+        let mut levels = Vec::new();
+        for section in parse_sections(input)? {
+            match section.name.as_str() {
+                "player" => {
+                    for (key, value, line) in &section.entries {
+                        match key.as_str() {
+                            "rotation_speed" => config.player.rotation_speed = parse_value(key, value, *line)?,
+                            "thrust_power" => config.player.thrust_power = parse_value(key, value, *line)?,
+                            "impulse_power" => config.player.impulse_power = parse_value(key, value, *line)?,
+                            "friction" => config.player.friction = parse_value(key, value, *line)?,
+                            "max_fuel" => config.player.max_fuel = parse_value(key, value, *line)?,
+                            "fuel_drain" => config.player.fuel_drain = parse_value(key, value, *line)?,
+                            "fuel_regen" => config.player.fuel_regen = parse_value(key, value, *line)?,
+                            _ => warn!("Line {}: unknown player key '{}' ignored", line, key),
+                        }
+                    }
+                }
+                "scores" => {
+                    for (key, value, line) in &section.entries {
+                        match key.as_str() {
+                            "asteroid" => config.scores.asteroid = parse_value(key, value, *line)?,
+                            "mining" => config.scores.mining = parse_value(key, value, *line)?,
+                            "ufo" => config.scores.ufo = parse_value(key, value, *line)?,
+                            "boss" => config.scores.boss = parse_value(key, value, *line)?,
+                            "scan" => config.scores.scan = parse_value(key, value, *line)?,
+                            _ => warn!("Line {}: unknown scores key '{}' ignored", line, key),
+                        }
+                    }
+                }
+                "audio" => {
+                    for (key, value, line) in &section.entries {
+                        let sound = key.strip_suffix("_interval").and_then(|name| SOUND_NAMES.iter().position(|n| *n == name));
+                        match sound {
+                            Some(i) => config.audio.replay_intervals[i] = parse_value(key, value, *line)?,
+                            None => warn!("Line {}: unknown audio key '{}' ignored", line, key),
+                        }
+                    }
+                }
+                "difficulty.easy" | "difficulty.normal" | "difficulty.hard" => {
+                    let preset = match section.name.as_str() {
+                        "difficulty.easy" => &mut config.difficulty.easy,
+                        "difficulty.normal" => &mut config.difficulty.normal,
+                        _ => &mut config.difficulty.hard,
+                    };
+                    parse_preset(preset, &section)?;
+                }
+                "level" => levels.push(LevelConfig::from_section(&section)?),
+                other => warn!("Unknown config section [{}] ignored", other),
+            }
+        }
+        if !levels.is_empty() {
+            config.levels = levels;
+        }
+        Ok(config)
+    }
+
+    /// Checks every level and tuning value, returning the first problem found.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.levels.is_empty() {
+            return Err("Config must define at least one level".to_string());
+        }
+        for (i, level) in self.levels.iter().enumerate() {
+            level.validate(i)?;
+        }
+        if self.audio.replay_intervals.iter().any(|i| !i.is_finite() || *i < 0.0) {
+            return Err("Audio replay intervals must be non-negative numbers".to_string());
+        }
+        if self.player.rotation_speed <= 0.0 || self.player.thrust_power <= 0.0 || self.player.impulse_power <= 0.0 {
+            return Err("Player rotation speed and thrust must be > 0".to_string());
+        }
+        if !(self.player.friction > 0.0 && self.player.friction <= 1.0) {
+            return Err("Player friction must be in (0.0, 1.0]".to_string());
+        }
+        if self.player.max_fuel <= 0.0 || self.player.fuel_drain < 0.0 || self.player.fuel_regen <= 0.0 {
+            return Err("Player max_fuel and fuel_regen must be > 0 and fuel_drain non-negative".to_string());
+        }
+        for d in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+            let p = self.difficulty.get(&d);
+            if p.enemy_speed <= 0.0 || p.enemy_aggression <= 0.0 || p.spawn_density < 0.0
+                || p.player_lives == 0 || p.player_handling <= 0.0 {
+                return Err(format!("Difficulty preset {:?} has out-of-range values", d));
+            }
+        }
         Ok(())
     }
+
+    /// The settings a fresh `settings.cfg` starts from: these presets and `[player]` handling.
+    pub fn settings_defaults(&self) -> SettingsDefaults {
+        SettingsDefaults {
+            difficulty: self.difficulty.clone(),
+            ship: ShipTuning {
+                rotation_speed: self.player.rotation_speed,
+                thrust_power: self.player.thrust_power,
+                friction: self.player.friction,
+            },
+        }
+    }
+
+    /// Loads `path`, falling back to the built-in defaults when the file is missing.
+    ///
+    /// Levels come from `[level]` sections in `path` if it has any, otherwise from
+    /// `levels_path` (see `load_levels_from_file`), with a warning if both define levels.
+    /// A file that exists but fails to parse is reported as an error.
+    pub fn load_from_file(path: &str, levels_path: &str) -> Result<GameConfig, String> {
+        let levels = load_levels_from_file(levels_path)?;
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                info!("Game config loaded from {}", path);
+                let mut config = GameConfig::from_str(&contents).map_err(|e| format!("{}: {}", path, e))?;
+                let has_levels = parse_sections(&contents)?.iter().any(|s| s.name == "level");
+                if !has_levels {
+                    config.levels = levels;
+                } else if fs::metadata(levels_path).is_ok() {
+                    warn!("{} has [level] sections, so the levels in {} are ignored", path, levels_path);
+                }
+                Ok(config)
+            }
+            Err(_) => {
+                info!("No game config at {}; using built-in defaults", path);
+                Ok(GameConfig { levels, ..GameConfig::default() })
+            }
+        }
+    }
+}
+
+fn parse_preset(preset: &mut AdvancedDifficulty, section: &ConfigSection) -> Result<(), String> {
+    for (key, value, line) in &section.entries {
+        match key.as_str() {
+            "enemy_speed" => preset.enemy_speed = parse_value(key, value, *line)?,
+            "enemy_aggression" => preset.enemy_aggression = parse_value(key, value, *line)?,
+            "spawn_density" => preset.spawn_density = parse_value(key, value, *line)?,
+            "player_lives" => preset.player_lives = parse_value(key, value, *line)?,
+            "player_handling" => preset.player_handling = parse_value(key, value, *line)?,
+            _ => warn!("Line {}: unknown difficulty key '{}' ignored", line, key),
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_mining_scores_add_up() {
+        let scores = ScoreTable { mining: 5, ..ScoreTable::default() };
+        let hits: Vec<u32> = [2, 1, 0].iter().map(|hp| scores.asteroid_hit(AsteroidSize::Medium, *hp, 3)).collect();
+        assert_eq!(hits, vec![5, 5, 90]);
+        assert_eq!(hits.iter().sum::<u32>(), scores.asteroid);
+        // Single-hit asteroids award the full amount at once
+        assert_eq!(scores.asteroid_hit(AsteroidSize::Medium, 0, 1), 100);
+        // By default only the breaking hit scores
+        let scores = ScoreTable::default();
+        assert_eq!([2, 1, 0].map(|hp| scores.asteroid_hit(AsteroidSize::Medium, hp, 3)), [0, 0, 100]);
+    }
+
+    #[test]
+    fn test_asteroid_points_scale_with_size() {
+        let scores = ScoreTable { asteroid: 150, ..ScoreTable::default() };
+        assert_eq!(scores.asteroid_points(AsteroidSize::Large), 300);
+        assert_eq!(scores.asteroid_points(AsteroidSize::Medium), 150);
+        assert_eq!(scores.asteroid_points(AsteroidSize::Small), 75);
+        assert_eq!(scores.asteroid_hit(AsteroidSize::Large, 0, 3), 300);
+    }
+
     #[test]
     fn test_valid_configs() {
         let levels = get_levels();
@@ -101,14 +674,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_procedural_waves_are_valid() {
+        for wave in 0..100 {
+            assert!(LevelConfig::procedural(wave).validate(wave).is_ok(), "wave {}", wave);
+        }
+    }
+
+    #[test]
+    fn test_procedural_waves_never_get_easier() {
+        for wave in 0..100 {
+            let (a, b) = (LevelConfig::procedural(wave), LevelConfig::procedural(wave + 1));
+            assert!(b.asteroid_count >= a.asteroid_count);
+            assert!(b.asteroid_speed_range.0 >= a.asteroid_speed_range.0);
+            assert!(b.asteroid_speed_range.1 >= a.asteroid_speed_range.1);
+            assert!(b.ufo_spawn_chance >= a.ufo_spawn_chance);
+            assert!(b.ufo_speed >= a.ufo_speed);
+        }
+        assert!(LevelConfig::procedural(10).asteroid_count > LevelConfig::procedural(0).asteroid_count);
+    }
+
+    #[test]
+    fn test_endless_boss_waves() {
+        let bosses: Vec<usize> = (0..15).filter(|w| LevelConfig::procedural(*w).is_boss()).collect();
+        assert_eq!(bosses, vec![4, 9, 14]);
+    }
+
     #[test]
     fn test_invalid_config() {
         let bad_config = LevelConfig {
             asteroid_count: 0,
             asteroid_speed_range: (1.0, 2.0),
             asteroid_size_mult: 1.0,
+            asteroid_hp: 1,
+            max_wraps: None,
             ufo_spawn_chance: 0.5,
             ufo_speed: 1.0,
+            kind: LevelKind::Asteroids,
+            gravity: None,
+            zones: vec![],
         };
         assert!(bad_config.validate(0).is_err());
     }
@@ -119,8 +723,13 @@ mod tests {
             asteroid_count: 4,
             asteroid_speed_range: (-1.0, 2.0),
             asteroid_size_mult: 1.0,
+            asteroid_hp: 1,
+            max_wraps: None,
             ufo_spawn_chance: 0.5,
             ufo_speed: 1.0,
+            kind: LevelKind::Asteroids,
+            gravity: None,
+            zones: vec![],
         };
         assert!(bad_config.validate(0).is_err());
     }
@@ -131,8 +740,13 @@ mod tests {
             asteroid_count: 4,
             asteroid_speed_range: (1.0, 2.0),
             asteroid_size_mult: 0.0,
+            asteroid_hp: 1,
+            max_wraps: None,
             ufo_spawn_chance: 0.5,
             ufo_speed: 1.0,
+            kind: LevelKind::Asteroids,
+            gravity: None,
+            zones: vec![],
         };
         assert!(bad_config.validate(0).is_err());
     }
@@ -143,8 +757,13 @@ mod tests {
             asteroid_count: 4,
             asteroid_speed_range: (1.0, 2.0),
             asteroid_size_mult: 1.0,
+            asteroid_hp: 1,
+            max_wraps: None,
             ufo_spawn_chance: 0.5,
             ufo_speed: -1.0,
+            kind: LevelKind::Asteroids,
+            gravity: None,
+            zones: vec![],
         };
         assert!(bad_config.validate(0).is_err());
     }
@@ -155,8 +774,13 @@ mod tests {
             asteroid_count: 4,
             asteroid_speed_range: (1.0, 2.0),
             asteroid_size_mult: 1.0,
+            asteroid_hp: 1,
+            max_wraps: None,
             ufo_spawn_chance: 0.5,
             ufo_speed: 2.0,
+            kind: LevelKind::Asteroids,
+            gravity: None,
+            zones: vec![],
         };
         let scaled = base.scaled(1.2, 1.5);
         assert_eq!(scaled.asteroid_speed_range.0, 1.2);
@@ -164,4 +788,171 @@ mod tests {
         assert_eq!(scaled.ufo_speed, 2.4);
         assert_eq!(scaled.ufo_spawn_chance, 0.75);
     }
+
+    #[test]
+    fn test_boss_rush_level_order() {
+        let levels = get_levels();
+        let first = first_level_idx(&levels, true).unwrap();
+        assert!(levels[first].is_boss());
+
+        let mut visited = vec![first];
+        let mut idx = first;
+        while let Some(next) = next_level_idx(&levels, idx, true) {
+            visited.push(next);
+            idx = next;
+        }
+        let bosses = levels.iter().filter(|l| l.is_boss()).count();
+        assert_eq!(visited.len(), bosses);
+        assert!(visited.iter().all(|&i| levels[i].is_boss()));
+    }
+
+    #[test]
+    fn test_campaign_level_order_visits_every_level() {
+        let levels = get_levels();
+        assert_eq!(first_level_idx(&levels, false), Some(0));
+        for i in 0..levels.len() - 1 {
+            assert_eq!(next_level_idx(&levels, i, false), Some(i + 1));
+        }
+        assert_eq!(next_level_idx(&levels, levels.len() - 1, false), None);
+    }
+
+    #[test]
+    fn test_invalid_zone_drag() {
+        let mut cfg = get_levels().remove(0);
+        cfg.zones.push(Zone { center: vec2(0.5, 0.5), radius: 50., effect: ZoneEffect::Drag(1.5) });
+        assert!(cfg.validate(0).is_err());
+    }
+
+    #[test]
+    fn test_gravity_well_validation() {
+        let mut cfg = get_levels().remove(0);
+        cfg.gravity = Some(GravityWell::centered(1.5, 250.));
+        assert!(cfg.validate(0).is_ok());
+        cfg.gravity = Some(GravityWell::centered(-1.0, 250.));
+        assert!(cfg.validate(0).is_err());
+        cfg.gravity = Some(GravityWell::centered(1.5, 0.));
+        assert!(cfg.validate(0).is_err());
+    }
+
+    #[test]
+    fn test_game_config_empty_uses_defaults() {
+        let cfg = GameConfig::from_str("").unwrap();
+        assert_eq!(cfg, GameConfig::default());
+        assert!(cfg.validate().is_ok());
+    }
+
+    #[test]
+    fn test_game_config_full_file() {
+        let input = "\
+# Modded game
+[player]
+rotation_speed=5.0
+friction=0.95
+
+[scores]
+ufo=750
+
+[audio]
+bang_interval=0.05
+
+[difficulty.hard]
+enemy_speed=1.5
+player_lives=2
+
+[level]
+asteroid_count=3
+asteroid_speed_range=0.5, 1.5
+max_wraps=4
+zone=drag,0.5,0.5,100,0.1
+gravity=1.5,250
+
+[level]
+kind=boss
+boss_hp=25
+";
+        let cfg = GameConfig::from_str(input).unwrap();
+        assert_eq!(cfg.player.rotation_speed, 5.0);
+        assert_eq!(cfg.player.thrust_power, 0.15);
+        assert_eq!(cfg.player.friction, 0.95);
+        assert_eq!(cfg.scores.ufo, 750);
+        assert_eq!(cfg.scores.asteroid, 100);
+        assert_eq!(cfg.audio.replay_intervals[SoundId::Bang.index()], 0.05);
+        assert_eq!(cfg.audio.replay_intervals[SoundId::Shoot.index()], DEFAULT_REPLAY_INTERVAL);
+        assert_eq!(cfg.difficulty.hard.enemy_speed, 1.5);
+        assert_eq!(cfg.difficulty.hard.player_lives, 2);
+        assert_eq!(cfg.difficulty.normal, Difficulty::Normal.advanced());
+        assert_eq!(cfg.levels.len(), 2);
+        assert_eq!(cfg.levels[0].asteroid_count, 3);
+        assert_eq!(cfg.levels[0].asteroid_speed_range, (0.5, 1.5));
+        assert_eq!(cfg.levels[0].zones.len(), 1);
+        assert_eq!(cfg.levels[0].max_wraps, Some(4));
+        assert_eq!(cfg.levels[1].max_wraps, None);
+        assert_eq!(cfg.levels[0].gravity, Some(GravityWell::centered(1.5, 250.)));
+        assert_eq!(cfg.levels[1].gravity, None);
+        assert_eq!(cfg.levels[1].kind, LevelKind::Boss { hp: 25 });
+        assert!(cfg.validate().is_ok());
+    }
+
+    #[test]
+    fn test_game_config_malformed_line() {
+        assert!(GameConfig::from_str("[player]\nrotation_speed 5\n").is_err());
+        assert!(GameConfig::from_str("[level]\nasteroid_speed_range=1.0\n").is_err());
+        assert!(GameConfig::from_str("[scores]\nufo=lots\n").is_err());
+    }
+
+    #[test]
+    fn test_game_config_validation_catches_bad_values() {
+        let cfg = GameConfig::from_str("[player]\nfriction=1.5\n").unwrap();
+        assert!(cfg.validate().is_err());
+        let cfg = GameConfig::from_str("[level]\nasteroid_count=0\n").unwrap();
+        assert!(cfg.validate().is_err());
+        let cfg = GameConfig::from_str("[audio]\nshoot_interval=-0.1\n").unwrap();
+        assert!(cfg.validate().is_err());
+        let cfg = GameConfig::from_str("[audio]\nbang_interval=NaN\n").unwrap();
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn test_parse_levels_well_formed() {
+        let input = "\
+# Two-level mod
+[level]
+asteroid_count=5
+asteroid_speed_range=1.5,2.5
+asteroid_size_mult=1.1
+ufo_spawn_chance=0.003
+ufo_speed=2.5
+
+[level]
+asteroid_count=7
+asteroid_speed_range=2.0, 4.0
+ufo_spawn_chance=0.01
+ufo_speed=3.0
+";
+        let levels = LevelConfig::parse_levels(input).unwrap();
+        assert_eq!(levels.len(), 2);
+        assert_eq!(levels[0].asteroid_count, 5);
+        assert_eq!(levels[0].asteroid_speed_range, (1.5, 2.5));
+        assert_eq!(levels[0].asteroid_size_mult, 1.1);
+        assert_eq!(levels[0].ufo_spawn_chance, 0.003);
+        assert_eq!(levels[1].asteroid_speed_range, (2.0, 4.0));
+        assert_eq!(levels[1].ufo_speed, 3.0);
+    }
+
+    #[test]
+    fn test_parse_levels_malformed_range() {
+        let err = LevelConfig::parse_levels("[level]\nasteroid_speed_range=1.0;2.0\n").unwrap_err();
+        assert!(err.contains("asteroid_speed_range"), "{}", err);
+        // Parses, but fails validation with the offending level named
+        let err = LevelConfig::parse_levels("[level]\n[level]\nasteroid_speed_range=3.0,1.0\n").unwrap_err();
+        assert!(err.starts_with("Level 2:"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_levels_missing_fields_default() {
+        let levels = LevelConfig::parse_levels("[level]\nasteroid_count=9\n").unwrap();
+        let expected = LevelConfig { asteroid_count: 9, ..LevelConfig::default() };
+        assert_eq!(levels, vec![expected]);
+        assert!(LevelConfig::parse_levels("# nothing here\n").is_err());
+    }
 }
@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+use crate::settings::Difficulty;
+
+/// Lines of command history and replies kept on screen.
+pub const CONSOLE_SCROLLBACK: usize = 8;
+/// Longest command line the console accepts.
+pub const CONSOLE_MAX_INPUT: usize = 60;
+/// Reply to `help`.
+pub const CONSOLE_HELP: &str = "set volume <0-1> | set difficulty <easy|normal|hard> | spawn ufo | give life | level <n>";
+
+/// A tuning command typed into the console.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    /// Master volume, 0.0..=1.0.
+    SetVolume(f32),
+    /// Switch preset (resets the advanced tuning, as in the settings screen).
+    SetDifficulty(Difficulty),
+    /// Bring a UFO in on the next frame, even if one is already flying.
+    SpawnUfo,
+    /// One extra life, as if earned.
+    GiveLife,
+    /// Jump to a campaign level, counted from 1.
+    Level(usize),
+    Help,
+}
+
+fn parse_difficulty(value: &str) -> Result<Difficulty, String> {
+    match value {
+        "easy" => Ok(Difficulty::Easy),
+        "normal" => Ok(Difficulty::Normal),
+        "hard" => Ok(Difficulty::Hard),
+        _ => Err(format!("Unknown difficulty '{}'; use easy, normal or hard", value)),
+    }
+}
+
+/// Parses one console line (case-insensitive, words separated by spaces).
+pub fn parse_command(input: &str) -> Result<Command, String> {
+    let lowered = input.trim().to_lowercase();
+    let words: Vec<&str> = lowered.split_whitespace().collect();
+    match words[..] {
+        ["set", "volume", value] => match value.parse::<f32>() {
+            Ok(v) if (0.0..=1.0).contains(&v) => Ok(Command::SetVolume(v)),
+            _ => Err(format!("Volume must be between 0 and 1, got '{}'", value)),
+        },
+        ["set", "difficulty", value] => parse_difficulty(value).map(Command::SetDifficulty),
+        ["set", name, _] => Err(format!("Unknown setting '{}'; try volume or difficulty", name)),
+        ["spawn", "ufo"] => Ok(Command::SpawnUfo),
+        ["spawn", what] => Err(format!("Can't spawn '{}'; try ufo", what)),
+        ["give", "life"] => Ok(Command::GiveLife),
+        ["give", what] => Err(format!("Can't give '{}'; try life", what)),
+        ["level", value] => match value.parse::<usize>() {
+            Ok(n) if n >= 1 => Ok(Command::Level(n)),
+            _ => Err(format!("Level must be a number from 1, got '{}'", value)),
+        },
+        ["help"] => Ok(Command::Help),
+        [] => Err("Type a command, or help".to_string()),
+        _ => Err(format!("Unknown command '{}'; type help", input.trim())),
+    }
+}
+
+/// Text-input overlay for tuning the game live: a command line plus a short scrollback.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Console {
+    pub open: bool,
+    /// The line being typed.
+    pub input: String,
+    /// Past commands and replies, oldest first.
+    lines: VecDeque<String>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opens or closes the overlay; whatever was half-typed is dropped.
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.input.clear();
+    }
+
+    /// Adds a typed character; control characters and the toggle key are ignored.
+    pub fn type_char(&mut self, c: char) {
+        if !c.is_control() && c != '`' && self.input.chars().count() < CONSOLE_MAX_INPUT {
+            self.input.push(c);
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        self.input.pop();
+    }
+
+    /// Takes the typed line, echoing it to the scrollback; `None` if it was blank.
+    pub fn submit(&mut self) -> Option<String> {
+        let line = std::mem::take(&mut self.input);
+        if line.trim().is_empty() {
+            return None;
+        }
+        self.print(format!("> {}", line.trim()));
+        Some(line)
+    }
+
+    /// Adds a line to the scrollback, dropping the oldest past `CONSOLE_SCROLLBACK`.
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.lines.push_back(line.into());
+        while self.lines.len() > CONSOLE_SCROLLBACK {
+            self.lines.pop_front();
+        }
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &String> {
+        self.lines.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_valid_commands() {
+        assert_eq!(parse_command("set volume 0.5"), Ok(Command::SetVolume(0.5)));
+        assert_eq!(parse_command("  SET Difficulty HARD "), Ok(Command::SetDifficulty(Difficulty::Hard)));
+        assert_eq!(parse_command("spawn ufo"), Ok(Command::SpawnUfo));
+        assert_eq!(parse_command("give life"), Ok(Command::GiveLife));
+        assert_eq!(parse_command("level 3"), Ok(Command::Level(3)));
+        assert_eq!(parse_command("help"), Ok(Command::Help));
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_commands() {
+        for bad in ["", "   ", "fly", "set volume 2", "set volume loud", "set gravity 1", "set difficulty insane",
+                    "spawn boss", "give bombs", "level 0", "level two", "level 1 2", "spawn"] {
+            assert!(parse_command(bad).is_err(), "'{}' should not parse", bad);
+        }
+        assert_eq!(parse_command("warp 9"), Err("Unknown command 'warp 9'; type help".to_string()));
+    }
+
+    #[test]
+    fn test_console_input_and_scrollback() {
+        let mut console = Console::new();
+        console.toggle();
+        for c in "`give lifee\r".chars() {
+            console.type_char(c);
+        }
+        console.backspace();
+        assert_eq!(console.input, "give life");
+        assert_eq!(console.submit(), Some("give life".to_string()));
+        assert!(console.input.is_empty());
+        assert_eq!(console.submit(), None);
+
+        for i in 0..CONSOLE_SCROLLBACK {
+            console.print(format!("line {}", i));
+        }
+        let lines: Vec<&String> = console.lines().collect();
+        assert_eq!(lines.len(), CONSOLE_SCROLLBACK);
+        assert_eq!(lines[0], "line 0");
+    }
+}
@@ -0,0 +1,88 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::rng::GameRng;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Converts days since 1970-01-01 into a `(year, month, day)` civil date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Daily-challenge seed for a Unix timestamp: the UTC date as `YYYYMMDD`.
+pub fn date_seed(unix_secs: u64) -> u64 {
+    let (year, month, day) = civil_from_days((unix_secs / SECONDS_PER_DAY) as i64);
+    year as u64 * 10_000 + month as u64 * 100 + day as u64
+}
+
+/// Seed for today's daily challenge (UTC), identical for every player.
+pub fn daily_seed() -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    date_seed(now)
+}
+
+/// Formats a daily seed as `YYYY-MM-DD` for display.
+pub fn format_daily_seed(seed: u64) -> String {
+    format!("{:04}-{:02}-{:02}", seed / 10_000, seed / 100 % 100, seed % 100)
+}
+
+/// Seeds the generator for a daily-challenge level.
+///
+/// The run draws both the layout for `level_idx` and its in-level events (UFO
+/// timing and position, drops, misjumps) from the returned generator, so they are
+/// the same for everyone playing the challenge with `seed`.
+pub fn seed_daily_level(seed: u64, level_idx: usize) -> GameRng {
+    GameRng::new(seed ^ (level_idx as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daily_seed_is_stable_within_a_day() {
+        // 2023-11-14 00:00:00 UTC
+        let midnight = 1_699_920_000;
+        assert_eq!(date_seed(midnight), 20231114);
+        assert_eq!(date_seed(midnight + 12 * 3600), 20231114);
+        assert_eq!(date_seed(midnight + SECONDS_PER_DAY - 1), 20231114);
+    }
+
+    #[test]
+    fn test_daily_seed_changes_across_days() {
+        let midnight = 1_699_920_000;
+        assert_eq!(date_seed(midnight - 1), 20231113);
+        assert_eq!(date_seed(midnight + SECONDS_PER_DAY), 20231115);
+        // Month, leap-day and year rollovers
+        assert_eq!(date_seed(1_709_164_800), 20240229);
+        assert_eq!(date_seed(1_709_251_200), 20240301);
+        assert_eq!(date_seed(1_704_067_199), 20231231);
+        assert_eq!(date_seed(1_704_067_200), 20240101);
+    }
+
+    #[test]
+    fn test_daily_level_rolls_match_for_every_player() {
+        let mut a = seed_daily_level(20240229, 2);
+        let mut b = seed_daily_level(20240229, 2);
+        for _ in 0..20 {
+            assert_eq!(a.gen_range_f32(0.0, 1.0), b.gen_range_f32(0.0, 1.0));
+        }
+        assert_ne!(seed_daily_level(20240229, 3).next_u64(), seed_daily_level(20240229, 2).next_u64());
+    }
+
+    #[test]
+    fn test_format_daily_seed() {
+        assert_eq!(format_daily_seed(20240229), "2024-02-29");
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::VecDeque;
+
+/// Seconds of recent play the director judges the player on.
+pub const DIRECTOR_WINDOW: f32 = 30.0;
+/// Seconds at the start of a run before the director starts adjusting anything.
+pub const DIRECTOR_WARMUP: f32 = 10.0;
+/// Score per second that counts as playing strongly (with full lives this keeps spawns at 1.25x).
+pub const STRONG_SCORE_RATE: f32 = 40.0;
+/// How much each life lost within the window lowers the multiplier.
+pub const DEATH_RELIEF: f32 = 0.25;
+/// Bounds on the UFO spawn multiplier, so adapting never trivializes or swamps a run.
+pub const SPAWN_MULTIPLIER_RANGE: (f32, f32) = (0.5, 1.75);
+
+/// How the player has been doing over the last `DIRECTOR_WINDOW` seconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Performance {
+    /// Points per second.
+    pub score_rate: f32,
+    /// Lives left as a fraction of the starting lives (above 1.0 with extra lives).
+    pub lives_fraction: f32,
+    /// Lives lost within the window.
+    pub recent_deaths: u32,
+}
+
+/// UFO spawn multiplier for a player's recent `performance`.
+///
+/// Scoring at `STRONG_SCORE_RATE` with full lives gives 1.25x, scaling up to the
+/// maximum for twice that pace; a quiet spell or missing lives drift it below 1.0,
+/// and every recent death takes off `DEATH_RELIEF` more.
+pub fn spawn_multiplier(performance: &Performance) -> f32 {
+    let pace = (performance.score_rate / STRONG_SCORE_RATE).clamp(0.0, 2.0);
+    let form = pace * performance.lives_fraction.clamp(0.0, 1.0);
+    let multiplier = 0.75 + 0.5 * form - DEATH_RELIEF * performance.recent_deaths as f32;
+    multiplier.clamp(SPAWN_MULTIPLIER_RANGE.0, SPAWN_MULTIPLIER_RANGE.1)
+}
+
+/// Watches score and lives during a run to rubber-band the UFO spawn chance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdaptiveDirector {
+    elapsed: f32,
+    /// (time, score) taken about once a second, oldest first, covering the window.
+    samples: VecDeque<(f32, u32)>,
+    /// When each recent life was lost.
+    deaths: VecDeque<f32>,
+    last_lives: i32,
+}
+
+impl AdaptiveDirector {
+    /// Starts watching a run that begins with `lives`.
+    pub fn new(lives: i32) -> Self {
+        AdaptiveDirector { elapsed: 0.0, samples: VecDeque::new(), deaths: VecDeque::new(), last_lives: lives }
+    }
+
+    /// Records `dt` seconds of play ending with `score` and `lives` left across the team.
+    pub fn update(&mut self, dt: f32, score: u32, lives: i32) {
+        self.elapsed += dt.max(0.0);
+        for _ in lives..self.last_lives {
+            self.deaths.push_back(self.elapsed);
+        }
+        self.last_lives = lives;
+
+        if self.samples.back().is_none_or(|(t, _)| self.elapsed - t >= 1.0) {
+            self.samples.push_back((self.elapsed, score));
+        }
+        let cutoff = self.elapsed - DIRECTOR_WINDOW;
+        while self.samples.len() > 1 && self.samples[0].0 < cutoff {
+            self.samples.pop_front();
+        }
+        while self.deaths.front().is_some_and(|t| *t < cutoff) {
+            self.deaths.pop_front();
+        }
+    }
+
+    /// Recent form, with lives measured against `start_lives`.
+    pub fn performance(&self, start_lives: i32) -> Performance {
+        let score_rate = match (self.samples.front(), self.samples.back()) {
+            (Some((t0, s0)), Some((t1, s1))) if t1 > t0 => s1.saturating_sub(*s0) as f32 / (t1 - t0),
+            _ => 0.0,
+        };
+        Performance {
+            score_rate,
+            lives_fraction: self.last_lives as f32 / start_lives.max(1) as f32,
+            recent_deaths: self.deaths.len() as u32,
+        }
+    }
+
+    /// Multiplier for the UFO spawn chance: 1.0 until the warm-up is over.
+    pub fn multiplier(&self, start_lives: i32) -> f32 {
+        if self.elapsed < DIRECTOR_WARMUP {
+            return 1.0;
+        }
+        spawn_multiplier(&self.performance(start_lives))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dominating_player_gets_more_ufos_within_bounds() {
+        let strong = Performance { score_rate: STRONG_SCORE_RATE, lives_fraction: 1.0, recent_deaths: 0 };
+        assert!(spawn_multiplier(&strong) > 1.0);
+        let dominating = Performance { score_rate: STRONG_SCORE_RATE * 10.0, lives_fraction: 2.0, recent_deaths: 0 };
+        assert_eq!(spawn_multiplier(&dominating), SPAWN_MULTIPLIER_RANGE.1);
+        // The same pace on a last life doesn't push as hard
+        let hanging_on = Performance { lives_fraction: 0.34, ..strong };
+        assert!(spawn_multiplier(&hanging_on) < spawn_multiplier(&strong));
+    }
+
+    #[test]
+    fn test_struggling_player_gets_fewer_ufos_within_bounds() {
+        let struggling = Performance { score_rate: 5.0, lives_fraction: 0.34, recent_deaths: 2 };
+        assert!(spawn_multiplier(&struggling) < 1.0);
+        let collapsing = Performance { score_rate: 0.0, lives_fraction: 0.0, recent_deaths: 5 };
+        assert_eq!(spawn_multiplier(&collapsing), SPAWN_MULTIPLIER_RANGE.0);
+        // A death still counts against a fast scorer
+        let reckless = Performance { score_rate: STRONG_SCORE_RATE, lives_fraction: 1.0, recent_deaths: 1 };
+        assert!(spawn_multiplier(&reckless) < 1.0 + f32::EPSILON);
+    }
+
+    #[test]
+    fn test_director_tracks_recent_score_and_deaths() {
+        let mut director = AdaptiveDirector::new(3);
+        assert_eq!(director.multiplier(3), 1.0);
+        let mut score = 0;
+        for _ in 0..(20 * 60) {
+            score += 1;
+            director.update(1.0 / 60.0, score, 3);
+        }
+        let form = director.performance(3);
+        assert!((form.score_rate - 60.0).abs() < 1.0, "rate {}", form.score_rate);
+        assert!(director.multiplier(3) > 1.0);
+
+        director.update(1.0 / 60.0, score, 2);
+        assert_eq!(director.performance(3).recent_deaths, 1);
+        // The death ages out of the window, and an extra life isn't a death
+        for _ in 0..(31 * 60) {
+            director.update(1.0 / 60.0, score, 3);
+        }
+        let form = director.performance(3);
+        assert_eq!(form.recent_deaths, 0);
+        assert_eq!(form.score_rate, 0.0);
+    }
+}
@@ -0,0 +1,156 @@
+use macroquad::logging::info;
+use std::fmt;
+use std::fs;
+
+/// Where the finished run's event log is written.
+pub const EVENT_LOG_PATH: &str = "events.log";
+
+/// Something notable that happened during a run.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    ShotFired { player: usize },
+    AsteroidDestroyed { radius: f32 },
+    UfoDestroyed,
+    BossDestroyed,
+    /// A ship lost a life (shields that absorb a hit don't count).
+    PlayerHit { player: usize },
+    BombDetonated { player: usize },
+    ExtraLife { lives: i32 },
+    /// `level` is the 1-based level (or wave) just cleared.
+    LevelComplete { level: usize },
+    RunEnded { score: u32 },
+}
+
+impl GameEvent {
+    /// Parses the text written by `Display` (variant name, then `key=value` fields).
+    // Logs are only written during play; reading them back is for replay and analysis tools
+    #[allow(dead_code)]
+    pub fn from_str(input: &str) -> Result<Self, String> {
+        let mut parts = input.split_whitespace();
+        let name = parts.next().ok_or("empty event")?;
+        let fields: Vec<(&str, &str)> = parts.filter_map(|p| p.split_once('=')).collect();
+        let field = |key: &str| -> Result<&str, String> {
+            fields
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| *v)
+                .ok_or_else(|| format!("{} is missing '{}'", name, key))
+        };
+        let invalid = |key: &str| format!("{} has an invalid '{}'", name, key);
+        Ok(match name {
+            "ShotFired" => GameEvent::ShotFired { player: field("player")?.parse().map_err(|_| invalid("player"))? },
+            "AsteroidDestroyed" => GameEvent::AsteroidDestroyed { radius: field("radius")?.parse().map_err(|_| invalid("radius"))? },
+            "UfoDestroyed" => GameEvent::UfoDestroyed,
+            "BossDestroyed" => GameEvent::BossDestroyed,
+            "PlayerHit" => GameEvent::PlayerHit { player: field("player")?.parse().map_err(|_| invalid("player"))? },
+            "BombDetonated" => GameEvent::BombDetonated { player: field("player")?.parse().map_err(|_| invalid("player"))? },
+            "ExtraLife" => GameEvent::ExtraLife { lives: field("lives")?.parse().map_err(|_| invalid("lives"))? },
+            "LevelComplete" => GameEvent::LevelComplete { level: field("level")?.parse().map_err(|_| invalid("level"))? },
+            "RunEnded" => GameEvent::RunEnded { score: field("score")?.parse().map_err(|_| invalid("score"))? },
+            _ => return Err(format!("unknown event '{}'", name)),
+        })
+    }
+}
+
+impl fmt::Display for GameEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameEvent::ShotFired { player } => write!(f, "ShotFired player={}", player),
+            GameEvent::AsteroidDestroyed { radius } => write!(f, "AsteroidDestroyed radius={}", radius),
+            GameEvent::UfoDestroyed => write!(f, "UfoDestroyed"),
+            GameEvent::BossDestroyed => write!(f, "BossDestroyed"),
+            GameEvent::PlayerHit { player } => write!(f, "PlayerHit player={}", player),
+            GameEvent::BombDetonated { player } => write!(f, "BombDetonated player={}", player),
+            GameEvent::ExtraLife { lives } => write!(f, "ExtraLife lives={}", lives),
+            GameEvent::LevelComplete { level } => write!(f, "LevelComplete level={}", level),
+            GameEvent::RunEnded { score } => write!(f, "RunEnded score={}", score),
+        }
+    }
+}
+
+/// Timestamped events of the current run, for post-run analysis.
+///
+/// Dumped as one `<seconds> <event>` line per event, oldest first.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EventLog {
+    /// Gameplay seconds since the run started, with the event.
+    pub events: Vec<(f64, GameEvent)>,
+}
+
+impl EventLog {
+    pub fn push(&mut self, time: f64, event: GameEvent) {
+        self.events.push((time, event));
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    #[allow(dead_code)]
+    pub fn from_str(input: &str) -> Result<Self, String> {
+        let mut log = EventLog::default();
+        for line in input.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (time, event) = line.split_once(' ').ok_or_else(|| format!("malformed line '{}'", line))?;
+            let time = time.parse::<f64>().map_err(|_| format!("invalid time '{}'", time))?;
+            log.push(time, GameEvent::from_str(event)?);
+        }
+        Ok(log)
+    }
+
+    pub fn dump_to_file(&self, path: &str) -> Result<(), String> {
+        if let Err(e) = fs::write(path, self.to_string()) {
+            return Err(format!("Failed to write event log to {}: {}", path, e));
+        }
+        info!("Event log ({} events) written to {}", self.events.len(), path);
+        Ok(())
+    }
+}
+
+impl fmt::Display for EventLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (time, event) in &self.events {
+            writeln!(f, "{:.3} {}", time, event)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_are_kept_in_order() {
+        let mut log = EventLog::default();
+        log.push(0.5, GameEvent::ShotFired { player: 0 });
+        log.push(0.75, GameEvent::AsteroidDestroyed { radius: 40.0 });
+        log.push(2.0, GameEvent::LevelComplete { level: 1 });
+        let names: Vec<String> = log.events.iter().map(|(_, e)| e.to_string()).collect();
+        assert_eq!(names, ["ShotFired player=0", "AsteroidDestroyed radius=40", "LevelComplete level=1"]);
+        log.clear();
+        assert!(log.events.is_empty());
+    }
+
+    #[test]
+    fn test_event_log_round_trip() {
+        let mut log = EventLog::default();
+        log.push(0.016, GameEvent::ShotFired { player: 1 });
+        log.push(1.25, GameEvent::AsteroidDestroyed { radius: 12.5 });
+        log.push(2.0, GameEvent::UfoDestroyed);
+        log.push(3.0, GameEvent::BossDestroyed);
+        log.push(4.5, GameEvent::PlayerHit { player: 0 });
+        log.push(5.0, GameEvent::BombDetonated { player: 1 });
+        log.push(6.0, GameEvent::ExtraLife { lives: 4 });
+        log.push(7.0, GameEvent::LevelComplete { level: 3 });
+        log.push(8.0, GameEvent::RunEnded { score: 12340 });
+        let text = log.to_string();
+        assert!(text.starts_with("0.016 ShotFired player=1\n"));
+        assert_eq!(EventLog::from_str(&text), Ok(log));
+        assert!(EventLog::from_str("1.0 Teleported").is_err());
+        assert!(EventLog::from_str("1.0 ExtraLife lives=lots").is_err());
+    }
+}
@@ -0,0 +1,210 @@
+use macroquad::logging::{error, info, warn};
+use macroquad::prelude::*;
+use std::fmt;
+use std::fs;
+use crate::daily::format_daily_seed;
+
+/// Ghost file format version written in the header.
+const GHOST_VERSION: u32 = 1;
+/// Seconds between recorded poses.
+pub const GHOST_SAMPLE_INTERVAL: f32 = 0.1;
+/// Moves longer than this between samples are wraps or hyperspace jumps, not flights.
+const GHOST_SNAP_DISTANCE: f32 = 100.0;
+
+/// File a new daily-challenge best is exported to as a ghost.
+pub fn ghost_path(seed: u64) -> String {
+    format!("ghost_{}.txt", seed)
+}
+
+/// File a shared ghost is imported from, kept apart from the player's own export.
+pub fn ghost_import_path(seed: u64) -> String {
+    format!("ghost_{}_import.txt", seed)
+}
+
+/// Ship position and heading at one sample.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GhostPose {
+    pub pos: Vec2,
+    pub rotation: f32,
+}
+
+/// A recorded run that can be shared and raced against.
+///
+/// Poses are sampled every `interval` seconds from the start of the run; `seed`
+/// identifies the daily challenge the run was played on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GhostData {
+    pub seed: u64,
+    pub score: u32,
+    pub interval: f32,
+    pub poses: Vec<GhostPose>,
+}
+
+impl GhostData {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, score: 0, interval: GHOST_SAMPLE_INTERVAL, poses: Vec::new() }
+    }
+
+    /// Checks the ghost was recorded on the challenge with `seed`.
+    pub fn check_seed(&self, seed: u64) -> Result<(), String> {
+        if self.seed != seed {
+            return Err(format!(
+                "ghost is from the {} challenge, not {}",
+                format_daily_seed(self.seed),
+                format_daily_seed(seed)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Interpolated pose `t` seconds into the run, or `None` once the run has ended.
+    pub fn pose_at(&self, t: f32) -> Option<GhostPose> {
+        if t < 0.0 || self.poses.is_empty() {
+            return None;
+        }
+        let index = (t / self.interval) as usize;
+        let a = *self.poses.get(index)?;
+        let Some(b) = self.poses.get(index + 1) else { return Some(a) };
+        if a.pos.distance(b.pos) > GHOST_SNAP_DISTANCE {
+            return Some(a);
+        }
+        let f = t / self.interval - index as f32;
+        Some(GhostPose { pos: a.pos.lerp(b.pos, f), rotation: a.rotation + (b.rotation - a.rotation) * f })
+    }
+
+    pub fn from_str(input: &str) -> Result<Self, String> {
+        let mut version = None;
+        let mut seed = None;
+        let mut ghost = GhostData::new(0);
+        for line in input.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(|| format!("malformed line '{}'", line))?;
+            match key {
+                "version" => version = value.parse::<u32>().ok(),
+                "seed" => seed = value.parse::<u64>().ok(),
+                "score" => ghost.score = value.parse().map_err(|_| format!("invalid score '{}'", value))?,
+                "interval" => ghost.interval = value.parse().map_err(|_| format!("invalid interval '{}'", value))?,
+                "pose" => {
+                    let parts: Vec<f32> = value.split(',').filter_map(|v| v.trim().parse().ok()).collect();
+                    let [x, y, rotation] = parts[..] else {
+                        return Err(format!("invalid pose '{}'", value));
+                    };
+                    ghost.poses.push(GhostPose { pos: vec2(x, y), rotation });
+                }
+                _ => {}
+            }
+        }
+        if version != Some(GHOST_VERSION) {
+            return Err("not a version 1 ghost file".to_string());
+        }
+        ghost.seed = seed.ok_or("ghost has no seed")?;
+        if !ghost.interval.is_finite() || ghost.interval <= 0.0 {
+            return Err("ghost sample interval must be positive".to_string());
+        }
+        Ok(ghost)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|e| {
+            warn!("Ghost file {} unreadable: {}", path, e);
+            format!("Failed to read ghost: {}", e)
+        })?;
+        let ghost = GhostData::from_str(&contents)?;
+        info!("Ghost loaded from {} ({} poses, score {})", path, ghost.poses.len(), ghost.score);
+        Ok(ghost)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        if let Err(e) = fs::write(path, self.to_string()) {
+            error!("Failed to save ghost to {}: {}", path, e);
+            return Err(format!("Failed to write ghost: {}", e));
+        }
+        info!("Ghost saved to {}", path);
+        Ok(())
+    }
+}
+
+impl fmt::Display for GhostData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# rust_asteroids ghost")?;
+        writeln!(f, "version={}", GHOST_VERSION)?;
+        writeln!(f, "seed={}", self.seed)?;
+        writeln!(f, "score={}", self.score)?;
+        writeln!(f, "interval={}", self.interval)?;
+        for pose in &self.poses {
+            writeln!(f, "pose={:.1},{:.1},{:.3}", pose.pos.x, pose.pos.y, pose.rotation)?;
+        }
+        Ok(())
+    }
+}
+
+/// Samples the player's pose at a fixed interval while a run is played.
+pub struct GhostRecorder {
+    elapsed: f32,
+    data: GhostData,
+}
+
+impl GhostRecorder {
+    pub fn new(seed: u64) -> Self {
+        Self { elapsed: 0.0, data: GhostData::new(seed) }
+    }
+
+    /// Seconds of the run recorded so far.
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    /// Advances the run clock by `dt`, recording a pose for every sample time reached.
+    pub fn record(&mut self, dt: f32, pos: Vec2, rotation: f32) {
+        while self.data.poses.len() as f32 * self.data.interval <= self.elapsed {
+            self.data.poses.push(GhostPose { pos, rotation });
+        }
+        self.elapsed += dt.max(0.0);
+    }
+
+    /// The recorded run with its final score.
+    pub fn finish(&self, score: u32) -> GhostData {
+        GhostData { score, ..self.data.clone() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ghost_round_trip() {
+        let mut recorder = GhostRecorder::new(20261016);
+        for i in 0..10 {
+            recorder.record(0.05, vec2(100. + i as f32 * 2.5, 200.5), 0.25 * i as f32);
+        }
+        let ghost = recorder.finish(1500);
+        assert_eq!(ghost.poses.len(), 5);
+        assert_eq!(GhostData::from_str(&ghost.to_string()), Ok(ghost.clone()));
+
+        // Halfway between the first two samples
+        let pose = ghost.pose_at(0.05).unwrap();
+        assert!((pose.pos.x - 102.5).abs() < 1e-4);
+        assert!(ghost.pose_at(10.0).is_none());
+    }
+
+    #[test]
+    fn test_rejects_mismatched_seed() {
+        let ghost = GhostData::new(20261015);
+        assert!(ghost.check_seed(20261015).is_ok());
+        assert_eq!(ghost_path(20261015), "ghost_20261015.txt");
+        assert_eq!(ghost_import_path(20261015), "ghost_20261015_import.txt");
+        let err = ghost.check_seed(20261016).unwrap_err();
+        assert_eq!(err, "ghost is from the 2026-10-15 challenge, not 2026-10-16");
+    }
+
+    #[test]
+    fn test_rejects_malformed_files() {
+        assert!(GhostData::from_str("seed=1\n").is_err());
+        assert!(GhostData::from_str("version=1\n").is_err());
+        assert!(GhostData::from_str("version=1\nseed=1\npose=1,2\n").is_err());
+    }
+}
@@ -0,0 +1,79 @@
+use macroquad::rand::gen_range;
+
+/// Seconds the HUD stays scrambled once a magnetic storm hits.
+pub const STORM_DURATION: f32 = 4.0;
+/// Range (seconds of gameplay) before the next storm strikes within a level.
+pub const STORM_INTERVAL: (f32, f32) = (20.0, 45.0);
+
+/// Magnetic storm hazard: periodically scrambles the HUD and knocks out the scanner.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MagneticStorm {
+    /// Gameplay seconds until the next storm starts.
+    next_storm_in: f32,
+    /// Remaining seconds of the current storm (0 when calm).
+    hud_scramble_timer: f32,
+}
+
+impl MagneticStorm {
+    /// Creates a calm storm tracker with the first storm `first_in` seconds away.
+    pub fn new(first_in: f32) -> Self {
+        Self { next_storm_in: first_in, hud_scramble_timer: 0.0 }
+    }
+
+    /// Calms any storm and schedules a random first storm for a freshly loaded level.
+    pub fn reset(&mut self) {
+        *self = Self::new(gen_range(STORM_INTERVAL.0, STORM_INTERVAL.1));
+    }
+
+    /// Advances the storm clock. Returns true on the frame a storm begins.
+    pub fn update(&mut self, dt: f32) -> bool {
+        if self.hud_scramble_timer > 0.0 {
+            self.hud_scramble_timer = (self.hud_scramble_timer - dt).max(0.0);
+            if self.hud_scramble_timer == 0.0 {
+                self.next_storm_in = gen_range(STORM_INTERVAL.0, STORM_INTERVAL.1);
+            }
+            return false;
+        }
+        self.next_storm_in -= dt;
+        if self.next_storm_in <= 0.0 {
+            self.hud_scramble_timer = STORM_DURATION;
+            return true;
+        }
+        false
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.hud_scramble_timer > 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storm_starts_and_ends() {
+        let mut storm = MagneticStorm::new(1.0);
+        assert!(!storm.update(0.5));
+        assert!(!storm.is_active());
+        assert!(storm.update(0.6));
+        assert!(storm.is_active());
+        // Still active part-way through, then clears
+        assert!(!storm.update(STORM_DURATION - 0.1));
+        assert!(storm.is_active());
+        storm.update(0.2);
+        assert!(!storm.is_active());
+        // Next storm is scheduled at least the minimum interval away
+        storm.update(STORM_INTERVAL.0 - 1.0);
+        assert!(!storm.is_active());
+    }
+
+    #[test]
+    fn test_reset_calms_storm() {
+        let mut storm = MagneticStorm::new(0.0);
+        storm.update(0.1);
+        assert!(storm.is_active());
+        storm.reset();
+        assert!(!storm.is_active());
+    }
+}
@@ -0,0 +1,220 @@
+use macroquad::logging::{error, info, warn};
+use std::fmt;
+use std::fs;
+use crate::settings::Difficulty;
+
+/// Number of runs kept in the high-score table.
+pub const MAX_HIGH_SCORES: usize = 10;
+/// Longest name stored with a score.
+pub const MAX_NAME_LEN: usize = 16;
+
+/// One finished run in the high-score table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScoreEntry {
+    pub name: String,
+    pub score: u32,
+    pub difficulty: Difficulty,
+}
+
+impl ScoreEntry {
+    pub fn new(name: &str, score: u32, difficulty: Difficulty) -> Self {
+        // Names are stored one per line; keep them short and single-line
+        let name: String = name.chars().filter(|c| !c.is_control()).take(MAX_NAME_LEN).collect();
+        let name = if name.trim().is_empty() { "Player".to_string() } else { name.trim().to_string() };
+        Self { name, score, difficulty }
+    }
+}
+
+/// Name offered for a new score until the player types one: the OS user name, or "Player".
+pub fn default_player_name() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "Player".to_string())
+}
+
+/// Best scores of past runs, highest first.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HighScores {
+    pub entries: Vec<ScoreEntry>,
+}
+
+impl HighScores {
+    /// Inserts a run, keeping the table sorted (highest first) and truncated.
+    ///
+    /// Ties rank below existing entries. Returns the 0-based rank of the new
+    /// entry, or `None` if it didn't make the table.
+    pub fn insert(&mut self, entry: ScoreEntry) -> Option<usize> {
+        let rank = self.entries.iter().position(|e| entry.score > e.score).unwrap_or(self.entries.len());
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+
+    /// Enters a finished run, unless it was sandbox practice (unlimited lives never rank).
+    ///
+    /// Returns the run's rank as `insert` does.
+    pub fn record_run(&mut self, entry: ScoreEntry, sandbox: bool) -> Option<usize> {
+        if sandbox {
+            return None;
+        }
+        self.insert(entry)
+    }
+
+    /// Renames the entry at `rank`, e.g. once the player has typed their name.
+    pub fn rename(&mut self, rank: usize, name: &str) {
+        if let Some(entry) = self.entries.get_mut(rank) {
+            *entry = ScoreEntry::new(name, entry.score, entry.difficulty.clone());
+        }
+    }
+
+    /// Adds the entries of a table that finished loading after this one was in use.
+    ///
+    /// `new_rank` is where a run entered in the meantime sits; returns where it sits
+    /// now, or `None` if the loaded scores pushed it off the table.
+    pub fn merge(&mut self, loaded: HighScores, new_rank: Option<usize>) -> Option<usize> {
+        let mut new_rank = new_rank;
+        for entry in loaded.entries {
+            if let Some(rank) = self.insert(entry) {
+                new_rank = new_rank.map(|r| if rank <= r { r + 1 } else { r }).filter(|&r| r < MAX_HIGH_SCORES);
+            }
+        }
+        new_rank
+    }
+
+    pub fn from_str(input: &str) -> Self {
+        let mut scores = HighScores::default();
+        for line in input.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            // entry=score,difficulty,name (the name goes last so it may contain commas)
+            let Some(value) = line.strip_prefix("entry=") else { continue };
+            let mut parts = value.splitn(3, ',');
+            let (Some(score), Some(difficulty), Some(name)) = (parts.next(), parts.next(), parts.next()) else {
+                continue;
+            };
+            if let (Ok(score), Ok(difficulty)) = (score.trim().parse::<u32>(), difficulty.trim().parse::<u8>()) {
+                scores.insert(ScoreEntry::new(name, score, Difficulty::from_u8(difficulty)));
+            }
+        }
+        scores
+    }
+
+    pub fn load_from_file(path: &str) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                info!("High scores loaded from {}", path);
+                Ok(HighScores::from_str(&contents))
+            }
+            Err(e) => {
+                warn!("High scores not found or unreadable: {}. Starting fresh.", e);
+                Err(format!("Failed to read high scores: {}", e))
+            }
+        }
+    }
+
+    pub fn save_to_file(&self, path: &str) -> Result<(), String> {
+        if let Err(e) = fs::write(path, self.to_string()) {
+            error!("Failed to save high scores to {}: {}", path, e);
+            return Err(format!("Failed to write high scores: {}", e));
+        }
+        info!("High scores saved to {}", path);
+        Ok(())
+    }
+}
+
+impl fmt::Display for HighScores {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for e in &self.entries {
+            writeln!(f, "entry={},{},{}", e.score, e.difficulty.to_u8(), e.name)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: u32) -> ScoreEntry {
+        ScoreEntry::new(name, score, Difficulty::Normal)
+    }
+
+    #[test]
+    fn test_insert_keeps_highest_first() {
+        let mut scores = HighScores::default();
+        assert_eq!(scores.insert(entry("a", 500)), Some(0));
+        assert_eq!(scores.insert(entry("b", 1500)), Some(0));
+        assert_eq!(scores.insert(entry("c", 900)), Some(1));
+        // A tie ranks below the existing score
+        assert_eq!(scores.insert(entry("d", 900)), Some(2));
+        let order: Vec<&str> = scores.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(order, ["b", "c", "d", "a"]);
+    }
+
+    #[test]
+    fn test_insert_truncates_to_top_ten() {
+        let mut scores = HighScores::default();
+        for i in 1..=MAX_HIGH_SCORES as u32 {
+            scores.insert(entry("p", i * 100));
+        }
+        assert_eq!(scores.insert(entry("low", 50)), None);
+        assert_eq!(scores.insert(entry("high", 2000)), Some(0));
+        assert_eq!(scores.entries.len(), MAX_HIGH_SCORES);
+        assert_eq!(scores.entries.last().unwrap().score, 200);
+    }
+
+    #[test]
+    fn test_sandbox_runs_stay_off_the_table() {
+        let mut scores = HighScores::default();
+        assert_eq!(scores.record_run(entry("practice", 99_999), true), None);
+        assert!(scores.entries.is_empty());
+        assert_eq!(scores.record_run(entry("real", 100), false), Some(0));
+        assert_eq!(scores.entries.len(), 1);
+    }
+
+    #[test]
+    fn test_late_load_merges_around_the_new_run() {
+        let mut scores = HighScores::default();
+        let rank = scores.insert(entry("new", 900));
+        let mut loaded = HighScores::default();
+        for score in [2000, 900, 100] {
+            loaded.insert(entry("old", score));
+        }
+        assert_eq!(scores.merge(loaded, rank), Some(1));
+        let order: Vec<u32> = scores.entries.iter().map(|e| e.score).collect();
+        assert_eq!(order, [2000, 900, 900, 100]);
+        assert_eq!(scores.entries[1].name, "new");
+
+        // A full table of better runs pushes it off
+        let mut full = HighScores::default();
+        for i in 0..MAX_HIGH_SCORES as u32 {
+            full.insert(entry("old", 5000 + i));
+        }
+        assert_eq!(scores.merge(full, Some(1)), None);
+        assert_eq!(scores.entries.len(), MAX_HIGH_SCORES);
+    }
+
+    #[test]
+    fn test_rename_keeps_score_and_cleans_name() {
+        let mut scores = HighScores::default();
+        scores.insert(entry("Player", 700));
+        scores.rename(0, "  Zed\t");
+        assert_eq!(scores.entries[0], entry("Zed", 700));
+        scores.rename(0, "");
+        assert_eq!(scores.entries[0].name, "Player");
+    }
+
+    #[test]
+    fn test_high_scores_round_trip() {
+        let mut scores = HighScores::default();
+        scores.insert(ScoreEntry::new("Ann, the Ace", 4200, Difficulty::Hard));
+        scores.insert(ScoreEntry::new("bob\n", 300, Difficulty::Easy));
+        assert_eq!(scores.entries[1].name, "bob");
+        assert_eq!(HighScores::from_str(&scores.to_string()), scores);
+    }
+}
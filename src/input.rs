@@ -0,0 +1,288 @@
+use macroquad::prelude::*;
+
+/// Keyboard layout for one ship.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShipKeys {
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub thrust: KeyCode,
+    pub fire: KeyCode,
+    pub hyperspace: KeyCode,
+    pub brake: KeyCode,
+    pub bomb: KeyCode,
+}
+
+/// Second player's keys in co-op (clear of every single-player gameplay key).
+pub const PLAYER_TWO_KEYS: ShipKeys = ShipKeys {
+    left: KeyCode::J,
+    right: KeyCode::L,
+    thrust: KeyCode::I,
+    fire: KeyCode::K,
+    hyperspace: KeyCode::U,
+    brake: KeyCode::O,
+    bomb: KeyCode::H,
+};
+
+/// Stick deflection below this reads as centred.
+pub const STICK_DEADZONE: f32 = 0.2;
+
+/// Controller buttons the game reads, named by position (Xbox: A = South, X = West).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PadButton {
+    South,
+    East,
+    West,
+    North,
+    LeftBumper,
+    RightBumper,
+    Select,
+    Start,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl PadButton {
+    pub const ALL: [PadButton; 12] = [
+        PadButton::South,
+        PadButton::East,
+        PadButton::West,
+        PadButton::North,
+        PadButton::LeftBumper,
+        PadButton::RightBumper,
+        PadButton::Select,
+        PadButton::Start,
+        PadButton::DPadUp,
+        PadButton::DPadDown,
+        PadButton::DPadLeft,
+        PadButton::DPadRight,
+    ];
+
+    fn index(self) -> usize {
+        PadButton::ALL.iter().position(|b| *b == self).unwrap_or(0)
+    }
+}
+
+/// One frame of the first connected controller (all released when none is connected).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PadState {
+    /// Left stick horizontal axis, -1.0 (left) to 1.0 (right).
+    pub stick_x: f32,
+    held: [bool; PadButton::ALL.len()],
+    pressed: [bool; PadButton::ALL.len()],
+}
+
+// Only the gilrs backend (and the tests) fill in a PadState
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+impl PadState {
+    pub fn set_held(&mut self, button: PadButton, held: bool) {
+        self.held[button.index()] = held;
+    }
+
+    pub fn set_pressed(&mut self, button: PadButton, pressed: bool) {
+        self.pressed[button.index()] = pressed;
+    }
+
+    pub fn held(&self, button: PadButton) -> bool {
+        self.held[button.index()]
+    }
+
+    /// Went down this frame.
+    pub fn pressed(&self, button: PadButton) -> bool {
+        self.pressed[button.index()]
+    }
+
+    /// Turn input: the D-pad turns at full rate, the stick in proportion to how far it's pushed.
+    pub fn turn(&self) -> f32 {
+        match (self.held(PadButton::DPadLeft), self.held(PadButton::DPadRight)) {
+            (true, false) => -1.0,
+            (false, true) => 1.0,
+            _ => apply_deadzone(self.stick_x),
+        }
+    }
+
+    /// Whether the controller equivalent of a menu key went down this frame.
+    ///
+    /// The D-pad stands in for the arrow keys, Start/A for Enter and Select for Escape.
+    pub fn menu_pressed(&self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Up => self.pressed(PadButton::DPadUp),
+            KeyCode::Down => self.pressed(PadButton::DPadDown),
+            KeyCode::Left => self.pressed(PadButton::DPadLeft),
+            KeyCode::Right => self.pressed(PadButton::DPadRight),
+            KeyCode::Enter => self.pressed(PadButton::Start) || self.pressed(PadButton::South),
+            KeyCode::Escape => self.pressed(PadButton::Select),
+            _ => false,
+        }
+    }
+}
+
+/// Zeroes stick noise inside `STICK_DEADZONE` and rescales the rest to the full -1.0..=1.0 range.
+pub fn apply_deadzone(value: f32) -> f32 {
+    let magnitude = value.abs().min(1.0);
+    if magnitude < STICK_DEADZONE {
+        return 0.0;
+    }
+    value.signum() * (magnitude - STICK_DEADZONE) / (1.0 - STICK_DEADZONE)
+}
+
+/// Whether `key` or its controller equivalent went down this frame (menus only).
+pub fn menu_pressed(key: KeyCode, pad: &PadState) -> bool {
+    is_key_pressed(key) || pad.menu_pressed(key)
+}
+
+/// Source of controller input, polled once per frame.
+///
+/// Controllers are read through gilrs when built with the `gamepad` feature;
+/// otherwise no controller is ever reported and the keyboard is the only input.
+pub struct Gamepad {
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+}
+
+#[cfg(feature = "gamepad")]
+impl Gamepad {
+    pub fn new() -> Self {
+        let gilrs = match gilrs::Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(e) => {
+                warn!("Controller support unavailable: {}", e);
+                None
+            }
+        };
+        Gamepad { gilrs }
+    }
+
+    /// Reads the first connected controller.
+    pub fn poll(&mut self) -> PadState {
+        let mut state = PadState::default();
+        let Some(gilrs) = &mut self.gilrs else { return state };
+        let mut went_down = Vec::new();
+        while let Some(gilrs::Event { id, event, .. }) = gilrs.next_event() {
+            if let gilrs::EventType::ButtonPressed(button, _) = event {
+                went_down.push((id, button));
+            }
+        }
+        if let Some((id, pad)) = gilrs.gamepads().next() {
+            state.stick_x = pad.value(gilrs::Axis::LeftStickX);
+            for button in PadButton::ALL {
+                let native = gilrs_button(button);
+                state.set_held(button, pad.is_pressed(native));
+                state.set_pressed(button, went_down.contains(&(id, native)));
+            }
+        }
+        state
+    }
+}
+
+#[cfg(feature = "gamepad")]
+fn gilrs_button(button: PadButton) -> gilrs::Button {
+    match button {
+        PadButton::South => gilrs::Button::South,
+        PadButton::East => gilrs::Button::East,
+        PadButton::West => gilrs::Button::West,
+        PadButton::North => gilrs::Button::North,
+        PadButton::LeftBumper => gilrs::Button::LeftTrigger,
+        PadButton::RightBumper => gilrs::Button::RightTrigger,
+        PadButton::Select => gilrs::Button::Select,
+        PadButton::Start => gilrs::Button::Start,
+        PadButton::DPadUp => gilrs::Button::DPadUp,
+        PadButton::DPadDown => gilrs::Button::DPadDown,
+        PadButton::DPadLeft => gilrs::Button::DPadLeft,
+        PadButton::DPadRight => gilrs::Button::DPadRight,
+    }
+}
+
+#[cfg(not(feature = "gamepad"))]
+impl Gamepad {
+    pub fn new() -> Self {
+        Gamepad {}
+    }
+
+    /// Without the `gamepad` feature there is never a controller.
+    pub fn poll(&mut self) -> PadState {
+        PadState::default()
+    }
+}
+
+/// One frame of a player's controls, polled once and then applied.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Controls {
+    /// -1.0 (turn left) to 1.0 (turn right); partial values turn slower.
+    pub turn: f32,
+    pub thrust_held: bool,
+    pub thrust_pressed: bool,
+    pub fire: bool,
+    /// `fire` comes from holding the button under auto-fire, not a fresh press.
+    pub fire_repeat: bool,
+    pub hyperspace: bool,
+    pub brake: bool,
+    pub bomb: bool,
+}
+
+impl Controls {
+    /// Drops every control (e.g. while the ship is stunned).
+    pub fn disabled(self, off: bool) -> Self {
+        if off { Controls::default() } else { self }
+    }
+}
+
+/// Reads one ship's `bindings` together with this frame's `pad`; with `auto_fire`
+/// holding fire keeps shooting.
+///
+/// On the controller A thrusts, B brakes, X or the right bumper fires, Y jumps
+/// to hyperspace and the left bumper drops a bomb. A turn key overrides the stick.
+pub fn poll_controls(bindings: &ShipKeys, pad: &PadState, auto_fire: bool) -> Controls {
+    let mut turn = 0.0;
+    if is_key_down(bindings.left) { turn -= 1.0; }
+    if is_key_down(bindings.right) { turn += 1.0; }
+    if turn == 0.0 { turn = pad.turn(); }
+    let fire_held = is_key_down(bindings.fire) || pad.held(PadButton::West) || pad.held(PadButton::RightBumper);
+    let fire_pressed = is_key_pressed(bindings.fire) || pad.pressed(PadButton::West) || pad.pressed(PadButton::RightBumper);
+    let fire = if auto_fire { fire_held } else { fire_pressed };
+    Controls {
+        turn,
+        thrust_held: is_key_down(bindings.thrust) || pad.held(PadButton::South),
+        thrust_pressed: is_key_pressed(bindings.thrust) || pad.pressed(PadButton::South),
+        fire,
+        fire_repeat: fire && !fire_pressed,
+        hyperspace: is_key_pressed(bindings.hyperspace) || pad.pressed(PadButton::North),
+        brake: is_key_down(bindings.brake) || pad.held(PadButton::East),
+        bomb: is_key_pressed(bindings.bomb) || pad.pressed(PadButton::LeftBumper),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadzone_rescales_stick() {
+        assert_eq!(apply_deadzone(0.1), 0.0);
+        assert_eq!(apply_deadzone(-0.19), 0.0);
+        assert_eq!(apply_deadzone(1.0), 1.0);
+        assert_eq!(apply_deadzone(-1.5), -1.0);
+        assert!((apply_deadzone(0.6) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_dpad_overrides_stick_for_turning() {
+        let mut pad = PadState { stick_x: 0.6, ..PadState::default() };
+        assert!((pad.turn() - 0.5).abs() < 1e-6);
+        pad.set_held(PadButton::DPadLeft, true);
+        assert_eq!(pad.turn(), -1.0);
+    }
+
+    #[test]
+    fn test_menu_buttons_map_to_keys() {
+        let mut pad = PadState::default();
+        assert!(!pad.menu_pressed(KeyCode::Enter));
+        pad.set_pressed(PadButton::Start, true);
+        pad.set_pressed(PadButton::DPadDown, true);
+        assert!(pad.menu_pressed(KeyCode::Enter));
+        assert!(pad.menu_pressed(KeyCode::Down));
+        assert!(!pad.menu_pressed(KeyCode::Up));
+        assert!(!pad.menu_pressed(KeyCode::Escape));
+    }
+}
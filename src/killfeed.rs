@@ -0,0 +1,105 @@
+use macroquad::prelude::*;
+use std::collections::VecDeque;
+use crate::components::AsteroidSize;
+use crate::palette::ColorScheme;
+
+/// Maximum number of entries shown at once.
+pub const KILL_FEED_MAX: usize = 5;
+/// How long an entry stays on screen, in seconds.
+pub const KILL_FEED_LIFETIME: f32 = 2.5;
+
+/// A single line in the kill feed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KillFeedEntry {
+    pub label: String,
+    pub points: u32,
+    /// Seconds remaining before the entry disappears
+    pub timer: f32,
+}
+
+/// A stacked log of recent destructions and their point values.
+pub struct KillFeed {
+    entries: VecDeque<KillFeedEntry>,
+}
+
+impl KillFeed {
+    pub fn new() -> Self {
+        Self { entries: VecDeque::with_capacity(KILL_FEED_MAX) }
+    }
+
+    /// Adds a scoring event to the top of the feed, dropping the oldest line when full.
+    pub fn push(&mut self, label: &str, points: u32) {
+        if self.entries.len() >= KILL_FEED_MAX {
+            self.entries.pop_back();
+        }
+        self.entries.push_front(KillFeedEntry {
+            label: label.to_string(),
+            points,
+            timer: KILL_FEED_LIFETIME,
+        });
+    }
+
+    /// Ages entries and removes the expired ones.
+    pub fn update(&mut self, dt: f32) {
+        for e in self.entries.iter_mut() {
+            e.timer -= dt;
+        }
+        self.entries.retain(|e| e.timer > 0.0);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Draws the feed right-aligned at `right_x`, newest entry at `top_y`.
+    pub fn draw(&self, right_x: f32, top_y: f32, scheme: ColorScheme) {
+        for (i, e) in self.entries.iter().enumerate() {
+            let text = format!("{} +{}", e.label, e.points);
+            let width = measure_text(&text, None, 18, 1.0).width;
+            let mut color = YELLOW;
+            color.a = (e.timer / KILL_FEED_LIFETIME).clamp(0.0, 1.0);
+            draw_text(&text, right_x - width, top_y + i as f32 * 20., 18., scheme.apply(color));
+        }
+    }
+}
+
+/// Human-readable name for an asteroid of the given radius.
+pub fn asteroid_label(radius: f32) -> &'static str {
+    AsteroidSize::from_radius(radius).label()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_caps_length_and_keeps_newest() {
+        let mut feed = KillFeed::new();
+        for i in 0..(KILL_FEED_MAX + 3) {
+            feed.push("Asteroid", i as u32);
+        }
+        assert_eq!(feed.entries.len(), KILL_FEED_MAX);
+        assert_eq!(feed.entries.front().unwrap().points, (KILL_FEED_MAX + 2) as u32);
+    }
+
+    #[test]
+    fn test_entries_expire() {
+        let mut feed = KillFeed::new();
+        feed.push("UFO", 500);
+        feed.update(KILL_FEED_LIFETIME * 0.5);
+        feed.push("Asteroid", 100);
+        assert_eq!(feed.entries.len(), 2);
+        feed.update(KILL_FEED_LIFETIME * 0.6);
+        assert_eq!(feed.entries.len(), 1);
+        assert_eq!(feed.entries[0].label, "Asteroid");
+        feed.update(KILL_FEED_LIFETIME);
+        assert_eq!(feed.entries.len(), 0);
+    }
+
+    #[test]
+    fn test_asteroid_labels() {
+        assert_eq!(asteroid_label(45.), "Large Asteroid");
+        assert_eq!(asteroid_label(20.), "Medium Asteroid");
+        assert_eq!(asteroid_label(10.), "Small Asteroid");
+    }
+}
@@ -1,15 +1,77 @@
 use macroquad::logging::{info, warn};
 use macroquad::prelude::*;
-use macroquad::rand::gen_range;
 use crate::components::*;
-use crate::config::LevelConfig;
+use crate::boss::BossPhase;
+use crate::config::{first_level_idx, LevelConfig, LevelKind};
+use crate::rng::{GameRng, ShapeSource};
+use crate::world::GameWorld;
+
+/// Range of the sideways kick (pixels per step) each half gets when an asteroid splits.
+const SPLIT_SCATTER: (f32, f32) = (0.5, 2.0);
+/// Fastest an asteroid spins either way, in degrees per second.
+pub const MAX_ASTEROID_SPIN: f32 = 90.0;
+
+/// A random spin for a new asteroid, in degrees per second.
+pub fn random_spin(rng: &mut GameRng) -> f32 {
+    rng.gen_range_f32(-MAX_ASTEROID_SPIN, MAX_ASTEROID_SPIN)
+}
+
+/// Picks a spawn point just outside a random screen edge and a velocity aimed into the interior.
+///
+/// Returns `(position, velocity)`; `speed` is the velocity magnitude.
+pub fn edge_spawn(bounds: Vec2, radius: f32, speed: f32, rng: &mut GameRng) -> (Vec2, Vec2) {
+    let (w, h) = (bounds.x, bounds.y);
+    let pos = match rng.gen_range_u32(0, 4) {
+        0 => vec2(-radius, rng.gen_range_f32(0., h)),
+        1 => vec2(w + radius, rng.gen_range_f32(0., h)),
+        2 => vec2(rng.gen_range_f32(0., w), -radius),
+        _ => vec2(rng.gen_range_f32(0., w), h + radius),
+    };
+    // Aim somewhere in the middle half of the screen so the rock fully enters
+    let target = vec2(rng.gen_range_f32(w * 0.25, w * 0.75), rng.gen_range_f32(h * 0.25, h * 0.75));
+    (pos, (target - pos).normalize_or_zero() * speed)
+}
+
+/// Breaks a destroyed asteroid into `AsteroidSize::splits_into` fragments of half its radius.
+///
+/// Each fragment leaves at the parent's velocity plus a kick, spread evenly around
+/// the circle starting across its path, so the pieces fly apart while keeping the
+/// parent's momentum. Each gets its own random spin and the hit points for its
+/// size. Small rocks leave no debris.
+pub fn split_asteroid(parent: &Asteroid, shapes: &mut ShapeSource, rng: &mut GameRng) -> Vec<Asteroid> {
+    let pieces = parent.size().splits_into();
+    if pieces == 0 {
+        return Vec::new();
+    }
+    // Across the direction of travel; any direction for a rock at rest
+    let across = parent
+        .vel
+        .try_normalize()
+        .map(|dir| dir.perp())
+        .unwrap_or_else(|| Vec2::from_angle(rng.gen_range_f32(0., std::f32::consts::TAU)));
+    let scatter = across * rng.gen_range_f32(SPLIT_SCATTER.0, SPLIT_SCATTER.1);
+    (0..pieces)
+        .map(|i| Vec2::from_angle(std::f32::consts::TAU * i as f32 / pieces as f32).rotate(scatter))
+        .map(|kick| Asteroid {
+            vel: parent.vel + kick,
+            spin: random_spin(rng),
+            id: parent.id,
+            ..Asteroid::new(parent.pos, parent.radius / 2.0, shapes.next_sides())
+        })
+        .collect()
+}
 
 /// Resets and populates the level with asteroids based on the provided configuration.
 /// 
-/// This clears existing asteroids/UFOs and spawns new asteroids at safe distances from the center.
-pub fn load_level(config: &LevelConfig, asteroids: &mut Vec<Asteroid>, ufos: &mut Vec<Ufo>) {
-    asteroids.clear();
-    ufos.clear(); 
+/// This clears existing asteroids/UFOs/bosses and spawns new asteroids at safe distances from the center,
+/// or, with `edge_entry`, just off-screen flying inwards.
+/// Boss levels additionally spawn their boss near the top of the screen.
+pub fn load_level(config: &LevelConfig, world: &mut GameWorld, edge_entry: bool, shapes: &mut ShapeSource, rng: &mut GameRng) {
+    let bounds = world.bounds;
+    world.asteroids.clear();
+    world.ufos.clear();
+    world.bosses.clear();
+    shapes.reset();
     info!("Loading level: asteroids={}, speed_range=({:.1},{:.1}), size_mult={:.2}",
         config.asteroid_count,
         config.asteroid_speed_range.0,
@@ -18,12 +80,29 @@ pub fn load_level(config: &LevelConfig, asteroids: &mut Vec<Asteroid>, ufos: &mu
     );
     
     for _ in 0..config.asteroid_count {
+        let radius = rng.gen_range_f32(30., 50.) * config.asteroid_size_mult;
+        let hp = AsteroidSize::from_radius(radius).hp().max(config.asteroid_hp);
+        if edge_entry {
+            // Interior spawns draw a speed per axis; match their typical overall speed
+            let speed = rng.gen_range_f32(config.asteroid_speed_range.0, config.asteroid_speed_range.1) * std::f32::consts::SQRT_2;
+            let (pos, vel) = edge_spawn(bounds.size(), radius, speed, rng);
+            world.asteroids.push(Asteroid {
+                vel,
+                entering: true,
+                hp,
+                max_hp: hp,
+                spin: random_spin(rng),
+                ..Asteroid::new(pos, radius, shapes.next_sides())
+            });
+            continue;
+        }
+
         let mut pos;
         // Ensure asteroids don't spawn on top of the player (center screen)
         let mut attempts = 0;
         loop {
-            pos = vec2(gen_range(0., screen_width()), gen_range(0., screen_height()));
-            if pos.distance(vec2(screen_width()/2., screen_height()/2.)) > 150. {
+            pos = bounds.random_pos(rng);
+            if pos.distance(bounds.center()) > 150. {
                 break;
             }
             attempts += 1;
@@ -33,16 +112,157 @@ pub fn load_level(config: &LevelConfig, asteroids: &mut Vec<Asteroid>, ufos: &mu
             }
         }
         
-        asteroids.push(Asteroid {
-            pos,
+        world.asteroids.push(Asteroid {
             vel: vec2(
-                gen_range(config.asteroid_speed_range.0, config.asteroid_speed_range.1) * if gen_range(0, 2) == 0 { 1. } else { -1. },
-                gen_range(config.asteroid_speed_range.0, config.asteroid_speed_range.1) * if gen_range(0, 2) == 0 { 1. } else { -1. }
+                rng.gen_range_f32(config.asteroid_speed_range.0, config.asteroid_speed_range.1) * rng.gen_sign(),
+                rng.gen_range_f32(config.asteroid_speed_range.0, config.asteroid_speed_range.1) * rng.gen_sign()
             ),
-            radius: gen_range(30., 50.) * config.asteroid_size_mult,
+            hp,
+            max_hp: hp,
+            spin: random_spin(rng),
+            ..Asteroid::new(pos, radius, shapes.next_sides())
+        });
+    }
+    if let LevelKind::Boss { hp } = config.kind {
+        world.bosses.push(Boss {
+            pos: vec2(bounds.width / 2., 80.),
+            prev_pos: vec2(bounds.width / 2., 80.),
+            vel: vec2(config.ufo_speed.max(1.0), 0.),
+            radius: 45.,
             active: true,
-            sides: gen_range(5, 9),
+            hp,
+            max_hp: hp,
+            shoot_timer: 0.,
+            phase: BossPhase::Sweeping,
+            phase_timer: 0.,
         });
+        info!("Boss spawned with {} hp", hp);
+    }
+    info!("Level loaded. Asteroids spawned: {}", world.asteroids.len());
+}
+
+/// Where a run is in the level sequence; `GameWorld::step` moves it on as fields are cleared.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Campaign {
+    /// Configured level being played (the last one cleared once endless waves take over).
+    pub level_idx: usize,
+    /// Procedural wave being played (`None` while still in the configured levels).
+    pub endless_wave: Option<usize>,
+    /// Boss rush: boss levels back-to-back against the clock.
+    pub boss_rush: bool,
+    /// Seconds on the boss rush clock.
+    pub boss_rush_time: f32,
+    pub boss_rush_cleared: usize,
+    /// Countdown (off the clock) before the next boss rush level loads.
+    pub boss_rush_breather: Option<f32>,
+}
+
+impl Campaign {
+    /// A new run from the first level, or the first boss level for a boss rush.
+    pub fn start(levels: &[LevelConfig], boss_rush: bool) -> Self {
+        Campaign { level_idx: first_level_idx(levels, boss_rush).unwrap_or(0), boss_rush, ..Default::default() }
+    }
+
+    /// Number of the level or wave being played, with endless waves counting on from the configured levels.
+    pub fn level_number(&self, levels: &[LevelConfig]) -> usize {
+        self.endless_wave.map_or(self.level_idx, |wave| levels.len() + wave) + 1
+    }
+}
+
+/// Puts the team at the start of a level: ships recentred (then moved to open space
+/// if the fresh field crowds them), shots and pickups cleared and `config` spawned anew.
+///
+/// Used both to advance to the next level and to restart the current one.
+pub fn start_level(world: &mut GameWorld, config: &LevelConfig, coop: bool, edge_entry: bool, shapes: &mut ShapeSource, rng: &mut GameRng, min_clearance: f32) {
+    world.respawn_team(coop);
+    world.bullets.clear();
+    world.powerups.clear();
+    load_level(config, world, edge_entry, shapes, rng);
+    world.move_team_to_safety(min_clearance);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{rock, test_world};
+    use crate::world::FIXED_DT;
+
+    #[test]
+    fn test_edge_spawn_starts_off_screen_and_moves_inward() {
+        let bounds = vec2(800., 600.);
+        let center = bounds / 2.;
+        let mut rng = GameRng::new(1534);
+        for _ in 0..50 {
+            let (pos, vel) = edge_spawn(bounds, 40., 2., &mut rng);
+            let off_screen = pos.x <= 0. || pos.x >= bounds.x || pos.y <= 0. || pos.y >= bounds.y;
+            assert!(off_screen, "spawned inside the screen at {:?}", pos);
+            assert!((pos + vel).distance(center) < pos.distance(center));
+        }
+    }
+
+    #[test]
+    fn test_entering_asteroid_flies_in_then_wraps() {
+        let mut world = test_world();
+        world.asteroids.push(Asteroid { vel: vec2(4., 0.), entering: true, ..Asteroid::new(vec2(-40., 300.), 40., 6) });
+        world.update_world(FIXED_DT);
+        // Not wrapped to the far side while entering
+        assert!(world.asteroids[0].pos.x < 0.);
+        assert!(world.asteroids[0].entering);
+        for _ in 0..30 {
+            world.update_world(FIXED_DT);
+        }
+        assert!(!world.asteroids[0].entering);
+    }
+
+    #[test]
+    fn test_split_conserves_momentum() {
+        let mut shapes = ShapeSource::new(None);
+        let mut rng = GameRng::new(7);
+        for vel in [vec2(3., -1.), vec2(-0.5, 2.5), Vec2::ZERO] {
+            let parent = Asteroid { active: false, ..rock(vec2(200., 200.), vel, 40.) };
+            let halves = split_asteroid(&parent, &mut shapes, &mut rng);
+            assert_eq!(halves.len(), 2);
+            // Each half carries half of the parent's mass
+            let half_mass = parent.body().mass / 2.;
+            let momentum: Vec2 = halves.iter().map(|h| h.vel * half_mass).sum();
+            assert!(momentum.distance(parent.vel * parent.body().mass) < 1e-2);
+            // ...but they still fly apart
+            assert!(halves[0].vel.distance(halves[1].vel) >= 2. * SPLIT_SCATTER.0 - 1e-4);
+            assert!(halves.iter().all(|h| h.radius == 20. && h.active && (5..=8).contains(&h.sides)));
+        }
+    }
+
+    #[test]
+    fn test_same_seed_gives_same_layout() {
+        let config = LevelConfig { asteroid_count: 6, ..LevelConfig::default() };
+        let layout = |seed: u64, edge_entry: bool| {
+            let mut world = test_world();
+            load_level(&config, &mut world, edge_entry, &mut ShapeSource::new(Some(seed)), &mut GameRng::new(seed));
+            world.asteroids.iter().map(|a| (a.pos, a.vel, a.radius, a.sides)).collect::<Vec<_>>()
+        };
+        for edge_entry in [false, true] {
+            assert_eq!(layout(1534, edge_entry), layout(1534, edge_entry));
+            assert_ne!(layout(1534, edge_entry), layout(1535, edge_entry));
+        }
+    }
+
+    #[test]
+    fn test_start_level_resets_the_field() {
+        let mut world = test_world();
+        world.players[0].pos = vec2(10., 10.);
+        world.players[0].vel = vec2(3., 0.);
+        world.bullets.push(Bullet::new(Vec2::ZERO, Vec2::ZERO, 1.0, BulletOwner::Ufo));
+        let config = LevelConfig { asteroid_count: 4, ..LevelConfig::default() };
+        start_level(&mut world, &config, true, false, &mut ShapeSource::new(None), &mut GameRng::new(1536), 0.);
+        assert_eq!(world.players.len(), 2);
+        assert!(world.players.iter().all(|p| p.vel == Vec2::ZERO && p.pos.distance(world.bounds.center()) < 100.));
+        assert!(world.bullets.is_empty());
+        assert_eq!(world.asteroids.len(), 4);
+    }
+
+    #[test]
+    fn test_small_asteroids_do_not_split() {
+        let mut shapes = ShapeSource::new(None);
+        assert!(split_asteroid(&rock(vec2(200., 200.), vec2(1., 0.), MEDIUM_ASTEROID_RADIUS), &mut shapes, &mut GameRng::new(7)).is_empty());
     }
-    info!("Level loaded. Asteroids spawned: {}", asteroids.len());
 }
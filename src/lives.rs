@@ -0,0 +1,205 @@
+use macroquad::prelude::*;
+use crate::components::Player;
+use crate::settings::CoopLives;
+
+/// Seconds of invulnerability a ship gets after respawning.
+pub const RESPAWN_GRACE: f32 = 3.0;
+
+/// What losing a life did to a ship.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LifeLoss {
+    /// Lives are left: the ship is back in play.
+    Respawned,
+    /// The ship's own pool ran dry but a teammate fights on.
+    Benched,
+    /// No lives left anywhere: the run is over.
+    GameOver,
+}
+
+/// Lives of the team: one shared pool, or one pool per ship in co-op.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lives {
+    counts: Vec<i32>,
+    shared: bool,
+}
+
+impl Lives {
+    /// `start` lives for a team of `players` ships.
+    pub fn new(mode: CoopLives, start: i32, players: usize) -> Self {
+        let shared = mode == CoopLives::Shared;
+        let pools = if shared { 1 } else { players.max(1) };
+        Lives { counts: vec![start; pools], shared }
+    }
+
+    /// Restores saved pools (a single count is a shared pool).
+    pub fn from_counts(counts: Vec<i32>) -> Self {
+        if counts.is_empty() {
+            return Lives { counts: vec![0], shared: true };
+        }
+        let shared = counts.len() == 1;
+        Lives { counts, shared }
+    }
+
+    /// One count per pool, as saved.
+    pub fn counts(&self) -> &[i32] {
+        &self.counts
+    }
+
+    pub fn is_shared(&self) -> bool {
+        self.shared
+    }
+
+    fn pool(&self, player: usize) -> usize {
+        player.min(self.counts.len() - 1)
+    }
+
+    /// Lives left to `player` (the whole pool when it's shared).
+    pub fn of(&self, player: usize) -> i32 {
+        self.counts[self.pool(player)]
+    }
+
+    /// Lives left across the team.
+    pub fn total(&self) -> i32 {
+        self.counts.iter().map(|c| (*c).max(0)).sum()
+    }
+
+    /// Whether `player` has run out and sits out the rest of the run.
+    pub fn is_out(&self, player: usize) -> bool {
+        self.of(player) <= 0
+    }
+
+    /// Extra life: the shared pool, or every ship still in play, gains one.
+    pub fn gain(&mut self) {
+        for count in self.counts.iter_mut().filter(|c| **c > 0) {
+            *count += 1;
+        }
+    }
+
+    /// Matches separate pools to a team that grew or shrank; a new ship starts with `start`.
+    pub fn resize(&mut self, players: usize, start: i32) {
+        if !self.shared {
+            self.counts.resize(players.max(1), start);
+        }
+    }
+
+    /// Takes `count` lives from `player` and reports what that did to the run.
+    pub fn lose(&mut self, player: usize, count: i32) -> LifeLoss {
+        let pool = self.pool(player);
+        self.counts[pool] -= count;
+        if self.counts.iter().all(|c| *c <= 0) {
+            LifeLoss::GameOver
+        } else if self.counts[pool] <= 0 {
+            LifeLoss::Benched
+        } else {
+            LifeLoss::Respawned
+        }
+    }
+}
+
+/// Lives a lethal hit costs: `lives_lost` in a normal run, none in sandbox practice.
+pub fn hit_cost(sandbox: bool, lives_lost: i32) -> i32 {
+    if sandbox { 0 } else { lives_lost }
+}
+
+/// Handles a ship destroyed by a hit: takes `count` lives from its pool, then either
+/// respawns it (at `respawn`, or in place when `None`) with `RESPAWN_GRACE` of
+/// invulnerability, or benches it once its pool is empty.
+pub fn destroy_ship(lives: &mut Lives, index: usize, count: i32, player: &mut Player, respawn: Option<Vec2>) -> LifeLoss {
+    let outcome = lives.lose(index, count);
+    match outcome {
+        LifeLoss::Respawned => {
+            if let Some(pos) = respawn {
+                player.pos = pos;
+                player.prev_pos = pos;
+                player.vel = Vec2::ZERO;
+            }
+            player.invulnerable = true;
+            player.invulnerable_timer = RESPAWN_GRACE;
+        }
+        LifeLoss::Benched => {
+            player.active = false;
+            player.thrusting = false;
+            player.vel = Vec2::ZERO;
+        }
+        LifeLoss::GameOver => {}
+    }
+    outcome
+}
+
+/// `destroy_ship`, logging what the loss did to ship `index`; `cause` finishes
+/// "Player 1 ..." (e.g. "shot down").
+pub fn lose_ship(lives: &mut Lives, index: usize, count: i32, player: &mut Player, respawn: Option<Vec2>, cause: &str) -> LifeLoss {
+    let outcome = destroy_ship(lives, index, count, player, respawn);
+    match outcome {
+        LifeLoss::GameOver => warn!("Game Over: Player {} {}. Lives: 0", index + 1, cause),
+        LifeLoss::Benched => warn!("Player {} {} and is out of lives.", index + 1, cause),
+        LifeLoss::Respawned => {
+            warn!("Player {} {}. Lives remaining: {}", index + 1, cause, lives.of(index));
+            info!(
+                "Player {} respawned at ({:.1}, {:.1}) with {}s invulnerability.",
+                index + 1, player.pos.x, player.pos.y, RESPAWN_GRACE
+            );
+        }
+    }
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ship() -> Player {
+        Player { vel: vec2(2., 0.), ..Player::new(vec2(100., 100.)) }
+    }
+
+    #[test]
+    fn test_shared_pool_reaching_zero_ends_the_game() {
+        let mut lives = Lives::new(CoopLives::Shared, 2, 2);
+        let (mut one, mut two) = (ship(), ship());
+        assert_eq!(destroy_ship(&mut lives, 0, 1, &mut one, Some(vec2(400., 300.))), LifeLoss::Respawned);
+        assert_eq!(one.pos, vec2(400., 300.));
+        assert!(one.invulnerable);
+        // The other ship draws from the same pool
+        assert_eq!(lives.of(1), 1);
+        assert_eq!(lose_ship(&mut lives, 1, 1, &mut two, None, "shot down"), LifeLoss::GameOver);
+        assert_eq!(lives.total(), 0);
+    }
+
+    #[test]
+    fn test_separate_pools_bench_a_ship_until_both_are_empty() {
+        let mut lives = Lives::new(CoopLives::Separate, 1, 2);
+        let (mut one, mut two) = (ship(), ship());
+        assert_eq!(destroy_ship(&mut lives, 0, 1, &mut one, None), LifeLoss::Benched);
+        assert!(!one.active);
+        assert!(lives.is_out(0) && !lives.is_out(1));
+        // Extra lives only go to ships still flying
+        lives.gain();
+        assert_eq!(lives.counts(), [0, 2]);
+        assert_eq!(destroy_ship(&mut lives, 1, 1, &mut two, None), LifeLoss::Respawned);
+        assert_eq!(destroy_ship(&mut lives, 1, 1, &mut two, None), LifeLoss::GameOver);
+    }
+
+    #[test]
+    fn test_sandbox_hits_respawn_without_costing_lives() {
+        let mut lives = Lives::new(CoopLives::Shared, 1, 1);
+        let mut one = ship();
+        for _ in 0..3 {
+            let cost = hit_cost(true, 1);
+            assert_eq!(destroy_ship(&mut lives, 0, cost, &mut one, Some(vec2(400., 300.))), LifeLoss::Respawned);
+        }
+        assert_eq!(lives.total(), 1);
+        // The same hit in a normal run is the last one
+        assert_eq!(destroy_ship(&mut lives, 0, hit_cost(false, 1), &mut one, None), LifeLoss::GameOver);
+        assert_eq!(hit_cost(false, 2), 2);
+    }
+
+    #[test]
+    fn test_solo_play_has_one_pool() {
+        let mut lives = Lives::new(CoopLives::Separate, 3, 1);
+        assert_eq!(lives.counts(), [3]);
+        // A second ship joining gets a pool of its own
+        lives.resize(2, 3);
+        assert_eq!(lives.counts(), [3, 3]);
+        assert_eq!(Lives::from_counts(vec![2, 3]).total(), 5);
+    }
+}
@@ -6,21 +6,82 @@ mod level;
 mod settings;
 mod ui;
 mod particles;
+mod records;
+mod killfeed;
+mod time;
+mod rng;
+mod hazards;
+mod alerts;
+mod palette;
+mod overdrive;
+mod daily;
+mod ghost;
+mod highscores;
+mod input;
+mod world;
+mod powerups;
+mod savegame;
+mod bomb;
+mod boss;
+mod score;
+mod events;
+mod lives;
+mod background;
+mod sweep;
+mod window;
+mod director;
+mod console;
+mod step;
+#[cfg(test)]
+mod test_support;
 
 use macroquad::prelude::*;
-use macroquad::rand::gen_range;
+use macroquad::miniquad::window::{clipboard_get, clipboard_set};
+use std::path::Path;
 use std::sync::mpsc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use components::*;
-use config::get_levels;
-use resources::Resources;
+use config::{GameConfig, LevelConfig};
+use resources::{AudioBackend, Resources, SoundId};
 use physics::*;
-use level::load_level;
-use settings::Settings;
-use ui::{draw_pause_screen, draw_settings_screen, draw_title_screen};
-use particles::{ParticleSpawnBridge, ParticleSystem, SpawnRequest};
+use level::{load_level, start_level, Campaign};
+use settings::{key_name, AdvancedField, ControlMode, Settings, SettingsTab, ShipField};
+use ui::{world_camera, draw_aim_assist, draw_radar, draw_brightness_overlay, draw_crosshair, draw_edge_arrow, draw_bomb_counts, draw_fuel_gauges, draw_ghost, draw_high_scores, draw_hud, draw_name_prompt, draw_overdrive_meter, draw_bullet_time_meter, draw_console, draw_pause_screen, draw_quit_prompt, draw_scanlines, draw_settings_screen, draw_title_screen, draw_world};
+use particles::{explosion_preset, EmissionTimer, ParticleSpawnBridge, ParticleSystem, SpawnRequest};
+use records::{BossRushRecords, DailyRecords};
+use killfeed::KillFeed;
+use director::AdaptiveDirector;
+use console::{parse_command, Command, Console, CONSOLE_HELP};
+use time::{dropped_to_last_life, frame_budget, frame_sleep, next_fps_cap, scaled_dt, BulletTime, GameClock, LastStand};
+use alerts::EdgeAlert;
+use hazards::{MagneticStorm, STORM_DURATION, STORM_INTERVAL};
+use overdrive::{shockwave, Overdrive};
+use rng::{random_seed, GameRng, ShapeSource, FIXED_SHAPES_SEED};
+use palette::{Role, MAX_BRIGHTNESS, MIN_BRIGHTNESS};
+use daily::{daily_seed, format_daily_seed, seed_daily_level};
+use ghost::{ghost_import_path, ghost_path, GhostData, GhostRecorder};
+use highscores::{default_player_name, HighScores, ScoreEntry, MAX_NAME_LEN};
+use score::ScoreState;
+use events::{EventLog, GameEvent, EVENT_LOG_PATH};
+use lives::Lives;
+use background::{Starfield, STARS_PER_LAYER, STAR_LAYERS};
+use sweep::SweepTracker;
+use savegame::GameSnapshot;
+use input::{menu_pressed, poll_controls, Gamepad, PadButton, PadState, PLAYER_TWO_KEYS};
+use step::{FrameInput, StepContext, SAFE_SPAWN_CLEARANCE};
+use window::{WindowConfig, RESOLUTION_ENV, WINDOW_CONFIG_PATH};
+use world::{FixedStep, GameWorld, MAX_FRAME_DT, TARGET_FPS, PLAYER_BULLET_LIFETIME, PLAYER_BULLET_SPEED};
+
+/// Fraction of speed explosion debris loses per second, so bursts settle instead of streaking.
+const DEBRIS_DRAG: f32 = 1.5;
+/// Seconds between exhaust puffs while an engine is firing.
+const EXHAUST_INTERVAL: f32 = 0.05;
+/// Speed (px/sec) exhaust is blown out behind the ship.
+const EXHAUST_SPEED: f32 = 120.0;
+/// Gameplay seconds a background scan takes (pausing stops the countdown).
+const SCAN_DURATION: f64 = 2.0;
 
 #[derive(Clone, Copy, PartialEq)]
 enum GameState {
@@ -36,89 +97,238 @@ enum SettingsOrigin {
     Pause,
 }
 
+/// Window title, size and fullscreen mode from `window.cfg`, with the size
+/// optionally overridden by the `ASTEROIDS_RESOLUTION` environment variable.
+fn window_conf() -> Conf {
+    WindowConfig::load_from_file(WINDOW_CONFIG_PATH)
+        .with_resolution(std::env::var(RESOLUTION_ENV).ok().as_deref())
+        .to_conf()
+}
+
 /// Main Entry Point
-#[macroquad::main("Rust Asteroids")]
+#[macroquad::main(window_conf)]
 async fn main() {
     info!("Starting Rust Asteroids");
     info!("Running in Standalone Mode (Assets Embedded)");
 
-    let levels = get_levels();
-    
-    // Validate Level Configurations
-    for (i, config) in levels.iter().enumerate() {
-        if let Err(e) = config.validate(i) {
+    // Load and validate the game config (levels, tuning, scores). Problems are shown
+    // on the config-error screen and the built-in defaults are used instead.
+    let mut config_error: Option<String> = None;
+    let game_config = match GameConfig::load_from_file("game.cfg", "levels.cfg").and_then(|cfg| cfg.validate().map(|_| cfg)) {
+        Ok(cfg) => cfg,
+        Err(e) => {
             error!("Configuration Error: {}", e);
-            // In a real app we might panic or show an error screen, 
-            // but here we just log it and likely crash later or have weird behavior.
-            // For safety, let's panic to prevent running with bad config
-            panic!("Invalid Level Configuration: {}", e);
+            config_error = Some(e);
+            GameConfig::default()
         }
+    };
+    if let Err(e) = game_config.validate() {
+        // Built-in defaults are broken; nothing sensible to fall back to
+        panic!("Invalid built-in configuration: {}", e);
     }
+    let levels = &game_config.levels;
+    let tuning = &game_config.player;
+    let scores = &game_config.scores;
 
-    let mut current_level_idx = 0;
+    // Level sequence: configured levels, endless waves or a boss rush
+    let mut campaign = Campaign::default();
     
     // Load Audio Resources safely
     let mut resources = Resources::load().await;
+    for (clip, interval) in SoundId::ALL.into_iter().zip(game_config.audio.replay_intervals) {
+        resources.set_replay_interval(clip, interval);
+    }
 
     // Settings (loaded on background thread)
     let settings_path = "settings.cfg".to_string();
     let (settings_tx, settings_rx) = mpsc::channel();
     let settings_path_clone = settings_path.clone();
+    let settings_defaults = game_config.settings_defaults();
+    let defaults_clone = settings_defaults.clone();
     thread::spawn(move || {
-        let result = Settings::load_from_file(&settings_path_clone);
+        let result = Settings::load_from_file(&settings_path_clone, &defaults_clone);
         let _ = settings_tx.send(result);
     });
-    let mut settings = Settings::default();
+    let mut settings = Settings::with_defaults(&settings_defaults);
     let mut settings_rx = Some(settings_rx);
 
+    // Boss rush best times (loaded on background thread)
+    let records_path = "bossrush.cfg".to_string();
+    let (records_tx, records_rx) = mpsc::channel();
+    let records_path_clone = records_path.clone();
+    thread::spawn(move || {
+        let result = BossRushRecords::load_from_file(&records_path_clone);
+        let _ = records_tx.send(result);
+    });
+    let mut boss_rush_records = BossRushRecords::default();
+    let mut records_rx = Some(records_rx);
+
+    // Daily challenge bests per date (loaded on background thread)
+    let daily_path = "daily.cfg".to_string();
+    let (daily_tx, daily_rx) = mpsc::channel();
+    let daily_path_clone = daily_path.clone();
+    thread::spawn(move || {
+        let result = DailyRecords::load_from_file(&daily_path_clone);
+        let _ = daily_tx.send(result);
+    });
+    let mut daily_records = DailyRecords::default();
+    let mut daily_rx = Some(daily_rx);
+
+    // Top scores of past runs (loaded on background thread)
+    let high_scores_path = "highscores.cfg".to_string();
+    let (high_scores_tx, high_scores_rx) = mpsc::channel();
+    let high_scores_path_clone = high_scores_path.clone();
+    thread::spawn(move || {
+        let result = HighScores::load_from_file(&high_scores_path_clone);
+        let _ = high_scores_tx.send(result);
+    });
+    let mut high_scores = HighScores::default();
+    let mut high_scores_rx = Some(high_scores_rx);
+
+    // Run saved from the pause menu, offered as "Continue" on the title screen
+    let save_game_path = "savegame.cfg".to_string();
+    let mut has_save = Path::new(&save_game_path).exists();
+    // Outcome of a save still being written on a background thread
+    let mut save_game_rx: Option<mpsc::Receiver<Result<(), String>>> = None;
+    // Whether the finished run has been entered, and the rank it reached
+    let mut high_score_recorded = false;
+    let mut high_score_rank: Option<usize> = None;
+    // Name being typed for that entry; it's saved once the player confirms it
+    let mut name_entry: Option<String> = None;
+
     // Game State initialization
-    let mut score = 0;
-    let mut lives = 3;
+    let mut score = ScoreState::new();
+    let mut lives = Lives::new(settings.coop_lives, settings.advanced.player_lives as i32, 1);
     let mut last_extra_life_score = 0;
-    let mut player = Player {
-        pos: vec2(screen_width() / 2., screen_height() / 2.),
-        vel: vec2(0., 0.),
-        rotation: 0.,
-        radius: 15.,
-        active: true,
-        sides: 3,
-        color: WHITE,
-        invulnerable: false,
-        invulnerable_timer: 0.,
+    // Logical playfield, fixed for the session; the window scales it when resized
+    let world_size = World::from_screen(screen_width(), screen_height());
+    let player = Player {
+        fuel: tuning.max_fuel,
+        max_fuel: tuning.max_fuel,
+        ..Player::new(world_size.center())
     };
     
+    // Result of the last settings share-code import/export, shown on the menus
+    let mut share_message = String::new();
+
+    // Two ships on one keyboard, sharing lives and score
+    let mut coop = false;
+
+    // Whether the OS cursor is currently hidden (mouse mode during play)
+    let mut cursor_hidden = false;
+
     let mut design_mode = false;
-    let available_colors = [WHITE, RED, ORANGE, YELLOW, GREEN, SKYBLUE, BLUE, PURPLE, PINK];
-    let mut current_color_idx = 0;
     
-    let mut bullets: Vec<Bullet> = Vec::new();
-    let mut asteroids: Vec<Asteroid> = Vec::new();
-    let mut ufos: Vec<Ufo> = Vec::new();
+    // Asteroid outlines (seeded when fixed shapes are enabled)
+    let mut shapes = ShapeSource::new(None);
+    // Level layouts, splits and particle bursts; a `seed` setting replays the same run
+    let run_seed = random_seed();
+    let mut level_rng = GameRng::new(run_seed);
+
+    // Simulated entities, advanced in fixed physics steps
+    let mut world = GameWorld::new(player, world_size, settings.ship.friction);
+    let mut fixed_step = FixedStep::new();
     
     let mut game_over = false;
     let mut game_won = false;
 
     let mut game_state = GameState::Title;
+    // "Quit? Y/N" prompt shown before leaving from the title screen or mid-run
+    let mut confirm_exit = false;
     let mut settings_origin = SettingsOrigin::Title;
+    let mut advanced_field = AdvancedField::EnemySpeed;
+    // Sound whose level the General tab's [ / ] keys adjust (index into SoundId::ALL)
+    let mut selected_sound = 0;
+    // Ship handling value the Controls tab's [ / ] keys adjust
+    let mut ship_field = ShipField::RotationSpeed;
+    let mut settings_tab = SettingsTab::General;
+
+    // Boss rush: boss levels back-to-back against the clock
+    let boss_rush_total = levels.iter().filter(|l| l.is_boss()).count();
+
+    // Endless mode: once the last level is cleared, procedural waves keep coming
+    let mut endless = false;
+    // Practice runs: hits respawn the ship but never cost a life
+    let mut sandbox = false;
+
+    // Daily challenge: the run's date seed (kept for the whole run, even past midnight)
+    let mut daily: Option<u64> = None;
+    let mut daily_recorded = false;
+    // Our pose recording for the current daily run, and an imported ghost to race
+    let mut ghost_recorder: Option<GhostRecorder> = None;
+    let mut rival_ghost: Option<GhostData> = None;
 
     // Multitasking Demo: Background Scanner
+    // Gameplay clock: only ticks while playing, so timed rewards respect pause
+    let mut game_clock = GameClock::new();
+    // Clock reading when the current run began, for the HUD run timer
+    let mut run_start = 0.0;
+    // Notable moments of the current run, written out when it ends
+    let mut event_log = EventLog::default();
+
     let mut is_scanning = false;
     let mut scan_receiver: Option<mpsc::Receiver<String>> = None;
+    let mut scan_result: Option<String> = None;
+    let mut scan_deadline = 0.0;
     let mut scan_message = String::new();
     let mut scan_message_timer = 0.0;
 
     // Particle system + spawn bridge (background generation)
     let mut particle_system = ParticleSystem::new();
-    let particle_spawner = ParticleSpawnBridge::new();
+    particle_system.set_drag(DEBRIS_DRAG);
+    let particle_spawner = ParticleSpawnBridge::new(run_seed);
+    // One exhaust emitter per ship
+    let mut exhaust_timers: Vec<EmissionTimer> = Vec::new();
+
+    // Kill feed (recent scoring events)
+    let mut kill_feed = KillFeed::new();
+
+    // Overdrive meter, filled by kills and spent on a screen-clearing shockwave
+    let mut overdrive = Overdrive::new();
+    // Slow-motion ability, limited by its own draining meter
+    let mut bullet_time = BulletTime::new();
+    // Backtick tuning console; `spawn ufo` leaves a request for the spawn logic
+    let mut console = Console::new();
+    let mut force_ufo = false;
+    // Watches recent form to rubber-band UFO spawns when adaptive spawning is on
+    let mut director = AdaptiveDirector::new(lives.total());
+
+    // Edge arrow warning of a freshly spawned UFO
+    let mut ufo_alert: Option<EdgeAlert> = None;
+
+    // Magnetic storm hazard (HUD scramble), rescheduled on every level load
+    let mut storm = MagneticStorm::new(STORM_INTERVAL.1);
+    // Fragments of the player's recent splits, for the clean-sweep bonus
+    let mut sweeps = SweepTracker::default();
+    let mut starfield = Starfield::new(STAR_LAYERS, STARS_PER_LAYER, world.bounds.size());
+
+    // Slow-motion moment when dropping to the final life
+    let mut last_stand = LastStand::new();
 
     // Initial Level Load (uses default settings until loaded)
-    let initial_cfg = levels[current_level_idx].scaled(
-        settings.difficulty.speed_multiplier(),
-        settings.difficulty.spawn_multiplier(),
+    let initial_cfg = levels[campaign.level_idx].scaled(
+        settings.advanced.enemy_speed,
+        settings.advanced.spawn_density,
     );
-    load_level(&initial_cfg, &mut asteroids, &mut ufos);
+    load_level(&initial_cfg, &mut world, settings.edge_entry, &mut shapes, &mut level_rng);
+
+    // First connected controller, read alongside the keyboard
+    let mut gamepad = Gamepad::new();
+
+    // Start of the current frame, for the soft FPS cap
+    let mut frame_start = Instant::now();
 
     loop {
+        // Soft FPS cap: sleep away whatever is left of the last frame's budget
+        if let Some(budget) = frame_budget(settings.target_fps) {
+            let slack = frame_sleep(budget, frame_start.elapsed());
+            if !slack.is_zero() {
+                thread::sleep(slack);
+            }
+        }
+        frame_start = Instant::now();
+        let pad = gamepad.poll();
+
         // Check for async settings load
         if let Some(rx) = &settings_rx {
             if let Ok(result) = rx.try_recv() {
@@ -126,8 +336,28 @@ async fn main() {
                 match result {
                     Ok(loaded) => {
                         settings = loaded;
+                        world.apply_ship_design(settings.ship_sides, settings.ship_radius, SHIP_COLORS[settings.ship_color]);
                         resources.set_volume(settings.volume);
-                        info!("Settings loaded: volume={:.2}, difficulty={:?}, show_fps={}", settings.volume, settings.difficulty, settings.show_fps);
+                        resources.set_muted(settings.muted);
+                        for (clip, level) in SoundId::ALL.into_iter().zip(settings.sound_volumes) {
+                            resources.set_sound_volume(clip, level);
+                        }
+                        if game_state == GameState::Title {
+                            lives = Lives::new(settings.coop_lives, settings.advanced.player_lives as i32, world.players.len());
+                            // Redraw the opening level from the fixed seed
+                            if let Some(seed) = settings.seed {
+                                info!("Using fixed run seed {}", seed);
+                                level_rng = GameRng::new(seed);
+                                particle_spawner.reseed(seed);
+                                shapes.seed = settings.fixed_shapes.then_some(FIXED_SHAPES_SEED).or(settings.seed);
+                                let seeded_cfg = levels[campaign.level_idx].scaled(
+                                    settings.advanced.enemy_speed,
+                                    settings.advanced.spawn_density,
+                                );
+                                load_level(&seeded_cfg, &mut world, settings.edge_entry, &mut shapes, &mut level_rng);
+                            }
+                        }
+                        info!("Settings loaded: volume={:.2}, difficulty={:?}, show_fps={}, asteroid_impacts={}", settings.volume, settings.difficulty, settings.show_fps, settings.asteroid_impacts);
                     }
                     Err(err) => {
                         warn!("Using default settings. Reason: {}", err);
@@ -136,57 +366,511 @@ async fn main() {
             }
         }
 
+        // Check for async boss rush records load
+        if let Some(rx) = &records_rx {
+            if let Ok(result) = rx.try_recv() {
+                records_rx = None;
+                if let Ok(loaded) = result {
+                    boss_rush_records = loaded;
+                }
+            }
+        }
+
+        // Check for async daily challenge records load
+        if let Some(rx) = &daily_rx {
+            if let Ok(result) = rx.try_recv() {
+                daily_rx = None;
+                if let Ok(loaded) = result {
+                    daily_records = loaded;
+                }
+            }
+        }
+
+        // Check for async high score load
+        if let Some(rx) = &high_scores_rx {
+            if let Ok(result) = rx.try_recv() {
+                high_scores_rx = None;
+                if let Ok(loaded) = result {
+                    high_score_rank = high_scores.merge(loaded, high_score_rank);
+                    if high_score_rank.is_none() {
+                        name_entry = None;
+                    }
+                    // A run already saved before the table arrived wrote it without the older scores
+                    if high_score_recorded && name_entry.is_none() && !sandbox {
+                        let save_scores = high_scores.clone();
+                        let save_path = high_scores_path.clone();
+                        thread::spawn(move || {
+                            let _ = save_scores.save_to_file(&save_path);
+                        });
+                    }
+                }
+            }
+        }
+
+        // Check for async savegame write
+        if let Some(rx) = &save_game_rx {
+            if let Ok(result) = rx.try_recv() {
+                save_game_rx = None;
+                share_message = match result {
+                    Ok(()) => {
+                        has_save = true;
+                        "Game saved: press C on the title screen to continue".to_string()
+                    }
+                    Err(err) => err,
+                };
+            }
+        }
+
         // Collect particle batches from background worker
         if let Some(batch) = particle_spawner.try_receive() {
             particle_system.spawn_batch(batch);
         }
 
+        shapes.seed = daily.or(settings.fixed_shapes.then_some(FIXED_SHAPES_SEED)).or(settings.seed);
+
+        // Hide the OS cursor only while aiming with the mouse in live gameplay
+        let want_hidden = settings.mouse_aim()
+            && settings.hide_cursor
+            && game_state == GameState::Playing
+            && !game_over
+            && !game_won
+            && config_error.is_none();
+        if want_hidden != cursor_hidden {
+            show_mouse(!want_hidden);
+            cursor_hidden = want_hidden;
+        }
+
+        // --- CONFIG ERROR SCREEN ---
+        if let Some(err) = &config_error {
+            clear_background(BLACK);
+            draw_text("CONFIGURATION ERROR", 40., 80., 36., RED);
+            draw_text(err, 40., 130., 20., WHITE);
+            draw_text("Fix game.cfg / levels.cfg and restart, or:", 40., 180., 20., GRAY);
+            draw_text("Press Enter to play with the built-in defaults", 40., 210., 20., GRAY);
+            draw_text("Press Esc to Quit", 40., 240., 20., GRAY);
+            if is_key_pressed(KeyCode::Enter) {
+                info!("Config error acknowledged; continuing with defaults");
+                config_error = None;
+            }
+            if is_key_pressed(KeyCode::Escape) {
+                break;
+            }
+            next_frame().await;
+            continue;
+        }
+
+        // Gameplay time only accumulates while actually flying
+        if game_state == GameState::Playing && !confirm_exit && !console.open {
+            game_clock.resume();
+        } else {
+            game_clock.pause();
+        }
+
+        // --- QUIT CONFIRMATION ---
+        if confirm_exit {
+            if game_state == GameState::Title {
+                draw_title_screen(&settings, coop, endless, sandbox, has_save, daily_records.best_for(daily_seed()));
+            } else {
+                clear_background(BLACK);
+                for player in world.players.iter_mut() {
+                    player.thrusting = false;
+                }
+                set_camera(&world_camera(&world.bounds));
+                draw_world(&world, fixed_step.alpha(), game_clock.elapsed(), settings.color_scheme, settings.palette);
+                set_default_camera();
+            }
+            draw_quit_prompt();
+            if is_key_pressed(KeyCode::Y) || pad.pressed(PadButton::South) {
+                info!("Quit confirmed");
+                break;
+            }
+            if is_key_pressed(KeyCode::N) || menu_pressed(KeyCode::Escape, &pad) || pad.pressed(PadButton::East) {
+                confirm_exit = false;
+            }
+            next_frame().await;
+            continue;
+        }
+
         // --- TITLE / PAUSE / SETTINGS SCREENS ---
         if game_state == GameState::Title {
-            draw_title_screen();
-            if is_key_pressed(KeyCode::Enter) {
+            draw_title_screen(&settings, coop, endless, sandbox, has_save, daily_records.best_for(daily_seed()));
+            draw_text(&share_message, 20., screen_height() - 20., 18., YELLOW);
+            if is_key_pressed(KeyCode::V) {
+                match clipboard_get().map(|code| Settings::from_share_code(&code)) {
+                    Some(Ok(imported)) => {
+                        settings = imported;
+                        world.apply_ship_design(settings.ship_sides, settings.ship_radius, SHIP_COLORS[settings.ship_color]);
+                        resources.set_volume(settings.volume);
+                        resources.set_muted(settings.muted);
+                        for (clip, level) in SoundId::ALL.into_iter().zip(settings.sound_volumes) {
+                            resources.set_sound_volume(clip, level);
+                        }
+                        lives = Lives::new(settings.coop_lives, settings.advanced.player_lives as i32, world.players.len());
+                        let save_settings = settings.clone();
+                        let save_path = settings_path.clone();
+                        thread::spawn(move || {
+                            let _ = save_settings.save_to_file(&save_path);
+                        });
+                        info!("Settings imported from share code");
+                        share_message = "Settings imported from clipboard".to_string();
+                    }
+                    Some(Err(err)) => {
+                        warn!("Rejected settings share code: {}", err);
+                        share_message = format!("Import failed: {}", err);
+                    }
+                    None => share_message = "Import failed: clipboard is empty".to_string(),
+                }
+            }
+            if menu_pressed(KeyCode::Enter, &pad) {
                 info!("Title -> Playing");
                 game_state = GameState::Playing;
             }
+            if is_key_pressed(KeyCode::Key2) {
+                coop = !coop;
+                world.respawn_team(coop);
+                lives.resize(world.players.len(), settings.advanced.player_lives as i32);
+                info!("Two-player co-op toggled: {}", coop);
+            }
+            if has_save && is_key_pressed(KeyCode::C) {
+                match GameSnapshot::load_from_file(&save_game_path) {
+                    Ok(snapshot) if snapshot.current_level_idx < levels.len() => {
+                        info!("Title -> Continue saved game");
+                        daily = None;
+                        sandbox = false;
+                        ghost_recorder = None;
+                        campaign = Campaign {
+                            level_idx: snapshot.current_level_idx,
+                            endless_wave: snapshot.endless_wave,
+                            ..Campaign::default()
+                        };
+                        score = ScoreState::with_score(snapshot.score);
+                        lives = Lives::from_counts(snapshot.lives);
+                        last_extra_life_score = snapshot.last_extra_life_score;
+                        game_over = false;
+                        high_score_recorded = false;
+                        run_start = game_clock.elapsed();
+                        event_log.clear();
+                        sweeps.clear();
+                        game_won = false;
+                        coop = snapshot.players.len() > 1;
+                        world.players = snapshot.players;
+                        world.asteroids = snapshot.asteroids;
+                        world.ufos = snapshot.ufos;
+                        world.bosses = snapshot.bosses;
+                        world.bullets = snapshot.bullets;
+                        // The saved ships keep their shields and bombs; only loose pickups go
+                        world.powerups.clear();
+                        kill_feed.clear();
+                        last_stand.clear();
+                        overdrive = Overdrive::new();
+                        bullet_time = BulletTime::new();
+                        director = AdaptiveDirector::new(lives.total());
+                        storm.reset();
+                        game_state = GameState::Playing;
+                    }
+                    Ok(snapshot) => {
+                        warn!("Saved game is on level {}, which no longer exists", snapshot.current_level_idx + 1);
+                        share_message = "Continue failed: the saved level no longer exists".to_string();
+                    }
+                    Err(err) => share_message = format!("Continue failed: {}", err),
+                }
+            }
+            if is_key_pressed(KeyCode::E) {
+                endless = !endless;
+                info!("Endless mode toggled: {}", endless);
+            }
+            if is_key_pressed(KeyCode::P) {
+                sandbox = !sandbox;
+                info!("Sandbox practice toggled: {}", sandbox);
+            }
+            if is_key_pressed(KeyCode::B) && boss_rush_total > 0 {
+                info!("Title -> Boss Rush");
+                daily = None;
+                sandbox = false;
+                ghost_recorder = None;
+                if let Some(seed) = settings.seed {
+                    level_rng = GameRng::new(seed);
+                    particle_spawner.reseed(seed);
+                }
+                campaign = Campaign::start(levels, true);
+                score = ScoreState::new();
+                lives = Lives::new(settings.coop_lives, settings.advanced.player_lives as i32, 1);
+                last_extra_life_score = 0;
+                game_over = false;
+                high_score_recorded = false;
+                run_start = game_clock.elapsed();
+                event_log.clear();
+                sweeps.clear();
+                game_won = false;
+                coop = false;
+                world.respawn_team(false);
+                world.bullets.clear();
+                world.reset_powerups();
+                kill_feed.clear();
+                last_stand.clear();
+                overdrive = Overdrive::new();
+                bullet_time = BulletTime::new();
+                director = AdaptiveDirector::new(lives.total());
+                let rush_cfg = levels[campaign.level_idx].scaled(
+                    settings.advanced.enemy_speed,
+                    settings.advanced.spawn_density,
+                );
+                load_level(&rush_cfg, &mut world, settings.edge_entry, &mut shapes, &mut level_rng);
+                world.move_team_to_safety(SAFE_SPAWN_CLEARANCE);
+                storm.reset();
+                game_state = GameState::Playing;
+            }
+            if is_key_pressed(KeyCode::D) {
+                let seed = daily_seed();
+                info!("Title -> Daily Challenge {}", format_daily_seed(seed));
+                daily = Some(seed);
+                sandbox = false;
+                daily_recorded = false;
+                ghost_recorder = Some(GhostRecorder::new(seed));
+                campaign = Campaign::start(levels, false);
+                score = ScoreState::new();
+                lives = Lives::new(settings.coop_lives, settings.advanced.player_lives as i32, 1);
+                last_extra_life_score = 0;
+                game_over = false;
+                high_score_recorded = false;
+                run_start = game_clock.elapsed();
+                event_log.clear();
+                sweeps.clear();
+                game_won = false;
+                coop = false;
+                world.respawn_team(false);
+                world.bullets.clear();
+                world.reset_powerups();
+                kill_feed.clear();
+                last_stand.clear();
+                overdrive = Overdrive::new();
+                bullet_time = BulletTime::new();
+                director = AdaptiveDirector::new(lives.total());
+                let daily_cfg = levels[campaign.level_idx].scaled(
+                    settings.advanced.enemy_speed,
+                    settings.advanced.spawn_density,
+                );
+                shapes.seed = Some(seed);
+                level_rng = seed_daily_level(seed, campaign.level_idx);
+                load_level(&daily_cfg, &mut world, settings.edge_entry, &mut shapes, &mut level_rng);
+                world.move_team_to_safety(SAFE_SPAWN_CLEARANCE);
+                storm.reset();
+                game_state = GameState::Playing;
+            }
+            if is_key_pressed(KeyCode::G) {
+                match GhostData::load(&ghost_import_path(daily_seed())).and_then(|g| g.check_seed(daily_seed()).map(|_| g)) {
+                    Ok(ghost) => {
+                        share_message = format!("Ghost loaded (score {}): press D to race it", ghost.score);
+                        rival_ghost = Some(ghost);
+                    }
+                    Err(err) => {
+                        warn!("Rejected ghost: {}", err);
+                        share_message = format!("Ghost import failed: {}", err);
+                    }
+                }
+            }
             if is_key_pressed(KeyCode::S) {
                 info!("Title -> Settings");
                 settings_origin = SettingsOrigin::Title;
                 game_state = GameState::Settings;
             }
-            if is_key_pressed(KeyCode::Escape) {
-                break;
+            if menu_pressed(KeyCode::Escape, &pad) {
+                if settings.confirm_quit { confirm_exit = true; } else { break; }
             }
             next_frame().await;
             continue;
         }
 
         if game_state == GameState::Settings {
-            draw_settings_screen(&settings);
+            draw_settings_screen(&settings, settings_tab, advanced_field, selected_sound, ship_field);
+            draw_text(&share_message, 40., 166., 18., YELLOW);
 
-            if is_key_pressed(KeyCode::Left) {
-                settings.volume = (settings.volume - 0.05).clamp(0.0, 1.0);
-                resources.set_volume(settings.volume);
-                info!("Settings volume changed: {:.2}", settings.volume);
-            }
-            if is_key_pressed(KeyCode::Right) {
-                settings.volume = (settings.volume + 0.05).clamp(0.0, 1.0);
-                resources.set_volume(settings.volume);
-                info!("Settings volume changed: {:.2}", settings.volume);
+            if menu_pressed(KeyCode::Left, &pad) {
+                settings_tab = settings_tab.prev();
             }
-            if is_key_pressed(KeyCode::Up) {
-                settings.difficulty = settings.difficulty.next();
-                info!("Settings difficulty changed: {:?}", settings.difficulty);
+            if menu_pressed(KeyCode::Right, &pad) {
+                settings_tab = settings_tab.next();
             }
-            if is_key_pressed(KeyCode::Down) {
-                settings.difficulty = settings.difficulty.prev();
-                info!("Settings difficulty changed: {:?}", settings.difficulty);
+
+            // Option keys only act on the category being shown
+            match settings_tab {
+                SettingsTab::General => {
+                    if menu_pressed(KeyCode::Up, &pad) {
+                        settings.volume = (settings.volume + 0.05).clamp(0.0, 1.0);
+                        resources.set_volume(settings.volume);
+                        info!("Settings volume changed: {:.2}", settings.volume);
+                    }
+                    if menu_pressed(KeyCode::Down, &pad) {
+                        settings.volume = (settings.volume - 0.05).clamp(0.0, 1.0);
+                        resources.set_volume(settings.volume);
+                        info!("Settings volume changed: {:.2}", settings.volume);
+                    }
+                    if is_key_pressed(KeyCode::M) {
+                        settings.muted = !settings.muted;
+                        resources.set_muted(settings.muted);
+                        info!("Settings muted toggled: {}", settings.muted);
+                    }
+                    if is_key_pressed(KeyCode::Tab) {
+                        selected_sound = (selected_sound + 1) % SoundId::ALL.len();
+                    }
+                    for (key, step) in [(KeyCode::LeftBracket, -0.1), (KeyCode::RightBracket, 0.1)] {
+                        if is_key_pressed(key) {
+                            let level = &mut settings.sound_volumes[selected_sound];
+                            *level = (*level + step).clamp(0.0, 1.0);
+                            let clip = SoundId::ALL[selected_sound];
+                            resources.set_sound_volume(clip, *level);
+                            info!("Settings {} volume changed: {:.1}", clip.name(), *level);
+                            resources.play(clip);
+                        }
+                    }
+                    if is_key_pressed(KeyCode::Q) {
+                        settings.confirm_quit = !settings.confirm_quit;
+                        info!("Settings confirm_quit toggled: {}", settings.confirm_quit);
+                    }
+                    if is_key_pressed(KeyCode::K) {
+                        settings.show_kill_feed = !settings.show_kill_feed;
+                        info!("Settings show_kill_feed toggled: {}", settings.show_kill_feed);
+                    }
+                    if is_key_pressed(KeyCode::N) {
+                        settings.enable_scanner = !settings.enable_scanner;
+                        info!("Settings enable_scanner toggled: {}", settings.enable_scanner);
+                    }
+                }
+                SettingsTab::Difficulty => {
+                    if menu_pressed(KeyCode::Up, &pad) {
+                        settings.set_difficulty(settings.difficulty.next(), &game_config.difficulty);
+                        info!("Settings difficulty changed: {:?}", settings.difficulty);
+                    }
+                    if menu_pressed(KeyCode::Down, &pad) {
+                        settings.set_difficulty(settings.difficulty.prev(), &game_config.difficulty);
+                        info!("Settings difficulty changed: {:?}", settings.difficulty);
+                    }
+                    if is_key_pressed(KeyCode::Tab) {
+                        advanced_field = advanced_field.next();
+                    }
+                    if is_key_pressed(KeyCode::LeftBracket) {
+                        settings.advanced.adjust(advanced_field, -1);
+                        info!("Settings advanced difficulty changed: {:?}", settings.advanced);
+                    }
+                    if is_key_pressed(KeyCode::RightBracket) {
+                        settings.advanced.adjust(advanced_field, 1);
+                        info!("Settings advanced difficulty changed: {:?}", settings.advanced);
+                    }
+                    if is_key_pressed(KeyCode::O) {
+                        settings.collision_response = settings.collision_response.next();
+                        info!("Settings collision_response changed: {:?}", settings.collision_response);
+                    }
+                    if is_key_pressed(KeyCode::A) {
+                        settings.adaptive_spawning = !settings.adaptive_spawning;
+                        info!("Settings adaptive_spawning toggled: {}", settings.adaptive_spawning);
+                    }
+                }
+                SettingsTab::Gameplay => {
+                    if is_key_pressed(KeyCode::I) {
+                        settings.asteroid_impacts = !settings.asteroid_impacts;
+                        info!("Settings asteroid_impacts toggled: {}", settings.asteroid_impacts);
+                    }
+                    if is_key_pressed(KeyCode::E) {
+                        settings.edge_entry = !settings.edge_entry;
+                        info!("Settings edge_entry toggled: {}", settings.edge_entry);
+                    }
+                    if is_key_pressed(KeyCode::G) {
+                        settings.fixed_shapes = !settings.fixed_shapes;
+                        info!("Settings fixed_shapes toggled: {}", settings.fixed_shapes);
+                    }
+                    if is_key_pressed(KeyCode::W) {
+                        settings.magnetic_storms = !settings.magnetic_storms;
+                        info!("Settings magnetic_storms toggled: {}", settings.magnetic_storms);
+                    }
+                    if is_key_pressed(KeyCode::R) {
+                        settings.friendly_fire = !settings.friendly_fire;
+                        info!("Settings friendly_fire toggled: {}", settings.friendly_fire);
+                    }
+                    if is_key_pressed(KeyCode::L) {
+                        settings.coop_lives = settings.coop_lives.next();
+                        info!("Settings coop_lives changed: {:?}", settings.coop_lives);
+                    }
+                }
+                SettingsTab::Controls => {
+                    if is_key_pressed(KeyCode::C) {
+                        settings.control_mode = settings.control_mode.next();
+                        info!("Settings control_mode changed: {:?}", settings.control_mode);
+                    }
+                    if is_key_pressed(KeyCode::H) {
+                        settings.hide_cursor = !settings.hide_cursor;
+                        info!("Settings hide_cursor toggled: {}", settings.hide_cursor);
+                    }
+                    if is_key_pressed(KeyCode::T) {
+                        settings.thrust_model = settings.thrust_model.next();
+                        info!("Settings thrust_model changed: {:?}", settings.thrust_model);
+                    }
+                    if is_key_pressed(KeyCode::A) {
+                        settings.auto_fire = !settings.auto_fire;
+                        info!("Settings auto_fire toggled: {}", settings.auto_fire);
+                    }
+                    if is_key_pressed(KeyCode::Tab) {
+                        ship_field = ship_field.next();
+                    }
+                    for (key, steps) in [(KeyCode::LeftBracket, -1), (KeyCode::RightBracket, 1)] {
+                        if is_key_pressed(key) {
+                            settings.ship.adjust(ship_field, steps);
+                            info!("Settings ship tuning changed: {:?}", settings.ship);
+                        }
+                    }
+                }
+                SettingsTab::Video => {
+                    if is_key_pressed(KeyCode::L) {
+                        settings.color_scheme = settings.color_scheme.next();
+                        info!("Settings color_scheme changed: {:?}", settings.color_scheme);
+                    }
+                    if is_key_pressed(KeyCode::Z) {
+                        settings.target_fps = next_fps_cap(settings.target_fps);
+                        info!("Settings target_fps changed: {}", settings.target_fps);
+                    }
+                    if is_key_pressed(KeyCode::F) {
+                        settings.show_fps = !settings.show_fps;
+                        info!("Settings show_fps toggled: {}", settings.show_fps);
+                    }
+                    if is_key_pressed(KeyCode::R) {
+                        settings.show_radar = !settings.show_radar;
+                        info!("Settings show_radar toggled: {}", settings.show_radar);
+                    }
+                    if is_key_pressed(KeyCode::Minus) {
+                        settings.brightness = (settings.brightness - 0.1).clamp(MIN_BRIGHTNESS, MAX_BRIGHTNESS);
+                        info!("Settings brightness changed: {:.1}", settings.brightness);
+                    }
+                    if is_key_pressed(KeyCode::Equal) {
+                        settings.brightness = (settings.brightness + 0.1).clamp(MIN_BRIGHTNESS, MAX_BRIGHTNESS);
+                        info!("Settings brightness changed: {:.1}", settings.brightness);
+                    }
+                }
+                SettingsTab::Accessibility => {
+                    if is_key_pressed(KeyCode::M) {
+                        settings.reduced_motion = !settings.reduced_motion;
+                        info!("Settings reduced_motion toggled: {}", settings.reduced_motion);
+                    }
+                    if is_key_pressed(KeyCode::J) {
+                        settings.aim_assist = !settings.aim_assist;
+                        info!("Settings aim_assist toggled: {}", settings.aim_assist);
+                    }
+                    if is_key_pressed(KeyCode::U) {
+                        settings.ufo_alerts = !settings.ufo_alerts;
+                        info!("Settings ufo_alerts toggled: {}", settings.ufo_alerts);
+                    }
+                    if is_key_pressed(KeyCode::P) {
+                        settings.palette = settings.palette.next();
+                        info!("Settings palette changed: {:?}", settings.palette);
+                    }
+                }
             }
-            if is_key_pressed(KeyCode::F) {
-                settings.show_fps = !settings.show_fps;
-                info!("Settings show_fps toggled: {}", settings.show_fps);
+            if is_key_pressed(KeyCode::X) {
+                let code = settings.to_share_code();
+                clipboard_set(&code);
+                info!("Settings share code: {}", code);
+                share_message = "Share code copied to clipboard".to_string();
             }
 
-            if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Escape) {
+            if menu_pressed(KeyCode::Enter, &pad) || menu_pressed(KeyCode::Escape, &pad) {
                 let save_settings = settings.clone();
                 let save_path = settings_path.clone();
                 thread::spawn(move || {
@@ -204,17 +888,59 @@ async fn main() {
         }
 
         if game_state == GameState::Paused {
-            draw_pause_screen();
-            if is_key_pressed(KeyCode::P) {
+            // Boss rush and the daily challenge are one-sitting runs
+            let can_save = !campaign.boss_rush && daily.is_none() && !sandbox;
+            draw_pause_screen(settings.keys.pause, can_save);
+            draw_text(&share_message, 20., screen_height() - 20., 18., YELLOW);
+            if can_save && save_game_rx.is_none() && is_key_pressed(KeyCode::F) {
+                let snapshot = GameSnapshot {
+                    score: score.score,
+                    lives: lives.counts().to_vec(),
+                    current_level_idx: campaign.level_idx,
+                    endless_wave: campaign.endless_wave,
+                    last_extra_life_score,
+                    players: world.players.clone(),
+                    asteroids: world.asteroids.clone(),
+                    ufos: world.ufos.clone(),
+                    bosses: world.bosses.clone(),
+                    bullets: world.bullets.clone(),
+                };
+                let (tx, rx) = mpsc::channel();
+                save_game_rx = Some(rx);
+                let save_path = save_game_path.clone();
+                thread::spawn(move || {
+                    let _ = tx.send(snapshot.save_to_file(&save_path));
+                });
+                share_message = "Saving...".to_string();
+            }
+            if is_key_pressed(settings.keys.pause) || pad.pressed(PadButton::Start) {
                 info!("Paused -> Playing");
                 game_state = GameState::Playing;
-            }
-            if is_key_pressed(KeyCode::S) {
+            } else if is_key_pressed(KeyCode::S) {
                 info!("Paused -> Settings");
                 settings_origin = SettingsOrigin::Pause;
                 game_state = GameState::Settings;
             }
-            if is_key_pressed(KeyCode::Escape) {
+            if is_key_pressed(KeyCode::R) {
+                // Back to the start of this level, keeping score and lives
+                let restart_cfg = campaign.endless_wave
+                    .map(LevelConfig::procedural)
+                    .as_ref()
+                    .unwrap_or(&levels[campaign.level_idx])
+                    .scaled(settings.advanced.enemy_speed, settings.advanced.spawn_density);
+                if let Some(seed) = daily {
+                    level_rng = seed_daily_level(seed, campaign.level_idx);
+                }
+                start_level(&mut world, &restart_cfg, coop, settings.edge_entry, &mut shapes, &mut level_rng, SAFE_SPAWN_CLEARANCE);
+                for player in world.players.iter_mut() {
+                    player.invulnerable = true;
+                    player.invulnerable_timer = 3.0;
+                }
+                storm.reset();
+                info!("Paused -> Playing (level {} restarted)", campaign.level_idx + 1);
+                game_state = GameState::Playing;
+            }
+            if menu_pressed(KeyCode::Escape, &pad) {
                 info!("Paused -> Title");
                 game_state = GameState::Title;
             }
@@ -227,30 +953,132 @@ async fn main() {
             clear_background(BLACK);
             let title = if game_won { "YOU WIN!" } else { "GAME OVER" };
             draw_text(title, screen_width()/2. - 100., screen_height()/2. - 50., 40., WHITE);
-            draw_text(&format!("Final Score: {}", score), screen_width()/2. - 100., screen_height()/2., 30., GREEN);
+            draw_text(&format!("Final Score: {}", score.score), screen_width()/2. - 100., screen_height()/2., 30., GREEN);
             draw_text("Press R to Restart", screen_width()/2. - 120., screen_height()/2. + 50., 20., GRAY);
             draw_text("Press Esc to Quit", screen_width()/2. - 110., screen_height()/2. + 80., 20., GRAY);
-            
+            if !high_score_recorded {
+                high_score_recorded = true;
+                event_log.push(game_clock.elapsed() - run_start, GameEvent::RunEnded { score: score.score });
+                let run_log = event_log.clone();
+                thread::spawn(move || {
+                    if let Err(err) = run_log.dump_to_file(EVENT_LOG_PATH) {
+                        warn!("{}", err);
+                    }
+                });
+                high_score_rank = high_scores.record_run(ScoreEntry::new(&default_player_name(), score.score, settings.difficulty.clone()), sandbox);
+                if let Some(rank) = high_score_rank {
+                    info!("New high score {} at rank {}", score.score, rank + 1);
+                    name_entry = Some(high_scores.entries[rank].name.clone());
+                    // Keys pressed during the last moments of the run aren't part of the name
+                    while get_char_pressed().is_some() {}
+                }
+            }
+            draw_high_scores(&high_scores, high_score_rank);
+            if let Some(date) = daily {
+                if !daily_recorded {
+                    daily_recorded = true;
+                    if daily_records.submit(date, score.score) {
+                        info!("New daily challenge best for {}: {}", format_daily_seed(date), score.score);
+                        let save_records = daily_records.clone();
+                        let save_path = daily_path.clone();
+                        let best_run = ghost_recorder.as_ref().map(|rec| rec.finish(score.score));
+                        thread::spawn(move || {
+                            let _ = save_records.save_to_file(&save_path);
+                            // Export the run so it can be shared and raced
+                            if let Some(ghost) = best_run {
+                                let _ = ghost.save(&ghost_path(date));
+                            }
+                        });
+                    }
+                }
+                let best = daily_records.best_for(date).unwrap_or(score.score);
+                draw_text(
+                    &format!("Daily Challenge {}  Best: {}", format_daily_seed(date), best),
+                    screen_width()/2. - 160., screen_height()/2. + 120., 20., SKYBLUE,
+                );
+            }
+            if campaign.boss_rush {
+                draw_text(
+                    &format!("Boss Rush: {}/{} cleared in {:.2}s", campaign.boss_rush_cleared, boss_rush_total, campaign.boss_rush_time),
+                    screen_width()/2. - 160., screen_height()/2. + 120., 20., SKYBLUE,
+                );
+                for (i, t) in boss_rush_records.times.iter().take(5).enumerate() {
+                    draw_text(&format!("{}. {:.2}s", i + 1, t), screen_width()/2. - 60., screen_height()/2. + 145. + i as f32 * 20., 18., LIGHTGRAY);
+                }
+            }
+
+            if let Some(name) = &mut name_entry {
+                draw_name_prompt(name);
+                while let Some(c) = get_char_pressed() {
+                    if !c.is_control() && name.chars().count() < MAX_NAME_LEN {
+                        name.push(c);
+                    }
+                }
+                if is_key_pressed(KeyCode::Backspace) {
+                    name.pop();
+                }
+                if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Escape) {
+                    if let Some(rank) = high_score_rank {
+                        high_scores.rename(rank, name);
+                        info!("High score entered as {}", high_scores.entries[rank].name);
+                    }
+                    name_entry = None;
+                    let save_scores = high_scores.clone();
+                    let save_path = high_scores_path.clone();
+                    thread::spawn(move || {
+                        let _ = save_scores.save_to_file(&save_path);
+                    });
+                }
+                next_frame().await;
+                continue;
+            }
+
             if is_key_pressed(KeyCode::R) {
-                current_level_idx = 0;
-                score = 0;
-                lives = 3;
+                campaign = Campaign::start(levels, campaign.boss_rush);
+                if daily.is_some() {
+                    // A restart after midnight plays the new day's challenge
+                    daily = Some(daily_seed());
+                    daily_recorded = false;
+                    ghost_recorder = daily.map(GhostRecorder::new);
+                } else if let Some(seed) = settings.seed {
+                    // A fixed seed replays the same run
+                    level_rng = GameRng::new(seed);
+                    particle_spawner.reseed(seed);
+                }
+                score = ScoreState::new();
+                lives = Lives::new(settings.coop_lives, settings.advanced.player_lives as i32, if coop { 2 } else { 1 });
                 last_extra_life_score = 0;
                 game_over = false;
+                high_score_recorded = false;
+                run_start = game_clock.elapsed();
+                event_log.clear();
+                sweeps.clear();
                 game_won = false;
                 game_state = GameState::Playing;
-                player.pos = vec2(screen_width() / 2., screen_height() / 2.);
-                player.vel = vec2(0., 0.);
-                player.active = true;
-                player.invulnerable = true;
-                player.invulnerable_timer = 3.0;
+                world.respawn_team(coop);
+                for player in world.players.iter_mut() {
+                    player.invulnerable = true;
+                    player.invulnerable_timer = 3.0;
+                }
                 info!("Restart: player respawned at center with 3s invulnerability.");
-                bullets.clear();
-                let restart_cfg = levels[current_level_idx].scaled(
-                    settings.difficulty.speed_multiplier(),
-                    settings.difficulty.spawn_multiplier(),
+                world.bullets.clear();
+                world.reset_powerups();
+                kill_feed.clear();
+                last_stand.clear();
+                overdrive = Overdrive::new();
+                bullet_time = BulletTime::new();
+                director = AdaptiveDirector::new(lives.total());
+                let restart_cfg = levels[campaign.level_idx].scaled(
+                    settings.advanced.enemy_speed,
+                    settings.advanced.spawn_density,
                 );
-                load_level(&restart_cfg, &mut asteroids, &mut ufos);
+                if let Some(seed) = daily {
+                    shapes.seed = Some(seed);
+                    level_rng = seed_daily_level(seed, campaign.level_idx);
+                }
+                load_level(&restart_cfg, &mut world, settings.edge_entry, &mut shapes, &mut level_rng);
+                world.move_team_to_safety(SAFE_SPAWN_CLEARANCE);
+                storm.reset();
             }
 
             if is_key_pressed(KeyCode::Escape) {
@@ -261,22 +1089,128 @@ async fn main() {
             continue;
         }
 
+        // Minimized: the window has no area, so hold the game still until it's back
+        if screen_width() <= 0. || screen_height() <= 0. {
+            next_frame().await;
+            continue;
+        }
+
+        // --- TUNING CONSOLE (`) ---: holds the game while commands are typed
+        if !settings.keys.uses(KeyCode::GraveAccent) && is_key_pressed(KeyCode::GraveAccent) {
+            console.toggle();
+            // Drop whatever was typed while flying, the backtick included
+            while get_char_pressed().is_some() {}
+        }
+        if console.open {
+            while let Some(c) = get_char_pressed() {
+                console.type_char(c);
+            }
+            if is_key_pressed(KeyCode::Backspace) {
+                console.backspace();
+            }
+            if is_key_pressed(KeyCode::Escape) {
+                console.toggle();
+            }
+            if is_key_pressed(KeyCode::Enter) {
+                if let Some(line) = console.submit() {
+                    let reply = match parse_command(&line) {
+                        Ok(Command::SetVolume(volume)) => {
+                            settings.volume = volume;
+                            resources.set_volume(volume);
+                            format!("volume = {:.2}", volume)
+                        }
+                        Ok(Command::SetDifficulty(difficulty)) => {
+                            settings.set_difficulty(difficulty, &game_config.difficulty);
+                            format!("difficulty = {:?}", settings.difficulty)
+                        }
+                        Ok(Command::SpawnUfo) => {
+                            force_ufo = true;
+                            "UFO incoming".to_string()
+                        }
+                        Ok(Command::GiveLife) => {
+                            lives.gain();
+                            format!("lives = {}", lives.total())
+                        }
+                        Ok(Command::Level(_)) if campaign.boss_rush || daily.is_some() => {
+                            "error: level jumps aren't available in boss rush or the daily".to_string()
+                        }
+                        Ok(Command::Level(n)) if n > levels.len() => {
+                            format!("error: there are only {} levels", levels.len())
+                        }
+                        Ok(Command::Level(n)) => {
+                            campaign.level_idx = n - 1;
+                            campaign.endless_wave = None;
+                            let level_cfg = levels[campaign.level_idx].scaled(
+                                settings.advanced.enemy_speed,
+                                settings.advanced.spawn_density,
+                            );
+                            start_level(&mut world, &level_cfg, coop, settings.edge_entry, &mut shapes, &mut level_rng, SAFE_SPAWN_CLEARANCE);
+                            storm.reset();
+                            format!("level = {}", n)
+                        }
+                        Ok(Command::Help) => CONSOLE_HELP.to_string(),
+                        Err(e) => format!("error: {}", e),
+                    };
+                    info!("Console: {} -> {}", line.trim(), reply);
+                    console.print(reply);
+                }
+            }
+            clear_background(BLACK);
+            set_camera(&world_camera(&world.bounds));
+            draw_world(&world, fixed_step.alpha(), game_clock.elapsed(), settings.color_scheme, settings.palette);
+            set_default_camera();
+            draw_console(&console);
+            next_frame().await;
+            continue;
+        }
+
+        // A hitch (debugger pause, alt-tab) advances the game by at most one slow frame
+        let real_dt = get_frame_time().min(MAX_FRAME_DT);
+        last_stand.update(real_dt);
+        // BULLET TIME (V Key): slows the world but not the controls
+        if !settings.keys.uses(KeyCode::V) && is_key_pressed(KeyCode::V) {
+            let was_active = bullet_time.is_active();
+            if bullet_time.toggle() {
+                resources.play(SoundId::SlowMo);
+                info!("Bullet time on ({:.0}% meter)", bullet_time.meter() * 100.);
+            } else if was_active {
+                info!("Bullet time ended early");
+            }
+        }
+        bullet_time.update(real_dt);
+        let input_dt = real_dt * last_stand.time_scale();
+        let dt = scaled_dt(input_dt, bullet_time.time_scale());
+        game_clock.tick(dt);
+        if let (Some(recorder), Some(ship)) = (&mut ghost_recorder, world.players.iter().find(|p| p.active)) {
+            recorder.record(dt, ship.pos, ship.rotation);
+        }
+        if settings.magnetic_storms {
+            if storm.update(dt) {
+                info!("Magnetic storm: HUD scrambled for {:.0}s", STORM_DURATION);
+            }
+        } else if storm.is_active() {
+            storm.reset();
+        }
+
         // --- MULTITASKING: BACKGROUND SCANNER ---
-        // Press 'S' to offload a task to another thread
-        if !is_scanning && is_key_pressed(KeyCode::S) {
+        // Press 'S' to offload a task to another thread (unless S is bound to a flight control)
+        if settings.enable_scanner && !is_scanning && !storm.is_active() && !settings.keys.uses(KeyCode::S) && is_key_pressed(KeyCode::S) {
             info!("Starting background scan...");
             is_scanning = true;
             scan_message = "Scanning Deep Space...".to_string();
+            scan_result = None;
+            scan_deadline = game_clock.deadline_after(SCAN_DURATION);
             
             let (tx, rx) = mpsc::channel();
             scan_receiver = Some(rx);
 
             // Spawn a separate OS thread to do "heavy work" without freezing the game
+            let scan_bonus = scores.scan;
             thread::spawn(move || {
                 // Simulate heavy calculation (blocking this thread)
                 thread::sleep(Duration::from_secs(2)); 
                 // Send result back to main thread
-                let _ = tx.send("Sector Analysis Complete: Data Cache Found! +500 pts".to_string());
+                let _ = tx.send(format!("Sector Analysis Complete: Data Cache Found! +{} pts", scan_bonus));
             });
         }
 
@@ -284,24 +1218,42 @@ async fn main() {
         if is_scanning {
             if let Some(rx) = &scan_receiver {
                 if let Ok(msg) = rx.try_recv() {
-                    // Message received! Thread has finished.
-                    is_scanning = false;
-                    scan_message = msg;
-                    scan_message_timer = 3.0; // Show result for 3 seconds
-                    score += 500;
-                    info!("Background scan complete. Bonus awarded. Score: {}", score);
-                    // resources.play("warp"); // Optional: Audio feedback
+                    // Message received! Thread has finished; hold it until the game clock catches up.
+                    scan_result = Some(msg);
+                    scan_receiver = None;
                 }
             }
+            // The reward is gated on gameplay time, so pausing also pauses the scan
+            if !settings.enable_scanner {
+                // Disabled mid-scan: drop the pending result without a bonus
+                is_scanning = false;
+                scan_receiver = None;
+                scan_result = None;
+            } else if scan_result.is_some() && game_clock.reached(scan_deadline) {
+                is_scanning = false;
+                scan_message = scan_result.take().unwrap_or_default();
+                scan_message_timer = 3.0; // Show result for 3 seconds
+                score.add(scores.scan);
+                kill_feed.push("Sector Scan", scores.scan);
+                info!("Background scan complete. Bonus awarded. Score: {}", score.score);
+                // resources.play(SoundId::Warp); // Optional: Audio feedback
+            }
         }
 
         // --- DESIGN MODE (Secret Tool) ---
-        if is_key_pressed(KeyCode::D) {
+        if !settings.keys.uses(KeyCode::D) && is_key_pressed(KeyCode::D) {
             design_mode = !design_mode;
             info!("Design mode toggled: {}", if design_mode { "ON" } else { "OFF" });
             // Reset velocity if entering design mode to prevent drifting while editing
             if design_mode {
-                player.vel = vec2(0., 0.);
+                world.players[0].vel = vec2(0., 0.);
+            } else {
+                // Keep the design for restarts and later sessions
+                let save_settings = settings.clone();
+                let save_path = settings_path.clone();
+                thread::spawn(move || {
+                    let _ = save_settings.save_to_file(&save_path);
+                });
             }
         }
 
@@ -309,15 +1261,19 @@ async fn main() {
             clear_background(DARKGRAY);
             
             // Design Inputs
-            if is_key_pressed(KeyCode::Right) && player.sides < 8 { player.sides += 1; }
-            if is_key_pressed(KeyCode::Left) && player.sides > 3 { player.sides -= 1; }
-            
-            if is_key_pressed(KeyCode::Up) && player.radius < 50. { player.radius += 1.; }
-            if is_key_pressed(KeyCode::Down) && player.radius > 5. { player.radius -= 1.; }
+            let design = (settings.ship_sides, settings.ship_radius, settings.ship_color);
+            if is_key_pressed(KeyCode::Right) { settings.ship_sides = settings.ship_sides.saturating_add(1); }
+            if is_key_pressed(KeyCode::Left) { settings.ship_sides = settings.ship_sides.saturating_sub(1); }
+
+            if is_key_pressed(KeyCode::Up) { settings.ship_radius += 1.; }
+            if is_key_pressed(KeyCode::Down) { settings.ship_radius -= 1.; }
 
             if is_key_pressed(KeyCode::C) {
-                current_color_idx = (current_color_idx + 1) % available_colors.len();
-                player.color = available_colors[current_color_idx];
+                settings.ship_color = (settings.ship_color + 1) % SHIP_COLORS.len();
+            }
+            if (settings.ship_sides, settings.ship_radius, settings.ship_color) != design {
+                settings.clamp();
+                world.apply_ship_design(settings.ship_sides, settings.ship_radius, SHIP_COLORS[settings.ship_color]);
             }
 
             // Draw Preview
@@ -327,485 +1283,337 @@ async fn main() {
             draw_text("Press 'D' to Return", screen_width()/2. - 70., 120., 20., LIGHTGRAY);
 
             // Display current stats
-            draw_text(&format!("Sides: {}", player.sides), 50., screen_height() - 100., 20., WHITE);
-            draw_text(&format!("Radius: {:.1}", player.radius), 50., screen_height() - 80., 20., WHITE);
+            draw_text(&format!("Sides: {}", world.players[0].sides), 50., screen_height() - 100., 20., WHITE);
+            draw_text(&format!("Radius: {:.1}", world.players[0].radius), 50., screen_height() - 80., 20., WHITE);
 
             // Draw Ship (Static centered)
-            draw_poly_lines(screen_width()/2., screen_height()/2., player.sides, player.radius, -90., 3., player.color);
+            draw_poly_lines(screen_width()/2., screen_height()/2., world.players[0].sides, world.players[0].radius, -90., 3., world.players[0].color);
 
             next_frame().await;
             continue;
         }
 
+        let run_time = game_clock.elapsed() - run_start;
+
         // --- CHECK EXTRA LIFE ---
-        if check_extra_life(score, &mut last_extra_life_score) {
-            lives += 1;
-            resources.play("warp"); // Joyful sound
-            info!("Extra life gained! Lives: {}", lives);
+        if check_extra_life(score.score, &mut last_extra_life_score) {
+            lives.gain();
+            resources.play(SoundId::Warp); // Joyful sound
+            info!("Extra life gained! Lives: {:?}", lives.counts());
+            event_log.push(run_time, GameEvent::ExtraLife { lives: lives.total() });
         }
 
-        let level_cfg = &levels[current_level_idx];
+        let wave_cfg = campaign.endless_wave.map(LevelConfig::procedural);
+        let level_cfg = wave_cfg.as_ref().unwrap_or(&levels[campaign.level_idx]);
         let effective_cfg = level_cfg.scaled(
-            settings.difficulty.speed_multiplier(),
-            settings.difficulty.spawn_multiplier(),
+            settings.advanced.enemy_speed,
+            settings.advanced.spawn_density,
         );
 
-        // --- SPAWN LOGIC (UFO) ---
-        if ufos.len() < 1 && gen_range(0.0, 1.0) < effective_cfg.ufo_spawn_chance {
-            resources.play("warp"); // Sound cue for UFO entry
-            ufos.push(Ufo {
-                pos: vec2(0., gen_range(0., screen_height())), 
-                vel: vec2(effective_cfg.ufo_speed, 0.),
-                radius: 20.,
-                active: true,
-                shoot_timer: 0.,
-                change_dir_timer: 0.,
-            });
-        }
-
-        // --- LEVEL PROGRESSION ---
-        if asteroids.is_empty() && ufos.is_empty() {
-            current_level_idx += 1;
-            if current_level_idx >= levels.len() {
-                game_won = true;
-                info!("All levels cleared. Game won.");
-            } else {
-                info!("Level complete. Advancing to level {}", current_level_idx + 1);
-                player.pos = vec2(screen_width() / 2., screen_height() / 2.);
-                player.vel = vec2(0., 0.);
-                bullets.clear();
-                let next_cfg = levels[current_level_idx].scaled(
-                    settings.difficulty.speed_multiplier(),
-                    settings.difficulty.spawn_multiplier(),
-                );
-                load_level(&next_cfg, &mut asteroids, &mut ufos);
-            }
-        }
-
-        // --- PRE-INPUT UPDATES ---
-        // Invulnerability countdown (used after respawn to avoid instant re-hit)
-        if player.invulnerable {
-            player.invulnerable_timer -= get_frame_time();
-            if player.invulnerable_timer <= 0.0 {
-                player.invulnerable = false;
-                info!("Invulnerability ended.");
-            }
-        }
-
         // --- INPUT ---
         if is_key_pressed(KeyCode::Escape) {
-            break;
+            if settings.confirm_quit { confirm_exit = true; } else { break; }
         }
 
-        if is_key_pressed(KeyCode::P) {
+        // Mute (M): instant silence that keeps the volume setting
+        if !settings.keys.uses(KeyCode::M) && is_key_pressed(KeyCode::M) {
+            settings.muted = !settings.muted;
+            resources.set_muted(settings.muted);
+            info!("Muted toggled: {}", settings.muted);
+            let save_settings = settings.clone();
+            let save_path = settings_path.clone();
+            thread::spawn(move || {
+                let _ = save_settings.save_to_file(&save_path);
+            });
+        }
+
+        if is_key_pressed(settings.keys.pause) || pad.pressed(PadButton::Start) {
             info!("Playing -> Paused");
             game_state = GameState::Paused;
             next_frame().await;
             continue;
         }
 
-        let rotation_speed = 4.0;
-        let thrust_power = 0.15;
-        
-        if is_key_down(KeyCode::Left) { player.rotation -= rotation_speed * get_frame_time(); }
-        if is_key_down(KeyCode::Right) { player.rotation += rotation_speed * get_frame_time(); }
-        
-        if is_key_down(KeyCode::Up) {
-            let direction = vec2(player.rotation.cos(), player.rotation.sin());
-            player.vel += direction * thrust_power;
-        }
-
-        // Shoot
-        if is_key_pressed(KeyCode::Space) {
-            resources.play("shoot");
-            let direction = vec2(player.rotation.cos(), player.rotation.sin());
-            bullets.push(Bullet {
-                pos: player.pos + direction * player.radius,
-                vel: direction * 8.0,
-                lifetime: 1.5,
-                active: true,
-                owner: BulletOwner::Player,
-            });
-            info!("Player fired. Bullets active: {}", bullets.len());
-
-            particle_spawner.request(SpawnRequest {
-                pos: player.pos + direction * player.radius,
-                color: YELLOW,
-                count: 8,
-                speed: 80.0,
-                life: 0.25,
-                size: 2.0,
-            });
-        }
-
-        // HYPERSPACE (Shift Key)
-        if is_key_pressed(KeyCode::LeftShift) {
-            resources.play("warp");
-            player.pos = vec2(gen_range(0., screen_width()), gen_range(0., screen_height()));
-            player.vel = vec2(0., 0.); // Reset velocity for safety
-            info!("Hyperspace jump to ({:.1}, {:.1})", player.pos.x, player.pos.y);
-        }
-
-        // --- PHYSICS & UPDATES ---
-
-        // Player
-        player.pos += player.vel;
-        player.vel *= 0.98; // Friction
-        player.pos = wrap_screen(player.pos);
-
-        // Particles
-        particle_system.update(get_frame_time());
-
-        // Bullets
-        for bullet in bullets.iter_mut() {
-            bullet.pos += bullet.vel;
-            bullet.lifetime -= get_frame_time();
-            if bullet.lifetime <= 0. { bullet.active = false; }
-            bullet.pos = wrap_screen(bullet.pos);
+        // Player one follows the chosen control mode; player two always uses the co-op keys
+        let mut player_one = poll_controls(&settings.keys.ship(), &pad, settings.auto_fire);
+        match settings.control_mode {
+            ControlMode::Classic => {}
+            ControlMode::OneButton => {
+                // Single turn key: hold to keep spinning one way, release to stop
+                player_one.turn = if is_key_down(settings.keys.rotate_right) { 1.0 } else { 0.0 };
+            }
+            ControlMode::Mouse => {
+                player_one.turn = 0.0;
+                if is_mouse_button_pressed(MouseButton::Left) {
+                    player_one.fire = true;
+                    player_one.fire_repeat = false;
+                } else if settings.auto_fire && is_mouse_button_down(MouseButton::Left) && !player_one.fire {
+                    player_one.fire = true;
+                    player_one.fire_repeat = true;
+                }
+                let cursor = world_camera(&world.bounds).screen_to_world(mouse_position().into());
+                if let Some(ship) = world.players.first_mut().filter(|p| p.active && p.stun_timer <= 0.0) {
+                    let rotation_speed = settings.ship.rotation_speed * settings.advanced.player_handling;
+                    if let Some(target) = angle_toward(ship.pos, cursor, 1.) {
+                        ship.rotation = turn_towards(ship.rotation, target, rotation_speed * input_dt);
+                    }
+                }
+            }
         }
-
-        // Asteroids
-        for asteroid in asteroids.iter_mut() {
-            asteroid.pos += asteroid.vel;
-            asteroid.pos = wrap_screen(asteroid.pos);
+        let mut frame_input = FrameInput { ships: vec![player_one] };
+        if coop {
+            frame_input.ships.push(poll_controls(&PLAYER_TWO_KEYS, &PadState::default(), settings.auto_fire));
         }
 
-        // UFO Logic
-        for ufo in ufos.iter_mut() {
-            ufo.pos += ufo.vel;
-            ufo.pos = wrap_screen(ufo.pos);
-            ufo.change_dir_timer += get_frame_time();
-            ufo.shoot_timer += get_frame_time();
-
-            if ufo.change_dir_timer > 2.0 {
-                ufo.vel.y = gen_range(-1.0, 1.0) * effective_cfg.ufo_speed;
-                ufo.change_dir_timer = 0.;
+        // OVERDRIVE (B Key): spend a full meter on a field-wide shockwave
+        if !settings.keys.uses(KeyCode::B) && is_key_pressed(KeyCode::B) && overdrive.try_activate() {
+            resources.play(SoundId::Bang);
+            let report = shockwave(&mut world);
+            let mut points = 0;
+            for (pos, radius) in &report.asteroids {
+                points += scores.asteroid_points(AsteroidSize::from_radius(*radius)) / 2;
+                event_log.push(run_time, GameEvent::AsteroidDestroyed { radius: *radius });
+                particle_spawner.request(SpawnRequest { pos: *pos, ..explosion_preset(*radius, settings.palette.color(Role::Particle)) });
             }
-
-            if ufo.shoot_timer > 2.0 {
-                ufo.shoot_timer = 0.;
-                resources.play("shoot"); 
-                // AI Aiming
-                let target = if asteroids.len() > 0 && gen_range(0, 3) == 0 {
-                    asteroids[0].pos 
-                } else {
-                    player.pos 
-                };
-
-                let aim_dir = (target - ufo.pos).normalize();
-                bullets.push(Bullet {
-                    pos: ufo.pos + aim_dir * ufo.radius,
-                    vel: aim_dir * 6.0,
-                    lifetime: 2.0,
-                    active: true,
-                    owner: BulletOwner::Ufo,
-                });
+            for _ in 0..report.ufos.len() {
+                event_log.push(run_time, GameEvent::UfoDestroyed);
             }
-        }
-
-        // --- COLLISIONS ---
-        
-        let mut new_asteroids = Vec::new();
-        
-        for bullet in bullets.iter_mut() {
-            if !bullet.active { continue; }
-
-            // Bullet vs Asteroid
-            for asteroid in asteroids.iter_mut() {
-                if !asteroid.active { continue; }
-                if check_collision(bullet.pos, 0., asteroid.pos, asteroid.radius) {
-                    bullet.active = false;
-                    asteroid.active = false;
-                    resources.play("bang");
-                    
-                    if bullet.owner == BulletOwner::Player { 
-                        score += 100; 
-                        info!("Asteroid destroyed. Score: {}", score);
-                        particle_spawner.request(SpawnRequest {
-                            pos: asteroid.pos,
-                            color: GRAY,
-                            count: 28,
-                            speed: 120.0,
-                            life: 0.9,
-                            size: 2.5,
-                        });
-                    }
-
-                    // Split asteroid
-                    if asteroid.radius > 15.0 {
-                        let new_radius = asteroid.radius / 2.0;
-                        for _ in 0..2 {
-                            new_asteroids.push(Asteroid {
-                                pos: asteroid.pos,
-                                vel: vec2(gen_range(-2., 2.), gen_range(-2., 2.)),
-                                radius: new_radius,
-                                active: true,
-                                sides: gen_range(5, 9),
-                            });
-                        }
-                    }
-                    break;
-                }
-            }
-
-            // Bullet vs UFO
-            if bullet.active && bullet.owner == BulletOwner::Player {
-                for ufo in ufos.iter_mut() {
-                    if !ufo.active { continue; }
-                    if check_collision(bullet.pos, 0., ufo.pos, ufo.radius) {
-                        ufo.active = false;
-                        bullet.active = false;
-                        resources.play("bang");
-                        score += 500; 
-                        info!("UFO destroyed. Score: {}", score);
-                        particle_spawner.request(SpawnRequest {
-                            pos: ufo.pos,
-                            color: RED,
-                            count: 32,
-                            speed: 140.0,
-                            life: 0.8,
-                            size: 3.0,
-                        });
-                    }
-                }
+            for _ in 0..report.bosses_destroyed {
+                event_log.push(run_time, GameEvent::BossDestroyed);
             }
-
-            // Bullet vs Player
-            if bullet.active && bullet.owner == BulletOwner::Ufo {
-                 if !player.invulnerable && check_collision(bullet.pos, 0., player.pos, player.radius) {
-                     resources.play("bang");
-                     lives -= 1;
-                     bullet.active = false;
-                     particle_spawner.request(SpawnRequest {
-                         pos: player.pos,
-                         color: ORANGE,
-                         count: 30,
-                         speed: 160.0,
-                         life: 0.9,
-                         size: 3.0,
-                     });
-                     if lives <= 0 {
-                         game_over = true;
-                         warn!("Game Over: Player hit by UFO bullet. Lives: 0");
-                     } else {
-                         warn!("Player hit by UFO bullet. Lives remaining: {}", lives);
-                         player.pos = vec2(gen_range(0., screen_width()), gen_range(0., screen_height()));
-                         player.vel = vec2(0., 0.);
-                         player.invulnerable = true;
-                         player.invulnerable_timer = 3.0;
-                         info!(
-                             "Respawned after UFO bullet at ({:.1}, {:.1}) with 3s invulnerability.",
-                             player.pos.x, player.pos.y
-                         );
-                     }
-                 }
-            }
-        }
-
-        // Physical Collisions (Ship vs Asteroid / UFO vs Asteroid / Ship vs UFO)
-        for asteroid in asteroids.iter_mut() {
-            if !asteroid.active { continue; }
-            
-            // Player vs Asteroid
-            if !player.invulnerable && check_collision(player.pos, player.radius, asteroid.pos, asteroid.radius) {
-                resources.play("bang");
-                lives -= 1;
-                asteroid.active = false; // Destroy asteroid on impact
+            points += report.ufos.len() as u32 * scores.ufo / 2;
+            points += report.bosses_destroyed * scores.boss;
+            score.add(points);
+            kill_feed.push("Overdrive", points);
+            for player in world.players.iter().filter(|p| p.active) {
                 particle_spawner.request(SpawnRequest {
                     pos: player.pos,
-                    color: ORANGE,
-                    count: 30,
-                    speed: 160.0,
-                    life: 0.9,
+                    color: SKYBLUE,
+                    count: 160,
+                    speed: 420.0,
+                    life: 1.0,
                     size: 3.0,
+                    ..Default::default()
                 });
+            }
+            info!(
+                "Overdrive shockwave: {} asteroids, {} UFOs, +{} pts",
+                report.asteroids.len(),
+                report.ufos.len(),
+                points
+            );
+        }
 
-                // Split implicitly if large
-                if asteroid.radius > 15.0 {
-                    let new_radius = asteroid.radius / 2.0;
-                    for _ in 0..2 {
-                        new_asteroids.push(Asteroid {
-                            pos: asteroid.pos,
-                            vel: vec2(gen_range(-2., 2.), gen_range(-2., 2.)),
-                            radius: new_radius,
-                            active: true,
-                            sides: gen_range(5, 9),
-                        });
-                    }
-                }
-                
-                if lives <= 0 {
-                    game_over = true;
-                     warn!("Game Over: Player hit by asteroid. Lives: 0");
-                } else {
-                     warn!("Player hit by asteroid. Lives remaining: {}", lives);
-                     player.pos = vec2(gen_range(0., screen_width()), gen_range(0., screen_height()));
-                     player.vel = vec2(0., 0.);
-                     player.invulnerable = true;
-                     player.invulnerable_timer = 3.0;
-                     info!(
-                        "Respawned after asteroid at ({:.1}, {:.1}) with 3s invulnerability.",
-                        player.pos.x, player.pos.y
-                     );
-                }
-                // We break here to avoid processing this asteroid more in this frame (though it is marked inactive now)
-                // Need to be careful with double mutations if we didn't use continue
-            }
-        }
-
-        // UFO vs Asteroid
-        // Note: We need a second pass or careful index handling if we want to mutate both in same loop.
-        // Simplified approach: Iterate UFOs and check against asteroids.
-        for ufo in ufos.iter_mut() {
-            if !ufo.active { continue; }
-            for asteroid in asteroids.iter_mut() {
-                if !asteroid.active { continue; }
-                if check_collision(ufo.pos, ufo.radius, asteroid.pos, asteroid.radius) {
-                    ufo.active = false;
-                    asteroid.active = false; 
-
-                    if asteroid.radius > 15.0 {
-                         let new_radius = asteroid.radius / 2.0;
-                        for _ in 0..2 {
-                            new_asteroids.push(Asteroid {
-                                pos: asteroid.pos,
-                                vel: vec2(gen_range(-2., 2.), gen_range(-2., 2.)),
-                                radius: new_radius,
-                                active: true,
-                                sides: gen_range(5, 9),
-                            });
-                        }
-                    }
-
-                    resources.play("bang");
-                    info!("UFO collided with asteroid.");
-                    particle_spawner.request(SpawnRequest {
-                        pos: asteroid.pos,
-                        color: GRAY,
-                        count: 24,
-                        speed: 120.0,
-                        life: 0.8,
-                        size: 2.5,
+        // --- UPDATE ---: controls, physics, enemy fire, collisions, scoring, UFO arrivals and level progression
+        let lives_before = lives.total();
+        let mut step_ctx = StepContext {
+            settings: &settings,
+            tuning,
+            scores,
+            level: &effective_cfg,
+            levels,
+            campaign: &mut campaign,
+            director: &mut director,
+            score: &mut score,
+            lives: &mut lives,
+            overdrive: &mut overdrive,
+            sweeps: &mut sweeps,
+            kill_feed: &mut kill_feed,
+            event_log: &mut event_log,
+            particles: &particle_spawner,
+            audio: &resources,
+            shapes: &mut shapes,
+            rng: &mut level_rng,
+            fixed: &mut fixed_step,
+            sandbox,
+            coop,
+            endless,
+            daily,
+            force_ufo: std::mem::take(&mut force_ufo),
+            run_time,
+            control_dt: input_dt,
+        };
+        let report = world.step(&frame_input, dt, &mut step_ctx);
+        if report.game_over {
+            game_over = true;
+        }
+        if let Some(spawn_pos) = report.ufo_spawned.filter(|_| settings.ufo_alerts) {
+            ufo_alert = Some(EdgeAlert::from_spawn(spawn_pos, world.bounds.size()));
+        }
+        if report.level_cleared {
+            storm.reset();
+        }
+        if report.game_won {
+            game_won = true;
+            if campaign.boss_rush {
+                if let Some(rank) = boss_rush_records.insert(campaign.boss_rush_time) {
+                    info!("New boss rush record, rank {}", rank + 1);
+                    let save_records = boss_rush_records.clone();
+                    let save_path = records_path.clone();
+                    thread::spawn(move || {
+                        let _ = save_records.save_to_file(&save_path);
                     });
                 }
             }
         }
-        
-        // Player vs UFO
-        for ufo in ufos.iter_mut() {
-            if ufo.active && !player.invulnerable && check_collision(player.pos, player.radius, ufo.pos, ufo.radius) {
-                ufo.active = false; // Destroy UFO
-                resources.play("bang");
-                lives -= 1;
+
+        // Exhaust trail: a few puffs from the flame base, blown out behind the ship
+        exhaust_timers.resize(world.players.len(), EmissionTimer::new(EXHAUST_INTERVAL));
+        for (player, exhaust) in world.players.iter().zip(exhaust_timers.iter_mut()) {
+            let puffs = exhaust.tick(dt, player.thrusting && player.active);
+            let forward = vec2(player.rotation.cos(), player.rotation.sin());
+            for _ in 0..puffs {
                 particle_spawner.request(SpawnRequest {
-                    pos: player.pos,
-                    color: ORANGE,
-                    count: 30,
-                    speed: 160.0,
-                    life: 0.9,
-                    size: 3.0,
+                    pos: player.pos - forward * (player.radius * 0.8),
+                    color: if level_rng.gen_range_u32(0, 2) == 0 { ORANGE } else { YELLOW },
+                    count: 3,
+                    speed: 30.0,
+                    life: 0.25,
+                    size: 1.5,
+                    drift: player.vel * TARGET_FPS - forward * EXHAUST_SPEED,
                 });
-                if lives <= 0 {
-                    game_over = true;
-                    warn!("Game Over: Player hit by UFO. Lives: 0");
-                } else {
-                    warn!("Player hit by UFO. Lives remaining: {}", lives);
-                    player.pos = vec2(gen_range(0., screen_width()), gen_range(0., screen_height()));
-                    player.vel = vec2(0., 0.);
-                    player.invulnerable = true;
-                    player.invulnerable_timer = 3.0;
-                    info!(
-                        "Respawned after UFO collision at ({:.1}, {:.1}) with 3s invulnerability.",
-                        player.pos.x, player.pos.y
-                    );
-                }
             }
         }
 
-        // Clean up
-        bullets.retain(|b| b.active);
-        asteroids.retain(|a| a.active);
-        ufos.retain(|u| u.active);
-        asteroids.append(&mut new_asteroids);
-
-        // --- DRAW ---
-        clear_background(BLACK);
-
-        particle_system.draw();
+        particle_system.set_gravity(world.gravity, world.bounds.size());
+        particle_system.update(dt);
+        kill_feed.update(dt);
+        if let Some(alert) = ufo_alert.as_mut() {
+            if !alert.update(dt) {
+                ufo_alert = None;
+            }
+        }
 
-        let mut player_draw_color = player.color;
-        if player.invulnerable {
-             // Blink transparency effect
-             player_draw_color.a = if (get_time() * 10.0) as i32 % 2 == 0 { 0.5 } else { 0.2 };
+        // Last stand: slow things down once when the player drops to their final life
+        if dropped_to_last_life(lives_before, lives.total()) && !settings.reduced_motion {
+            info!("Last stand: down to the final life");
+            last_stand.trigger();
         }
-        draw_poly_lines(player.pos.x, player.pos.y, player.sides, player.radius, player.rotation * 180. / std::f32::consts::PI, 2., player_draw_color);
 
-        // Calculate direction vectors for visual effects
-        let rotation_rad = player.rotation;
-        let forward = vec2(rotation_rad.cos(), rotation_rad.sin());
-        
-        // --- 1. SHIP TIP HIGHLIGHT ---
-        // The "nose" is at the perimeter in the direction of rotation
-        let nose_pos = player.pos + forward * player.radius;
-        draw_circle(nose_pos.x, nose_pos.y, 3.0, RED);
-
-        // --- 2. ENGINE FLAME ---
-        // Only draw if thrusting and player is active
-        if is_key_down(KeyCode::Up) && player.active {
-            // The flame comes out of the back
-            // We use a slight offset so it looks like it's coming from the engine, not the center
-            let flame_base = player.pos - forward * (player.radius * 0.8);
-            
-            // Randomize flame length for flickering effect
-            let flickr_len = gen_range(10.0, 20.0);
-            let flame_tip = flame_base - forward * flickr_len;
-            
-            // Draw a simple triangle for the flame
-            // We need two side points at the base to make it a triangle
-            // Right vector is forward rotated 90 degrees
-            let right = vec2(forward.y, -forward.x);
-            let side_width = 5.0;
-            let p1 = flame_base + right * side_width;
-            let p2 = flame_base - right * side_width;
-            
-            draw_triangle(p1, p2, flame_tip, ORANGE);
-            draw_triangle(p1 + forward*2., p2 + forward*2., flame_tip + forward*5., YELLOW); // Inner flame
+        // Stars hold still with reduced motion
+        if !settings.reduced_motion {
+            starfield.update(world.players[0].vel, dt);
         }
 
-        for a in asteroids.iter() {
-             draw_poly_lines(a.pos.x, a.pos.y, a.sides, a.radius, 0., 2., GRAY);
+        // --- DRAW ---
+        clear_background(BLACK);
+        let scheme = settings.color_scheme;
+
+        // The playfield is drawn in logical coordinates, scaled to the window
+        let camera = world_camera(&world.bounds);
+        set_camera(&camera);
+        starfield.draw(scheme);
+
+        // Space weather zones (faint translucent circles)
+        for zone in effective_cfg.zones.iter() {
+            let center = zone.world_center(world.bounds.size());
+            let color = match zone.effect {
+                ZoneEffect::Drag(_) => Color::new(0.6, 0.3, 0.8, 0.12),
+                ZoneEffect::Push(_) => Color::new(0.3, 0.7, 1.0, 0.10),
+            };
+            draw_circle(center.x, center.y, zone.radius, scheme.apply(color));
+            draw_circle_lines(center.x, center.y, zone.radius, 1., scheme.apply(Color::new(color.r, color.g, color.b, 0.3)));
         }
 
-        for u in ufos.iter() {
-            draw_poly_lines(u.pos.x, u.pos.y, 8, u.radius, 0., 2., RED);
-            draw_line(u.pos.x - u.radius, u.pos.y, u.pos.x + u.radius, u.pos.y, 2., RED);
+        // Central star of a gravity level: its reach as a faint ring
+        if let Some(well) = &effective_cfg.gravity {
+            let center = well.world_center(world.bounds.size());
+            draw_circle_lines(center.x, center.y, well.radius, 1., scheme.apply(Color::new(1.0, 0.8, 0.3, 0.15)));
+            draw_circle(center.x, center.y, 14., scheme.apply(Color::new(1.0, 0.8, 0.3, 0.25)));
+            draw_circle(center.x, center.y, 8., scheme.apply(Color::new(1.0, 0.9, 0.5, 1.0)));
         }
 
-        for b in bullets.iter() {
-            let color = if b.owner == BulletOwner::Player { YELLOW } else { RED };
-            draw_circle(b.pos.x, b.pos.y, 2., color);
+        particle_system.draw(scheme);
+
+        if let (Some(ghost), Some(recorder), Some(date)) = (&rival_ghost, &ghost_recorder, daily) {
+            if ghost.seed == date {
+                if let Some(pose) = ghost.pose_at(recorder.elapsed()) {
+                    draw_ghost(pose.pos, pose.rotation, world.players[0].sides, world.players[0].radius, scheme);
+                }
+            }
+        }
+        draw_world(&world, fixed_step.alpha(), game_clock.elapsed(), scheme, settings.palette);
+        if settings.aim_assist {
+            let range = PLAYER_BULLET_SPEED * PLAYER_BULLET_LIFETIME * TARGET_FPS;
+            draw_aim_assist(&world, fixed_step.alpha(), range, scheme);
+        }
+        if settings.mouse_aim() {
+            let (mx, my) = mouse_position();
+            draw_crosshair(camera.screen_to_world(vec2(mx, my)), scheme.apply(world.players[0].color));
+        }
+        if let Some(alert) = &ufo_alert {
+            draw_edge_arrow(alert.anchor, alert.direction, alert.alpha(), world.bounds.size(), scheme);
         }
+        if last_stand.intensity() > 0.0 {
+            // Wash the playfield towards gray for the duration of the last stand
+            draw_rectangle(0., 0., world.bounds.width, world.bounds.height, Color::new(0.35, 0.35, 0.35, 0.4 * last_stand.intensity()));
+        }
+        set_default_camera();
+        draw_brightness_overlay(settings.brightness);
 
-        draw_text(&format!("Level: {}", current_level_idx + 1), 20., 30., 20., WHITE);
-        draw_text(&format!("Score: {}", score), 20., 55., 20., GREEN);
-        draw_text(&format!("Lives: {}", lives), 20., 80., 20., RED);
-        draw_text("Hyperspace: Shift | Quit: Esc", screen_width() - 300., 30., 20., BLUE);
+        if campaign.boss_rush {
+            if let Some(t) = campaign.boss_rush_breather {
+                draw_text(&format!("NEXT BOSS IN {:.1}", t.max(0.0)), screen_width()/2. - 90., screen_height()/2., 28., scheme.apply(MAGENTA));
+            }
+        }
+        let heading = if campaign.boss_rush {
+            format!("Boss Rush: {}/{}  Time: {:.2}s", campaign.boss_rush_cleared, boss_rush_total, campaign.boss_rush_time)
+        } else if let Some(date) = daily {
+            format!("Daily {}  Level: {}", format_daily_seed(date), campaign.level_idx + 1)
+        } else if endless || campaign.endless_wave.is_some() {
+            format!("Wave: {}", campaign.level_number(levels))
+        } else {
+            format!("Level: {}", campaign.level_idx + 1)
+        };
+        draw_hud(&heading, &score, &lives, game_clock.elapsed() - run_start, storm.is_active(), scheme);
+        draw_fuel_gauges(&world.players, scheme);
+        draw_bomb_counts(&world.players, scheme);
+        draw_overdrive_meter(overdrive.charge, scheme);
+        draw_bullet_time_meter(bullet_time.meter(), bullet_time.is_active(), bullet_time.is_ready(), scheme);
+        if settings.show_radar {
+            for (index, player) in world.players.iter().enumerate().filter(|(_, p)| p.active) {
+                draw_radar(player, index, &world.asteroids, &world.ufos, &world.bounds, scheme);
+            }
+        }
+        let hint = format!("Hyperspace: {} | Quit: Esc", key_name(settings.keys.hyperspace));
+        draw_text(&hint, screen_width() - measure_text(&hint, None, 20, 1.0).width - 20., 30., 20., scheme.apply(BLUE));
+        if settings.muted {
+            draw_text("MUTED", screen_width() / 2. - 30., 30., 20., scheme.apply(GRAY));
+        }
+        if sandbox {
+            draw_text("SANDBOX", screen_width() / 2. - 40., 55., 20., scheme.apply(ORANGE));
+        }
         if settings.show_fps {
-            draw_text(&format!("FPS: {}", get_fps()), screen_width() - 120., 55., 20., YELLOW);
+            draw_text(&format!("Particles: {}", particle_system.len()), screen_width() - 280., 55., 20., scheme.apply(YELLOW));
+            draw_text(&format!("FPS: {}", get_fps()), screen_width() - 120., 55., 20., scheme.apply(YELLOW));
+        }
+        if settings.show_kill_feed {
+            kill_feed.draw(screen_width() - 20., 85., scheme);
         }
         
         // --- SCANNER UI ---
-        if is_scanning {
-             draw_text("SCANNING SECTOR...", 20., 110., 20., SKYBLUE);
-             // Visualize the "work"
-             let dots = (get_time() * 5.0) as i32 % 4;
-             let bar = ".".repeat(dots as usize);
-             draw_text(&bar, 220., 110., 20., SKYBLUE);
-        } else if scan_message_timer > 0.0 {
-             draw_text(&scan_message, 20., 110., 20., YELLOW);
-             scan_message_timer -= get_frame_time();
-        } else {
-             draw_text("Press 'S' to Scan Sector", 20., 110., 15., GRAY);
+        if settings.enable_scanner {
+            if storm.is_active() {
+                draw_text("SCANNER OFFLINE", 20., 110., 20., scheme.apply(DARKGRAY));
+            } else if is_scanning {
+                draw_text("SCANNING SECTOR...", 20., 110., 20., scheme.apply(SKYBLUE));
+                // Visualize the "work"
+                let dots = (game_clock.elapsed() * 5.0) as i32 % 4;
+                let bar = ".".repeat(dots as usize);
+                draw_text(&bar, 220., 110., 20., scheme.apply(SKYBLUE));
+                let remaining = (scan_deadline - game_clock.elapsed()).max(0.0);
+                draw_text(&format!("{:.1}s", remaining), 260., 110., 20., scheme.apply(SKYBLUE));
+            } else if scan_message_timer > 0.0 {
+                draw_text(&scan_message, 20., 110., 20., scheme.apply(YELLOW));
+                scan_message_timer -= get_frame_time();
+            } else {
+                draw_text("Press 'S' to Scan Sector", 20., 110., 15., scheme.apply(GRAY));
+            }
+        }
+
+        // CRT scanlines go over everything; no flicker with reduced motion
+        if scheme.is_phosphor() {
+            draw_scanlines(!settings.reduced_motion);
         }
 
         next_frame().await
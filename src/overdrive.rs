@@ -0,0 +1,130 @@
+use macroquad::prelude::*;
+use crate::world::GameWorld;
+
+/// Meter gained per asteroid destroyed (before the difficulty fill rate).
+pub const OVERDRIVE_PER_KILL: f32 = 0.05;
+/// Boss hit points knocked off by a shockwave.
+pub const SHOCKWAVE_BOSS_DAMAGE: u32 = 5;
+
+/// Overdrive meter: fills on kills and, when full, can be spent on a shockwave.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Overdrive {
+    /// 0.0 (empty) to 1.0 (ready).
+    pub charge: f32,
+}
+
+impl Overdrive {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds charge for one kill, scaled by `fill_rate`.
+    pub fn add_kill(&mut self, fill_rate: f32) {
+        self.charge = (self.charge + OVERDRIVE_PER_KILL * fill_rate).min(1.0);
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.charge >= 1.0
+    }
+
+    /// Spends a full meter. Returns false (and keeps the charge) if it isn't full yet.
+    pub fn try_activate(&mut self) -> bool {
+        if !self.is_full() {
+            return false;
+        }
+        self.charge = 0.0;
+        true
+    }
+}
+
+/// What a shockwave destroyed, for scoring and effects.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ShockwaveReport {
+    /// Positions and radii of the asteroids destroyed.
+    pub asteroids: Vec<(Vec2, f32)>,
+    /// Positions of the UFOs destroyed.
+    pub ufos: Vec<Vec2>,
+    /// Bosses destroyed by the blast.
+    pub bosses_destroyed: u32,
+}
+
+/// Field-wide blast: destroys every asteroid and UFO (without splitting) and damages bosses.
+pub fn shockwave(world: &mut GameWorld) -> ShockwaveReport {
+    let mut report = ShockwaveReport::default();
+    for asteroid in world.asteroids.iter_mut().filter(|a| a.active) {
+        asteroid.active = false;
+        report.asteroids.push((asteroid.pos, asteroid.radius));
+    }
+    for ufo in world.ufos.iter_mut().filter(|u| u.active) {
+        ufo.active = false;
+        report.ufos.push(ufo.pos);
+    }
+    for boss in world.bosses.iter_mut().filter(|b| b.active) {
+        boss.hp = boss.hp.saturating_sub(SHOCKWAVE_BOSS_DAMAGE);
+        if boss.hp == 0 {
+            boss.active = false;
+            report.bosses_destroyed += 1;
+        }
+    }
+    world.asteroids.retain(|a| a.active);
+    world.ufos.retain(|u| u.active);
+    world.bosses.retain(|b| b.active);
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boss::BossPhase;
+    use crate::components::*;
+    use crate::test_support::{rock, test_world};
+
+    /// Three rocks in a row and a boss, around the lone ship.
+    fn crowded_world() -> GameWorld {
+        let mut world = test_world();
+        for i in 0..3 {
+            world.asteroids.push(rock(vec2(100. + i as f32 * 100., 100.), Vec2::ZERO, 30.));
+        }
+        world.bosses.push(Boss {
+            pos: vec2(400., 80.),
+            prev_pos: vec2(400., 80.),
+            vel: Vec2::ZERO,
+            radius: 45.,
+            active: true,
+            hp: 10,
+            max_hp: 10,
+            shoot_timer: 0.,
+            phase: BossPhase::Sweeping,
+            phase_timer: 0.,
+        });
+        world
+    }
+
+    #[test]
+    fn test_meter_fills_and_caps() {
+        let mut od = Overdrive::new();
+        for _ in 0..19 {
+            od.add_kill(1.0);
+        }
+        assert!(!od.is_full());
+        assert!(!od.try_activate());
+        od.add_kill(1.0);
+        od.add_kill(1.0);
+        assert_eq!(od.charge, 1.0);
+    }
+
+    #[test]
+    fn test_activation_clears_asteroids_and_resets_meter() {
+        let mut world = crowded_world();
+        let mut od = Overdrive { charge: 1.0 };
+        assert!(od.try_activate());
+        assert_eq!(od.charge, 0.0);
+
+        let report = shockwave(&mut world);
+        assert_eq!(report.asteroids.len(), 3);
+        assert!(world.asteroids.is_empty());
+        // Bosses survive with reduced health
+        assert_eq!(world.bosses[0].hp, 10 - SHOCKWAVE_BOSS_DAMAGE);
+        assert_eq!(report.bosses_destroyed, 0);
+    }
+}
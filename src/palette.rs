@@ -0,0 +1,244 @@
+use macroquad::prelude::*;
+
+/// Classic CRT green.
+const GREEN_PHOSPHOR: Color = Color::new(0.2, 1.0, 0.35, 1.0);
+/// Classic CRT amber.
+const AMBER_PHOSPHOR: Color = Color::new(1.0, 0.7, 0.15, 1.0);
+/// Brightness setting range (1.0 = unchanged).
+pub const MIN_BRIGHTNESS: f32 = 0.5;
+pub const MAX_BRIGHTNESS: f32 = 1.5;
+/// Strongest white wash at maximum brightness; kept low so the field doesn't turn gray.
+const MAX_BRIGHTEN_ALPHA: f32 = 0.25;
+
+/// Overall look of the playfield.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorScheme {
+    /// Full color.
+    Standard,
+    /// Everything in shades of green with scanlines.
+    GreenPhosphor,
+    /// Everything in shades of amber with scanlines.
+    AmberPhosphor,
+}
+
+impl ColorScheme {
+    pub fn from_name(value: &str) -> Option<Self> {
+        match value {
+            "standard" => Some(ColorScheme::Standard),
+            "green" => Some(ColorScheme::GreenPhosphor),
+            "amber" => Some(ColorScheme::AmberPhosphor),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorScheme::Standard => "standard",
+            ColorScheme::GreenPhosphor => "green",
+            ColorScheme::AmberPhosphor => "amber",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColorScheme::Standard => "Standard",
+            ColorScheme::GreenPhosphor => "Green Phosphor",
+            ColorScheme::AmberPhosphor => "Amber Phosphor",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            ColorScheme::Standard => ColorScheme::GreenPhosphor,
+            ColorScheme::GreenPhosphor => ColorScheme::AmberPhosphor,
+            ColorScheme::AmberPhosphor => ColorScheme::Standard,
+        }
+    }
+
+    /// Whether this is a monochrome CRT scheme (drawn with a scanline overlay).
+    pub fn is_phosphor(&self) -> bool {
+        *self != ColorScheme::Standard
+    }
+
+    /// Maps a draw color into this scheme.
+    ///
+    /// Phosphor schemes keep only the color's brightness and render it as a shade
+    /// of the phosphor color; dim colors are lifted slightly so they stay visible.
+    pub fn apply(&self, color: Color) -> Color {
+        let phosphor = match self {
+            ColorScheme::Standard => return color,
+            ColorScheme::GreenPhosphor => GREEN_PHOSPHOR,
+            ColorScheme::AmberPhosphor => AMBER_PHOSPHOR,
+        };
+        let luminance = 0.299 * color.r + 0.587 * color.g + 0.114 * color.b;
+        let level = 0.25 + 0.75 * luminance;
+        Color::new(phosphor.r * level, phosphor.g * level, phosphor.b * level, color.a)
+    }
+}
+
+/// Something on the playfield whose color depends on the active `Palette`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    Player,
+    Asteroid,
+    Ufo,
+    PlayerBullet,
+    UfoBullet,
+    /// Explosion debris.
+    Particle,
+}
+
+/// Color set for the playfield, for players who find the default colors hard to tell apart.
+///
+/// Applied before the `ColorScheme`, so a phosphor scheme still turns everything monochrome.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Palette {
+    Default,
+    /// Saturated colors with the strongest contrast against the black background.
+    HighContrast,
+    /// Colors that stay distinct without red-green vision (Okabe-Ito hues).
+    Deuteranopia,
+}
+
+impl Palette {
+    pub fn from_name(value: &str) -> Option<Self> {
+        match value {
+            "default" => Some(Palette::Default),
+            "high_contrast" => Some(Palette::HighContrast),
+            "deuteranopia" => Some(Palette::Deuteranopia),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Palette::Default => "default",
+            Palette::HighContrast => "high_contrast",
+            Palette::Deuteranopia => "deuteranopia",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Palette::Default => "Default",
+            Palette::HighContrast => "High Contrast",
+            Palette::Deuteranopia => "Deuteranopia",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Palette::Default => Palette::HighContrast,
+            Palette::HighContrast => Palette::Deuteranopia,
+            Palette::Deuteranopia => Palette::Default,
+        }
+    }
+
+    pub fn color(&self, role: Role) -> Color {
+        match (self, role) {
+            (Palette::Default, Role::Player) => WHITE,
+            (Palette::Default, Role::Asteroid) => GRAY,
+            (Palette::Default, Role::Ufo) => RED,
+            (Palette::Default, Role::PlayerBullet) => YELLOW,
+            (Palette::Default, Role::UfoBullet) => Color::new(1.0, 0.45, 0.45, 1.0),
+            (Palette::Default, Role::Particle) => LIGHTGRAY,
+            (Palette::HighContrast, Role::Player) => WHITE,
+            (Palette::HighContrast, Role::Asteroid) => Color::new(1.0, 1.0, 0.0, 1.0),
+            (Palette::HighContrast, Role::Ufo) => Color::new(1.0, 0.2, 1.0, 1.0),
+            (Palette::HighContrast, Role::PlayerBullet) => Color::new(0.0, 1.0, 1.0, 1.0),
+            (Palette::HighContrast, Role::UfoBullet) => Color::new(1.0, 0.35, 0.2, 1.0),
+            (Palette::HighContrast, Role::Particle) => Color::new(1.0, 0.65, 0.0, 1.0),
+            (Palette::Deuteranopia, Role::Player) => WHITE,
+            (Palette::Deuteranopia, Role::Asteroid) => Color::new(0.6, 0.6, 0.6, 1.0),
+            (Palette::Deuteranopia, Role::Ufo) => Color::new(0.9, 0.6, 0.0, 1.0),
+            (Palette::Deuteranopia, Role::PlayerBullet) => Color::new(0.34, 0.71, 0.91, 1.0),
+            (Palette::Deuteranopia, Role::UfoBullet) => Color::new(0.94, 0.89, 0.26, 1.0),
+            (Palette::Deuteranopia, Role::Particle) => Color::new(0.8, 0.47, 0.65, 1.0),
+        }
+    }
+
+    /// Draw color for a ship: the default palette keeps the color picked in design mode.
+    pub fn ship_color(&self, chosen: Color) -> Color {
+        match self {
+            Palette::Default => chosen,
+            _ => self.color(Role::Player),
+        }
+    }
+}
+
+/// Full-screen overlay color that applies `brightness` to everything drawn under it.
+///
+/// Below 1.0 the overlay is black (darken), above 1.0 it is white (brighten);
+/// at exactly 1.0 it is fully transparent.
+pub fn brightness_overlay(brightness: f32) -> Color {
+    let b = brightness.clamp(MIN_BRIGHTNESS, MAX_BRIGHTNESS);
+    if b < 1.0 {
+        Color::new(0., 0., 0., 1.0 - b)
+    } else {
+        let t = (b - 1.0) / (MAX_BRIGHTNESS - 1.0);
+        Color::new(1., 1., 1., t * MAX_BRIGHTEN_ALPHA)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROLES: [Role; 6] = [Role::Player, Role::Asteroid, Role::Ufo, Role::PlayerBullet, Role::UfoBullet, Role::Particle];
+
+    #[test]
+    fn test_standard_is_identity() {
+        assert_eq!(ColorScheme::Standard.apply(ORANGE), ORANGE);
+    }
+
+    #[test]
+    fn test_phosphor_is_monochrome() {
+        let scheme = ColorScheme::GreenPhosphor;
+        for c in [RED, YELLOW, GRAY, SKYBLUE, WHITE] {
+            let out = scheme.apply(c);
+            // Every output is a shade of the phosphor color
+            let ratio = out.g / GREEN_PHOSPHOR.g;
+            assert!((out.r - GREEN_PHOSPHOR.r * ratio).abs() < 1e-5);
+            assert!((out.b - GREEN_PHOSPHOR.b * ratio).abs() < 1e-5);
+            assert_eq!(out.a, c.a);
+        }
+        // Brighter inputs stay brighter
+        assert!(scheme.apply(WHITE).g > scheme.apply(DARKGRAY).g);
+    }
+
+    #[test]
+    fn test_scheme_names_round_trip() {
+        for scheme in [ColorScheme::Standard, ColorScheme::GreenPhosphor, ColorScheme::AmberPhosphor] {
+            assert_eq!(ColorScheme::from_name(scheme.name()), Some(scheme));
+        }
+    }
+
+    #[test]
+    fn test_palettes_give_every_role_a_distinct_visible_color() {
+        for palette in [Palette::Default, Palette::HighContrast, Palette::Deuteranopia] {
+            let colors: Vec<Color> = ROLES.iter().map(|role| palette.color(*role)).collect();
+            for (i, c) in colors.iter().enumerate() {
+                assert_eq!(c.a, 1.0, "{:?} {:?} is transparent", palette, ROLES[i]);
+                for (j, other) in colors.iter().enumerate().skip(i + 1) {
+                    assert_ne!(c, other, "{:?} reuses a color for {:?} and {:?}", palette, ROLES[i], ROLES[j]);
+                }
+            }
+            assert_eq!(Palette::from_name(palette.name()), Some(palette));
+        }
+    }
+
+    #[test]
+    fn test_brightness_overlay_range() {
+        assert_eq!(brightness_overlay(1.0).a, 0.0);
+        // Darkening uses black, brightening white, both growing away from 1.0
+        let dim = brightness_overlay(MIN_BRIGHTNESS);
+        assert_eq!((dim.r, dim.a), (0.0, 0.5));
+        let bright = brightness_overlay(MAX_BRIGHTNESS);
+        assert_eq!((bright.r, bright.a), (1.0, MAX_BRIGHTEN_ALPHA));
+        assert!(brightness_overlay(0.8).a < dim.a);
+        assert!(brightness_overlay(1.2).a < bright.a);
+        // Out-of-range values are clamped
+        assert_eq!(brightness_overlay(3.0), bright);
+        assert_eq!(brightness_overlay(0.0), dim);
+    }
+}
@@ -1,8 +1,31 @@
 use macroquad::prelude::*;
-use ::rand::Rng;
-use ::rand::thread_rng;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread;
+use crate::palette::ColorScheme;
+use crate::physics::{apply_gravity, GravityWell};
+use crate::rng::GameRng;
+use crate::world::TARGET_FPS;
+
+/// Above this many live particles only every other one is drawn.
+pub const PARTICLE_REDUCED_THRESHOLD: usize = 400;
+/// Above this many live particles only every fourth one is drawn.
+pub const PARTICLE_MINIMAL_THRESHOLD: usize = 1000;
+/// Most particles kept alive by default; the oldest are dropped past this.
+pub const DEFAULT_MAX_PARTICLES: usize = 2000;
+/// Fewest and most particles one explosion may throw out.
+pub const EXPLOSION_COUNT_RANGE: (usize, usize) = (8, 120);
+
+/// Draw every `n`th particle for a given live count, thinning out heavy bursts
+/// to protect the frame rate. Full quality returns as soon as the count drops.
+pub fn draw_stride(count: usize) -> usize {
+    if count > PARTICLE_MINIMAL_THRESHOLD {
+        4
+    } else if count > PARTICLE_REDUCED_THRESHOLD {
+        2
+    } else {
+        1
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct ParticleInit {
@@ -24,14 +47,47 @@ pub struct Particle {
 }
 
 pub struct ParticleSystem {
+    /// Oldest first.
     particles: Vec<Particle>,
+    max_particles: usize,
+    /// The level's gravity well, pulling debris the way it pulls ships and rocks.
+    gravity: Option<GravityWell>,
+    /// Playfield size the well's center is placed in.
+    bounds: Vec2,
+    /// Fraction of velocity lost per second.
+    drag: f32,
 }
 
 impl ParticleSystem {
     pub fn new() -> Self {
-        Self { particles: Vec::new() }
+        Self::with_capacity_limit(DEFAULT_MAX_PARTICLES)
+    }
+
+    /// A system that never holds more than `max_particles` at once.
+    pub fn with_capacity_limit(max_particles: usize) -> Self {
+        Self { particles: Vec::new(), max_particles, gravity: None, bounds: Vec2::ZERO, drag: 0.0 }
+    }
+
+    /// Follows the world's gravity well (`None` in open space) in a playfield of `bounds`.
+    pub fn set_gravity(&mut self, gravity: Option<GravityWell>, bounds: Vec2) {
+        self.gravity = gravity;
+        self.bounds = bounds;
+    }
+
+    pub fn set_drag(&mut self, drag: f32) {
+        self.drag = drag.max(0.0);
     }
 
+    /// Live particle count.
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    /// Adds a batch, then drops the oldest particles if that went over the cap.
     pub fn spawn_batch(&mut self, batch: Vec<ParticleInit>) {
         self.particles.reserve(batch.len());
         for init in batch {
@@ -44,21 +100,36 @@ impl ParticleSystem {
                 size: init.size,
             });
         }
+        let excess = self.particles.len().saturating_sub(self.max_particles);
+        if excess > 0 {
+            self.particles.drain(..excess);
+        }
     }
 
     pub fn update(&mut self, dt: f32) {
+        // Drag never reverses a particle, however long the frame
+        let damping = (1.0 - self.drag * dt).max(0.0);
         for p in self.particles.iter_mut() {
+            if let Some(well) = &self.gravity {
+                // The well's pull is in pixels per step; particles move in pixels per second
+                p.vel += apply_gravity(p.pos, Vec2::ZERO, well, self.bounds, dt) * TARGET_FPS;
+            }
+            p.vel *= damping;
             p.pos += p.vel * dt;
             p.life -= dt;
         }
         self.particles.retain(|p| p.life > 0.0);
     }
 
-    pub fn draw(&self) {
-        for p in &self.particles {
+    pub fn draw(&self, scheme: ColorScheme) {
+        if self.is_empty() {
+            return;
+        }
+        let stride = draw_stride(self.particles.len());
+        for p in self.particles.iter().step_by(stride) {
             let mut c = p.color;
             c.a *= (p.life / p.max_life).clamp(0.0, 1.0);
-            draw_circle(p.pos.x, p.pos.y, p.size, c);
+            draw_circle(p.pos.x, p.pos.y, p.size, scheme.apply(c));
         }
     }
 }
@@ -68,41 +139,51 @@ pub struct SpawnRequest {
     pub pos: Vec2,
     pub color: Color,
     pub count: usize,
+    /// Top speed of the random burst around `drift`.
     pub speed: f32,
     pub life: f32,
     pub size: f32,
+    /// Velocity added to every particle of the burst: debris carries on with whatever
+    /// exploded, exhaust is blown out behind a ship. Zero for a burst that stays put.
+    pub drift: Vec2,
+}
+
+impl Default for SpawnRequest {
+    /// An empty white burst at the origin with no drift; callers fill in what they need.
+    fn default() -> Self {
+        SpawnRequest { pos: Vec2::ZERO, color: WHITE, count: 0, speed: 0.0, life: 0.0, size: 1.0, drift: Vec2::ZERO }
+    }
+}
+
+/// Work sent to the particle thread.
+enum BridgeMessage {
+    Spawn(SpawnRequest),
+    /// Restart the burst generator from a seed.
+    Reseed(u64),
 }
 
 pub struct ParticleSpawnBridge {
-    tx: Sender<SpawnRequest>,
+    tx: Sender<BridgeMessage>,
     rx: Receiver<Vec<ParticleInit>>,
 }
 
 impl ParticleSpawnBridge {
-    pub fn new() -> Self {
-        let (tx_req, rx_req) = mpsc::channel::<SpawnRequest>();
+    /// Starts the particle thread, drawing bursts from a generator seeded with `seed`.
+    pub fn new(seed: u64) -> Self {
+        let (tx_req, rx_req) = mpsc::channel::<BridgeMessage>();
         let (tx_out, rx_out) = mpsc::channel::<Vec<ParticleInit>>();
 
         thread::spawn(move || {
-            let mut rng = thread_rng();
-            while let Ok(req) = rx_req.recv() {
-                let count = clamp_count(req.count, 1, 250);
-                let mut batch = Vec::with_capacity(count);
-                for _ in 0..count {
-                    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
-                    let speed = rng.gen_range(req.speed * 0.5..req.speed);
-                    let vel = vec2(angle.cos(), angle.sin()) * speed;
-                    let life = rng.gen_range(req.life * 0.6..req.life);
-                    let size = rng.gen_range(req.size * 0.6..req.size * 1.2);
-                    batch.push(ParticleInit {
-                        pos: req.pos,
-                        vel,
-                        life,
-                        color: req.color,
-                        size,
-                    });
-                }
-                let _ = tx_out.send(batch);
+            let mut rng = GameRng::new(seed);
+            while let Ok(message) = rx_req.recv() {
+                let req = match message {
+                    BridgeMessage::Spawn(req) => req,
+                    BridgeMessage::Reseed(seed) => {
+                        rng = GameRng::new(seed);
+                        continue;
+                    }
+                };
+                let _ = tx_out.send(generate_burst(&req, &mut rng));
             }
         });
 
@@ -110,7 +191,12 @@ impl ParticleSpawnBridge {
     }
 
     pub fn request(&self, req: SpawnRequest) {
-        let _ = self.tx.send(req);
+        let _ = self.tx.send(BridgeMessage::Spawn(req));
+    }
+
+    /// Restarts burst generation from `seed`; requests already queued still use the old sequence.
+    pub fn reseed(&self, seed: u64) {
+        let _ = self.tx.send(BridgeMessage::Reseed(seed));
     }
 
     pub fn try_receive(&self) -> Option<Vec<ParticleInit>> {
@@ -118,6 +204,72 @@ impl ParticleSpawnBridge {
     }
 }
 
+/// Particles for one request: each flies off at a random angle and speed (half to
+/// full `speed`) on top of the request's `drift`.
+pub fn generate_burst(req: &SpawnRequest, rng: &mut GameRng) -> Vec<ParticleInit> {
+    let count = clamp_count(req.count, 1, 250);
+    let mut batch = Vec::with_capacity(count);
+    for _ in 0..count {
+        let angle = rng.gen_range_f32(0.0, std::f32::consts::TAU);
+        let speed = rng.gen_range_f32(req.speed * 0.5, req.speed);
+        let vel = req.drift + vec2(angle.cos(), angle.sin()) * speed;
+        let life = rng.gen_range_f32(req.life * 0.6, req.life);
+        let size = rng.gen_range_f32(req.size * 0.6, req.size * 1.2);
+        batch.push(ParticleInit {
+            pos: req.pos,
+            vel,
+            life,
+            color: req.color,
+            size,
+        });
+    }
+    batch
+}
+
+/// Fixed-rate emitter: turns variable frame times into a steady number of emissions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmissionTimer {
+    interval: f32,
+    accumulator: f32,
+}
+
+impl EmissionTimer {
+    pub fn new(interval: f32) -> Self {
+        // Primed, so switching on emits straight away
+        Self { interval, accumulator: interval }
+    }
+
+    /// Advances by `dt` and returns how many emissions are due this frame.
+    ///
+    /// While inactive nothing is emitted and the timer re-primes, so emission
+    /// stops the moment it's switched off and restarts without delay.
+    pub fn tick(&mut self, dt: f32, active: bool) -> u32 {
+        if !active {
+            self.accumulator = self.interval;
+            return 0;
+        }
+        self.accumulator += dt;
+        let due = (self.accumulator / self.interval).floor();
+        self.accumulator -= due * self.interval;
+        due as u32
+    }
+}
+
+/// Blast for something of `radius` being destroyed: bigger objects throw more,
+/// faster, larger and longer-lived debris. `pos` is left at zero for the caller to fill in.
+pub fn explosion_preset(radius: f32, base_color: Color) -> SpawnRequest {
+    let radius = radius.max(0.0);
+    let (min, max) = EXPLOSION_COUNT_RANGE;
+    SpawnRequest {
+        color: base_color,
+        count: clamp_count(8 + (radius * 1.1) as usize, min, max),
+        speed: (80.0 + radius * 3.0).min(360.0),
+        life: (0.6 + radius * 0.01).min(1.2),
+        size: (1.5 + radius * 0.04).min(4.0),
+        ..Default::default()
+    }
+}
+
 pub fn clamp_count(value: usize, min: usize, max: usize) -> usize {
     value.max(min).min(max)
 }
@@ -126,6 +278,11 @@ pub fn clamp_count(value: usize, min: usize, max: usize) -> usize {
 mod tests {
     use super::*;
 
+    /// Number of circles `ParticleSystem::draw` issues for `count` live particles.
+    fn draw_count(count: usize) -> usize {
+        count.div_ceil(draw_stride(count))
+    }
+
     #[test]
     fn test_clamp_count() {
         assert_eq!(clamp_count(0, 1, 10), 1);
@@ -133,6 +290,33 @@ mod tests {
         assert_eq!(clamp_count(50, 1, 10), 10);
     }
 
+    #[test]
+    fn test_explosions_grow_with_radius_up_to_the_ceiling() {
+        let fragment = explosion_preset(10.0, GRAY);
+        let rock = explosion_preset(40.0, GRAY);
+        assert!(rock.count > fragment.count);
+        assert!(rock.speed > fragment.speed);
+        assert!(rock.size > fragment.size);
+        assert_eq!(rock.color, GRAY);
+
+        let huge = explosion_preset(10_000.0, GRAY);
+        assert_eq!(huge.count, EXPLOSION_COUNT_RANGE.1);
+        assert_eq!(explosion_preset(-5.0, GRAY).count, EXPLOSION_COUNT_RANGE.0);
+    }
+
+    #[test]
+    fn test_drift_shifts_the_whole_burst() {
+        let mean = |batch: &[ParticleInit]| batch.iter().map(|p| p.vel).sum::<Vec2>() / batch.len() as f32;
+        let still = explosion_preset(30.0, GRAY);
+        let moving = SpawnRequest { drift: vec2(120.0, -45.0), ..still.clone() };
+        let a = generate_burst(&still, &mut GameRng::new(7));
+        let b = generate_burst(&moving, &mut GameRng::new(7));
+        assert_eq!(a.len(), b.len());
+        assert!((mean(&b) - mean(&a)).distance(moving.drift) < 1e-3);
+        // Without drift the debris stays centred on the blast
+        assert!(mean(&a).length() < still.speed * 0.5);
+    }
+
     #[test]
     fn test_particle_update_culls_dead() {
         let mut system = ParticleSystem::new();
@@ -146,4 +330,111 @@ mod tests {
         system.update(0.1);
         assert_eq!(system.particles.len(), 0);
     }
+
+    fn burst(count: usize, size: f32) -> Vec<ParticleInit> {
+        (0..count)
+            .map(|_| ParticleInit { pos: vec2(0.0, 0.0), vel: vec2(0.0, 0.0), life: 1.0, color: WHITE, size })
+            .collect()
+    }
+
+    #[test]
+    fn test_spawning_past_the_cap_stays_at_the_limit() {
+        let mut system = ParticleSystem::with_capacity_limit(50);
+        system.spawn_batch(burst(30, 1.0));
+        assert_eq!(system.len(), 30);
+        system.spawn_batch(burst(30, 1.0));
+        assert_eq!(system.len(), 50);
+        // A single batch bigger than the cap is trimmed too
+        system.spawn_batch(burst(80, 1.0));
+        assert_eq!(system.len(), 50);
+    }
+
+    #[test]
+    fn test_trimming_keeps_the_newest_particles() {
+        let mut system = ParticleSystem::with_capacity_limit(10);
+        system.spawn_batch(burst(8, 1.0));
+        system.spawn_batch(burst(5, 2.0));
+        assert_eq!(system.len(), 10);
+        // The three oldest went; the whole newer batch survived, at the end
+        assert_eq!(system.particles.iter().filter(|p| p.size == 1.0).count(), 5);
+        assert!(system.particles[5..].iter().all(|p| p.size == 2.0));
+    }
+
+    fn moving_particle(vel: Vec2) -> ParticleInit {
+        ParticleInit { pos: vec2(0.0, 0.0), vel, life: 10.0, color: WHITE, size: 1.0 }
+    }
+
+    #[test]
+    fn test_drag_slows_particles_down() {
+        let mut system = ParticleSystem::new();
+        system.set_drag(2.0);
+        system.spawn_batch(vec![moving_particle(vec2(100.0, 0.0))]);
+        let mut last_speed = 100.0;
+        for _ in 0..5 {
+            system.update(0.1);
+            let speed = system.particles[0].vel.length();
+            assert!(speed < last_speed);
+            last_speed = speed;
+        }
+        // Without drag (the default) speed is unchanged
+        let mut plain = ParticleSystem::new();
+        plain.spawn_batch(vec![moving_particle(vec2(100.0, 0.0))]);
+        plain.update(0.1);
+        assert_eq!(plain.particles[0].vel, vec2(100.0, 0.0));
+    }
+
+    #[test]
+    fn test_gravity_curves_the_path() {
+        // A star in the middle of an 800x600 field, debris passing 100 px to its right
+        let mut system = ParticleSystem::new();
+        system.set_gravity(Some(GravityWell::centered(1.0, 300.)), vec2(800., 600.));
+        system.spawn_batch(vec![ParticleInit { pos: vec2(500., 300.), ..moving_particle(vec2(0.0, -100.0)) }]);
+        let mut path = Vec::new();
+        for _ in 0..3 {
+            system.update(0.1);
+            path.push(system.particles[0].pos);
+        }
+        // Still rising, but drawn further towards the star each step
+        assert!(path.iter().all(|p| p.x < 500.0));
+        assert!(path[1].x - path[2].x > path[0].x - path[1].x);
+        assert!(path[2].y < path[1].y && path[1].y < path[0].y);
+
+        // In open space the path stays straight
+        system.set_gravity(None, vec2(800., 600.));
+        let (pos, vel) = (system.particles[0].pos, system.particles[0].vel);
+        system.update(0.1);
+        assert_eq!(system.particles[0].pos, pos + vel * 0.1);
+    }
+
+    #[test]
+    fn test_emission_rate_is_frame_rate_independent() {
+        // One second of emitting at 60 fps and at 20 fps gives the same total
+        let mut fast = EmissionTimer::new(0.05);
+        let mut slow = EmissionTimer::new(0.05);
+        let fast_total: u32 = (0..60).map(|_| fast.tick(1.0 / 60.0, true)).sum();
+        let slow_total: u32 = (0..20).map(|_| slow.tick(1.0 / 20.0, true)).sum();
+        assert_eq!(fast_total, slow_total);
+        assert!((20..=21).contains(&fast_total));
+    }
+
+    #[test]
+    fn test_emission_stops_and_restarts_immediately() {
+        let mut timer = EmissionTimer::new(0.05);
+        assert_eq!(timer.tick(0.01, true), 1);
+        assert_eq!(timer.tick(0.01, true), 0);
+        assert_eq!(timer.tick(0.5, false), 0);
+        // Re-engaging puffs on the first frame again
+        assert_eq!(timer.tick(0.01, true), 1);
+    }
+
+    #[test]
+    fn test_draw_count_thins_out_large_bursts() {
+        assert_eq!(draw_count(0), 0);
+        assert_eq!(draw_count(PARTICLE_REDUCED_THRESHOLD), PARTICLE_REDUCED_THRESHOLD);
+        assert_eq!(draw_count(PARTICLE_REDUCED_THRESHOLD + 2), (PARTICLE_REDUCED_THRESHOLD + 2) / 2);
+        assert_eq!(draw_count(PARTICLE_MINIMAL_THRESHOLD + 4), (PARTICLE_MINIMAL_THRESHOLD + 4) / 4);
+        // Matches what draw actually iterates over
+        let stride = draw_stride(601);
+        assert_eq!((0..601).step_by(stride).count(), draw_count(601));
+    }
 }
@@ -1,20 +1,80 @@
 use macroquad::prelude::*;
+use std::collections::HashMap;
+use crate::components::{Asteroid, Player, Ufo};
+use crate::rng::GameRng;
+
+/// Playfield size used when the window reports no area at startup (e.g. launched minimized).
+pub const FALLBACK_WORLD_SIZE: (f32, f32) = (800., 600.);
+
+/// Playfield size in logical pixels.
+///
+/// Set once at startup and independent of the window: the renderer scales the
+/// playfield to whatever size the window currently has.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct World {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl World {
+    pub fn new(width: f32, height: f32) -> Self {
+        World { width, height }
+    }
+
+    /// A playfield the size of the window, or `FALLBACK_WORLD_SIZE` if the window has no area.
+    pub fn from_screen(width: f32, height: f32) -> Self {
+        if width > 0. && height > 0. {
+            World::new(width, height)
+        } else {
+            World::new(FALLBACK_WORLD_SIZE.0, FALLBACK_WORLD_SIZE.1)
+        }
+    }
+
+    pub fn size(&self) -> Vec2 {
+        vec2(self.width, self.height)
+    }
+
+    pub fn center(&self) -> Vec2 {
+        self.size() / 2.
+    }
+
+    /// A random point anywhere in the playfield.
+    pub fn random_pos(&self, rng: &mut GameRng) -> Vec2 {
+        vec2(rng.gen_range_f32(0., self.width), rng.gen_range_f32(0., self.height))
+    }
+}
+
+/// Wraps a position around the edges of `world` (see `wrap_pos`).
+pub fn wrap_world(pos: Vec2, world: &World) -> Vec2 {
+    wrap_pos(pos, world.width, world.height)
+}
 
 /// Wraps a position vector around a boundary defined by width and height.
 /// 
 /// If an object goes off the left side, it appears on the right, and vice versa.
 pub fn wrap_pos(pos: Vec2, width: f32, height: f32) -> Vec2 {
+    wrap_pos_checked(pos, width, height).0
+}
+
+/// Like `wrap_pos`, but also reports whether the position was actually teleported.
+///
+/// A boundary with no area (a minimized window) leaves the position alone.
+pub fn wrap_pos_checked(pos: Vec2, width: f32, height: f32) -> (Vec2, bool) {
+    if width <= 0. || height <= 0. {
+        return (pos, false);
+    }
     let mut new_pos = pos;
     if new_pos.x > width { new_pos.x = 0.; }
     if new_pos.x < 0. { new_pos.x = width; }
     if new_pos.y > height { new_pos.y = 0.; }
     if new_pos.y < 0. { new_pos.y = height; }
-    new_pos
+    (new_pos, new_pos != pos)
 }
 
-/// Convenience function to wrap position around the global screen dimensions.
-pub fn wrap_screen(pos: Vec2) -> Vec2 {
-    wrap_pos(pos, screen_width(), screen_height())
+/// `v` scaled to unit length, or `fallback` when `v` is too short to have a direction
+/// (e.g. aiming at a target on top of the shooter).
+pub fn safe_normalize(v: Vec2, fallback: Vec2) -> Vec2 {
+    v.try_normalize().unwrap_or(fallback)
 }
 
 /// Simple circle-based collision detection.
@@ -24,6 +84,44 @@ pub fn check_collision(pos1: Vec2, r1: f32, pos2: Vec2, r2: f32) -> bool {
     pos1.distance(pos2) < (r1 + r2)
 }
 
+/// Candidate spawn points per axis checked by `find_safe_spawn` (besides the centre).
+const SPAWN_GRID: (usize, usize) = (8, 6);
+
+/// Open space around `pos`: distance to the nearest active asteroid or UFO edge,
+/// measured across the screen wrap. Infinite on an empty field.
+pub fn hazard_clearance(pos: Vec2, asteroids: &[Asteroid], ufos: &[Ufo], world: &World) -> f32 {
+    let wrapped_distance = |other: Vec2| {
+        let d = (pos - other).abs();
+        vec2(d.x.min(world.width - d.x), d.y.min(world.height - d.y)).length()
+    };
+    let rocks = asteroids.iter().filter(|a| a.active).map(|a| wrapped_distance(a.pos) - a.radius);
+    let saucers = ufos.iter().filter(|u| u.active).map(|u| wrapped_distance(u.pos) - u.radius);
+    rocks.chain(saucers).fold(f32::INFINITY, f32::min)
+}
+
+/// Picks a respawn point away from asteroids and UFOs.
+///
+/// The centre is tried first, then a grid of points across the playfield; the first
+/// with at least `min_clearance` of open space wins. On a field too crowded for
+/// that, the point with the most room is used.
+pub fn find_safe_spawn(asteroids: &[Asteroid], ufos: &[Ufo], world: &World, min_clearance: f32) -> Vec2 {
+    let (cols, rows) = SPAWN_GRID;
+    let grid = (0..rows).flat_map(|row| {
+        (0..cols).map(move |col| vec2((col as f32 + 0.5) / cols as f32, (row as f32 + 0.5) / rows as f32))
+    });
+    let mut best = (world.center(), f32::NEG_INFINITY);
+    for pos in std::iter::once(world.center()).chain(grid.map(|f| f * world.size())) {
+        let clearance = hazard_clearance(pos, asteroids, ufos, world);
+        if clearance >= min_clearance {
+            return pos;
+        }
+        if clearance > best.1 {
+            best = (pos, clearance);
+        }
+    }
+    best.0
+}
+
 /// Determines if the player has earned an extra life based on score milestones.
 /// 
 /// Returns `true` if score has crossed a 3000-point threshold since the last check.
@@ -37,10 +135,551 @@ pub fn check_extra_life(score: u32, last_score: &mut u32) -> bool {
     }
 }
 
+/// Whether the line segment from `a` to `b` passes within `radius` of `center`.
+pub fn segment_circle_intersect(a: Vec2, b: Vec2, center: Vec2, radius: f32) -> bool {
+    let ab = b - a;
+    let len_sq = ab.length_squared();
+    let t = if len_sq > 0. { ((center - a).dot(ab) / len_sq).clamp(0., 1.) } else { 0. };
+    let closest = a + ab * t;
+    closest.distance_squared(center) <= radius * radius
+}
+
+/// Corners of a regular polygon as `draw_poly_lines` draws it. `rotation` is in radians here,
+/// while `draw_poly_lines` itself takes degrees.
+pub fn poly_vertices(center: Vec2, sides: u8, radius: f32, rotation: f32) -> Vec<Vec2> {
+    (0..sides)
+        .map(|i| center + Vec2::from_angle(rotation + std::f32::consts::TAU * i as f32 / sides as f32) * radius)
+        .collect()
+}
+
+/// Whether `point` lies inside the polygon with corners `vertices` (even-odd rule).
+pub fn point_in_poly(point: Vec2, vertices: &[Vec2]) -> bool {
+    let mut inside = false;
+    let mut j = vertices.len().wrapping_sub(1);
+    for (i, a) in vertices.iter().enumerate() {
+        let b = vertices[j];
+        if (a.y > point.y) != (b.y > point.y) && point.x < (b.x - a.x) * (point.y - a.y) / (b.y - a.y) + a.x {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// Whether a circle overlaps the polygon: its centre is inside, or an edge passes within `radius`.
+pub fn circle_vs_poly(center: Vec2, radius: f32, vertices: &[Vec2]) -> bool {
+    if point_in_poly(center, vertices) {
+        return true;
+    }
+    let edges = vertices.iter().zip(vertices.iter().cycle().skip(1));
+    edges.into_iter().any(|(a, b)| segment_circle_intersect(*a, *b, center, radius))
+}
+
+/// Whether a ship's hull touches the circle at `pos`.
+///
+/// The bounding circle rules out distant bodies cheaply; anything inside it is
+/// tested against the ship's actual outline, so the empty corners of the bounding
+/// circle around a thin triangle don't count as hits.
+pub fn ship_hits_circle(player: &Player, pos: Vec2, radius: f32) -> bool {
+    if !check_collision(player.pos, player.radius, pos, radius) {
+        return false;
+    }
+    circle_vs_poly(pos, radius, &poly_vertices(player.pos, player.sides, player.radius, player.rotation))
+}
+
+/// Heading (radians) that points from `from` at `to`, or `None` when they're
+/// within `dead_zone` pixels of each other and no direction is meaningful.
+pub fn angle_toward(from: Vec2, to: Vec2, dead_zone: f32) -> Option<f32> {
+    let delta = to - from;
+    if delta.length_squared() <= dead_zone * dead_zone {
+        return None;
+    }
+    Some(delta.y.atan2(delta.x))
+}
+
+/// Signed turn (radians, in [-PI, PI)) that takes `current` to `target` the short way round.
+pub fn shortest_rotation(current: f32, target: f32) -> f32 {
+    use std::f32::consts::{PI, TAU};
+    (target - current + PI).rem_euclid(TAU) - PI
+}
+
+/// Rotates `current` towards `target` (radians) along the shortest arc, by at most `max_step`.
+pub fn turn_towards(current: f32, target: f32, max_step: f32) -> f32 {
+    current + shortest_rotation(current, target).clamp(-max_step, max_step)
+}
+
+/// A circular rigid body used by the collision-response helpers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Body {
+    pub pos: Vec2,
+    pub vel: Vec2,
+    pub radius: f32,
+    pub mass: f32,
+}
+
+/// Resolves an elastic collision between two overlapping circular bodies.
+///
+/// Velocities are exchanged along the collision normal (weighted by mass) and the
+/// bodies are pushed apart so they no longer overlap. Returns `false` (and leaves
+/// both bodies untouched) when they are not overlapping or are already separating.
+pub fn resolve_elastic(a: &mut Body, b: &mut Body) -> bool {
+    let delta = b.pos - a.pos;
+    let dist = delta.length();
+    if dist >= a.radius + b.radius {
+        return false;
+    }
+    // Coincident centers have no meaningful normal; pick an arbitrary one.
+    let normal = if dist > f32::EPSILON { delta / dist } else { vec2(1., 0.) };
+
+    // Push apart proportionally to the other body's mass
+    let overlap = a.radius + b.radius - dist;
+    let total_mass = a.mass + b.mass;
+    a.pos -= normal * overlap * (b.mass / total_mass);
+    b.pos += normal * overlap * (a.mass / total_mass);
+
+    let closing = (a.vel - b.vel).dot(normal);
+    if closing <= 0.0 {
+        return true;
+    }
+    let impulse = 2.0 * closing / total_mass;
+    a.vel -= normal * impulse * b.mass;
+    b.vel += normal * impulse * a.mass;
+    true
+}
+
+/// Bounces two overlapping asteroids off each other, with mass proportional to radius².
+///
+/// See `resolve_elastic`; returns `false` when they aren't touching.
+pub fn resolve_asteroid_collision(a: &mut Asteroid, b: &mut Asteroid) -> bool {
+    let mut body_a = a.body();
+    let mut body_b = b.body();
+    if !resolve_elastic(&mut body_a, &mut body_b) {
+        return false;
+    }
+    a.pos = body_a.pos;
+    a.vel = body_a.vel;
+    b.pos = body_b.pos;
+    b.vel = body_b.vel;
+    true
+}
+
+/// What happens when two asteroids run into each other.
+#[derive(Debug, PartialEq)]
+pub enum AsteroidImpact {
+    /// Slow contact: the asteroids bounce off each other.
+    Bounce,
+    /// High-speed contact: the first asteroid (the smaller one) shatters.
+    ShatterFirst,
+    /// High-speed contact: the second asteroid (the smaller one) shatters.
+    ShatterSecond,
+}
+
+/// Classifies an asteroid-asteroid contact by relative speed.
+///
+/// Above `threshold` the smaller asteroid shatters (ties shatter the second one).
+pub fn classify_asteroid_impact(a: &Body, b: &Body, threshold: f32) -> AsteroidImpact {
+    if (a.vel - b.vel).length() <= threshold {
+        AsteroidImpact::Bounce
+    } else if a.radius < b.radius {
+        AsteroidImpact::ShatterFirst
+    } else {
+        AsteroidImpact::ShatterSecond
+    }
+}
+
+/// How a zone alters the motion of objects inside it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ZoneEffect {
+    /// Extra friction applied per frame (0.0 = none, 1.0 = full stop).
+    Drag(f32),
+    /// Constant push added to velocity per frame.
+    Push(Vec2),
+}
+
+/// A circular region of "space weather" that modifies physics locally.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Zone {
+    /// Center as a fraction of the playfield (0.0..=1.0 on each axis).
+    pub center: Vec2,
+    /// Radius in pixels.
+    pub radius: f32,
+    pub effect: ZoneEffect,
+}
+
+impl Zone {
+    /// Returns the zone's center in playfield coordinates.
+    pub fn world_center(&self, bounds: Vec2) -> Vec2 {
+        self.center * bounds
+    }
+}
+
+/// Returns true if `pos` lies inside the zone.
+pub fn is_inside_zone(pos: Vec2, zone: &Zone, bounds: Vec2) -> bool {
+    pos.distance(zone.world_center(bounds)) < zone.radius
+}
+
+/// Applies the effect of every zone containing `pos` to `vel` and returns the result.
+pub fn apply_zones(pos: Vec2, vel: Vec2, zones: &[Zone], bounds: Vec2) -> Vec2 {
+    let mut new_vel = vel;
+    for zone in zones.iter().filter(|z| is_inside_zone(pos, z, bounds)) {
+        match zone.effect {
+            ZoneEffect::Drag(drag) => new_vel *= 1.0 - drag.clamp(0.0, 1.0),
+            ZoneEffect::Push(force) => new_vel += force,
+        }
+    }
+    new_vel
+}
+
+/// Fraction of the tank an empty ship must regain before thrust works again.
+pub const FUEL_RESUME_FRACTION: f32 = 0.2;
+
+/// Advances a fuel tank by `dt` seconds and returns `(fuel, locked)`.
+///
+/// `burn` is the seconds of thrust used this frame (0.0 while coasting): burning
+/// drains `drain` per second of thrust, coasting regains `regen` per second. Running
+/// dry locks thrust out until the tank is back above `FUEL_RESUME_FRACTION`.
+pub fn update_fuel(fuel: f32, max_fuel: f32, locked: bool, burn: f32, drain: f32, regen: f32, dt: f32) -> (f32, bool) {
+    let fuel = if burn > 0.0 { fuel - drain * burn } else { fuel + regen * dt };
+    let fuel = fuel.clamp(0.0, max_fuel);
+    let locked = if fuel <= 0.0 { true } else { locked && fuel < max_fuel * FUEL_RESUME_FRACTION };
+    (fuel, locked)
+}
+
+/// Turns a drawing angle (degrees) by `spin` degrees per second over `dt`, kept within 0..360.
+pub fn update_rotation(rotation: f32, spin: f32, dt: f32) -> f32 {
+    (rotation + spin * dt).rem_euclid(360.0)
+}
+
+/// Speed the brake takes off per second, in px per tick (about 2.5 s from top speed).
+pub const BRAKE_DECEL: f32 = 6.0;
+/// Below this speed (px per tick) the brake holds the ship still instead of creeping.
+pub const BRAKE_STOP_SPEED: f32 = 0.05;
+
+/// Slows `vel` by `BRAKE_DECEL` over `dt` seconds without changing its direction.
+///
+/// The brake never pushes the ship backwards: once the speed would drop under
+/// `BRAKE_STOP_SPEED` the ship comes to a dead stop.
+pub fn apply_brake(vel: Vec2, dt: f32) -> Vec2 {
+    let speed = vel.length();
+    let slowed = speed - BRAKE_DECEL * dt;
+    if slowed <= BRAKE_STOP_SPEED {
+        return Vec2::ZERO;
+    }
+    vel * (slowed / speed)
+}
+
+/// Buckets circles into square cells so collision checks only look at nearby entities.
+///
+/// An entity is stored in every cell its bounding box touches, so a query only
+/// needs the cells its own box touches. Queries return candidates, not hits:
+/// callers still run `check_collision` on each.
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self { cell_size: cell_size.max(1.0), cells: HashMap::new() }
+    }
+
+    /// Range of cells covered by the box around a circle.
+    fn cell_range(&self, pos: Vec2, radius: f32) -> ((i32, i32), (i32, i32)) {
+        let cell = |v: f32| (v / self.cell_size).floor() as i32;
+        (
+            (cell(pos.x - radius), cell(pos.y - radius)),
+            (cell(pos.x + radius), cell(pos.y + radius)),
+        )
+    }
+
+    /// Adds entity `id`, a circle of `radius` at `pos`.
+    pub fn insert(&mut self, id: usize, pos: Vec2, radius: f32) {
+        let ((x0, y0), (x1, y1)) = self.cell_range(pos, radius);
+        for x in x0..=x1 {
+            for y in y0..=y1 {
+                self.cells.entry((x, y)).or_default().push(id);
+            }
+        }
+    }
+
+    /// Ids of entities that might overlap a circle of `radius` at `pos`, ascending and without duplicates.
+    pub fn query_near(&self, pos: Vec2, radius: f32) -> impl Iterator<Item = usize> {
+        let ((x0, y0), (x1, y1)) = self.cell_range(pos, radius);
+        let mut ids = Vec::new();
+        for x in x0..=x1 {
+            for y in y0..=y1 {
+                if let Some(cell) = self.cells.get(&(x, y)) {
+                    ids.extend_from_slice(cell);
+                }
+            }
+        }
+        ids.sort_unstable();
+        ids.dedup();
+        ids.into_iter()
+    }
+}
+
+/// Distance (pixels) inside which a gravity well pulls no harder, so nothing is
+/// flung out of the center.
+pub const MIN_GRAVITY_DISTANCE: f32 = 30.0;
+/// Distance (pixels) at which a well's pull equals its `strength`.
+const GRAVITY_REFERENCE_DISTANCE: f32 = 100.0;
+
+/// A star whose gravity pulls on everything within reach.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GravityWell {
+    /// Center as a fraction of the playfield (0.0..=1.0 on each axis).
+    pub center: Vec2,
+    /// Velocity gained per second (pixels per step) at 100 px; falls off with the square of distance.
+    pub strength: f32,
+    /// Reach in pixels; nothing farther away is pulled.
+    pub radius: f32,
+}
+
+impl GravityWell {
+    /// A well of `strength` and reach `radius` at the center of the playfield.
+    pub fn centered(strength: f32, radius: f32) -> Self {
+        GravityWell { center: vec2(0.5, 0.5), strength, radius }
+    }
+
+    /// Returns the well's center in playfield coordinates.
+    pub fn world_center(&self, bounds: Vec2) -> Vec2 {
+        self.center * bounds
+    }
+}
+
+/// Applies `dt` seconds of a gravity well's inverse-square pull to `vel` and returns the result.
+///
+/// The distance is clamped to `MIN_GRAVITY_DISTANCE` so the pull stays finite at the center.
+pub fn apply_gravity(pos: Vec2, vel: Vec2, well: &GravityWell, bounds: Vec2, dt: f32) -> Vec2 {
+    let offset = well.world_center(bounds) - pos;
+    let distance = offset.length();
+    if distance > well.radius || distance <= f32::EPSILON {
+        return vel;
+    }
+    let falloff = GRAVITY_REFERENCE_DISTANCE / distance.max(MIN_GRAVITY_DISTANCE);
+    vel + offset / distance * well.strength * falloff * falloff * dt
+}
+
 // --- UNIT TESTS ---
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::rock;
+
+    #[test]
+    fn test_gravity_pulls_towards_the_well() {
+        let bounds = vec2(800., 600.);
+        let well = GravityWell::centered(1.0, 300.);
+        // Right of the star: pulled left, by `strength` per second at 100 px
+        let vel = apply_gravity(vec2(500., 300.), Vec2::ZERO, &well, bounds, 1.0);
+        assert!((vel - vec2(-1., 0.)).length() < 1e-5);
+        // Below it: pulled up, four times weaker at twice the distance
+        let vel = apply_gravity(vec2(400., 500.), Vec2::ZERO, &well, bounds, 1.0);
+        assert!((vel - vec2(0., -0.25)).length() < 1e-5);
+        // Out of reach
+        assert_eq!(apply_gravity(vec2(750., 300.), vec2(1., 1.), &well, bounds, 1.0), vec2(1., 1.));
+    }
+
+    #[test]
+    fn test_gravity_clamped_near_center() {
+        let bounds = vec2(800., 600.);
+        let well = GravityWell::centered(1.0, 300.);
+        let at_min = apply_gravity(vec2(400. + MIN_GRAVITY_DISTANCE, 300.), Vec2::ZERO, &well, bounds, 1.0);
+        let closer = apply_gravity(vec2(400.5, 300.), Vec2::ZERO, &well, bounds, 1.0);
+        assert!((at_min.length() - closer.length()).abs() < 1e-4);
+        assert!(closer.is_finite());
+        // Dead center: no direction to pull in
+        assert_eq!(apply_gravity(vec2(400., 300.), Vec2::ZERO, &well, bounds, 1.0), Vec2::ZERO);
+    }
+
+    #[test]
+    fn test_rotation_wraps_within_a_turn() {
+        assert!((update_rotation(10.0, 90.0, 0.5) - 55.0).abs() < 1e-4);
+        assert!((update_rotation(350.0, 40.0, 1.0) - 30.0).abs() < 1e-4);
+        assert!((update_rotation(5.0, -30.0, 0.5) - 350.0).abs() < 1e-4);
+        let spun = update_rotation(0.0, 720.0, 10.5);
+        assert!((0.0..360.0).contains(&spun));
+    }
+
+    #[test]
+    fn test_brake_slows_fast_ship_along_its_heading() {
+        let vel = vec2(6.0, -8.0);
+        let braked = apply_brake(vel, 0.5);
+        assert!((braked.length() - (10.0 - BRAKE_DECEL * 0.5)).abs() < 1e-5);
+        assert!(braked.normalize().abs_diff_eq(vel.normalize(), 1e-5));
+    }
+
+    #[test]
+    fn test_brake_stops_slow_ship_without_reversing() {
+        let vel = vec2(-0.2, 0.1);
+        assert_eq!(apply_brake(vel, 1.0), Vec2::ZERO);
+        // A long frame can't overshoot into reverse either
+        assert_eq!(apply_brake(vec2(3.0, 0.0), 10.0), Vec2::ZERO);
+    }
+
+    #[test]
+    fn test_brake_holds_still_ship_at_zero() {
+        assert_eq!(apply_brake(Vec2::ZERO, 1.0 / 60.0), Vec2::ZERO);
+        assert_eq!(apply_brake(vec2(BRAKE_STOP_SPEED, 0.0), 1.0 / 60.0), Vec2::ZERO);
+    }
+
+    #[test]
+    fn test_fuel_drains_and_regenerates() {
+        // Partial tank: thrust drains, coasting refills
+        assert_eq!(update_fuel(50., 100., false, 1.0, 25., 10., 1.0), (25., false));
+        assert_eq!(update_fuel(50., 100., false, 0.0, 25., 10., 1.0), (60., false));
+        // Full tank stays full
+        assert_eq!(update_fuel(100., 100., false, 0.0, 25., 10., 1.0), (100., false));
+        // Burning the last drop empties and locks the tank
+        assert_eq!(update_fuel(10., 100., false, 1.0, 25., 10., 1.0), (0., true));
+    }
+
+    #[test]
+    fn test_empty_tank_stays_locked_until_threshold() {
+        let (fuel, locked) = update_fuel(0., 100., true, 0.0, 25., 10., 1.0);
+        assert_eq!((fuel, locked), (10., true));
+        let (fuel, locked) = update_fuel(fuel, 100., locked, 0.0, 25., 10., 1.0);
+        assert_eq!((fuel, locked), (20., false));
+    }
+
+    #[test]
+    fn test_spatial_grid_matches_brute_force() {
+        use macroquad::rand::{gen_range, srand};
+        srand(1506);
+        let circles: Vec<(Vec2, f32)> = (0..300)
+            .map(|_| (vec2(gen_range(-50., 850.), gen_range(-50., 650.)), gen_range(3., 50.)))
+            .collect();
+        let mut grid = SpatialGrid::new(64.);
+        for (id, (pos, radius)) in circles.iter().enumerate() {
+            grid.insert(id, *pos, *radius);
+        }
+        for _ in 0..200 {
+            let pos = vec2(gen_range(-50., 850.), gen_range(-50., 650.));
+            let radius = if gen_range(0, 2) == 0 { 0. } else { gen_range(1., 20.) };
+            let naive: Vec<usize> = (0..circles.len())
+                .filter(|&id| check_collision(pos, radius, circles[id].0, circles[id].1))
+                .collect();
+            let candidates: Vec<usize> = grid.query_near(pos, radius).collect();
+            let hits: Vec<usize> = candidates
+                .iter()
+                .copied()
+                .filter(|&id| check_collision(pos, radius, circles[id].0, circles[id].1))
+                .collect();
+            assert_eq!(hits, naive);
+            assert!(candidates.windows(2).all(|w| w[0] < w[1]));
+        }
+    }
+
+    #[test]
+    fn test_gap_between_ship_corners_is_not_a_hit() {
+        // A triangle ship pointing right: corners at 0, 120 and 240 degrees
+        let hull = poly_vertices(Vec2::ZERO, 3, 15., 0.);
+        assert!(hull[0].distance(vec2(15., 0.)) < 1e-4);
+        // Inside the bounding circle but in the gap between two corners
+        let gap = Vec2::from_angle(std::f32::consts::FRAC_PI_3) * 12.;
+        assert!(gap.length() < 15.);
+        assert!(!point_in_poly(gap, &hull));
+        assert!(!circle_vs_poly(gap, 1., &hull));
+        // Out along the nose spoke, just as far from the centre
+        assert!(point_in_poly(vec2(12., 0.), &hull));
+        assert!(circle_vs_poly(vec2(12., 0.), 1., &hull));
+        // A body whose edge reaches over a hull edge touches even with its centre outside
+        assert!(circle_vs_poly(vec2(-12., 0.), 5., &hull));
+        assert!(!circle_vs_poly(vec2(-14., 0.), 5., &hull));
+    }
+
+    #[test]
+    fn test_segment_circle_intersect() {
+        let a = vec2(0., 0.);
+        let b = vec2(100., 0.);
+        // Straight through the middle
+        assert!(segment_circle_intersect(a, b, vec2(50., 0.), 10.));
+        // Grazing within the radius
+        assert!(segment_circle_intersect(a, b, vec2(50., 9.), 10.));
+        // Off to the side
+        assert!(!segment_circle_intersect(a, b, vec2(50., 20.), 10.));
+        // Beyond the end of the segment (out of range)
+        assert!(!segment_circle_intersect(a, b, vec2(130., 0.), 10.));
+        // Behind the start
+        assert!(!segment_circle_intersect(a, b, vec2(-30., 0.), 10.));
+        // Zero-length segment inside the circle
+        assert!(segment_circle_intersect(a, a, vec2(5., 0.), 10.));
+    }
+
+    #[test]
+    fn test_angle_toward_point() {
+        use std::f32::consts::FRAC_PI_2;
+        assert_eq!(angle_toward(vec2(100., 100.), vec2(200., 100.), 1.), Some(0.0));
+        // Screen y points down, so a point below is a quarter turn clockwise
+        assert!((angle_toward(vec2(100., 100.), vec2(100., 150.), 1.).unwrap() - FRAC_PI_2).abs() < 1e-5);
+        // Cursor on top of the ship: no heading to chase
+        assert_eq!(angle_toward(vec2(100., 100.), vec2(100.5, 100.), 1.), None);
+    }
+
+    #[test]
+    fn test_shortest_rotation_delta() {
+        use std::f32::consts::PI;
+        assert!((shortest_rotation(0.0, 1.0) - 1.0).abs() < 1e-5);
+        assert!((shortest_rotation(1.0, 0.0) + 1.0).abs() < 1e-5);
+        // Across the seam it's a small step, not most of a circle
+        assert!((shortest_rotation(PI - 0.1, -PI + 0.1) - 0.2).abs() < 1e-5);
+        // Whole turns don't count
+        assert!(shortest_rotation(0.5, 0.5 + 4. * PI).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_turn_towards_shortest_arc() {
+        use std::f32::consts::PI;
+        // Small difference: reached in one step
+        assert!((turn_towards(0.0, 0.1, 0.5) - 0.1).abs() < 1e-5);
+        // Limited by max_step
+        assert!((turn_towards(0.0, 1.0, 0.25) - 0.25).abs() < 1e-5);
+        // Crossing the -PI/PI seam turns the short way (negative direction here)
+        let r = turn_towards(-PI + 0.1, PI - 0.1, 0.05);
+        assert!(r < -PI + 0.1);
+    }
+
+    #[test]
+    fn test_wrap_world_uses_its_own_size() {
+        // A small, non-square playfield unrelated to any window
+        let world = World::new(320., 200.);
+        assert_eq!(wrap_world(vec2(100., 100.), &world), vec2(100., 100.));
+        assert_eq!(wrap_world(vec2(321., 50.), &world), vec2(0., 50.));
+        assert_eq!(wrap_world(vec2(-1., 50.), &world), vec2(320., 50.));
+        assert_eq!(wrap_world(vec2(10., 201.), &world), vec2(10., 0.));
+        assert_eq!(wrap_world(vec2(10., -5.), &world), vec2(10., 200.));
+        // Inside an 800x600 screen, but outside this world
+        assert_eq!(wrap_world(vec2(500., 300.), &world), vec2(0., 0.));
+    }
+
+    #[test]
+    fn test_world_random_pos_stays_inside() {
+        let world = World::new(320., 200.);
+        assert_eq!(world.center(), vec2(160., 100.));
+        let mut rng = GameRng::new(30);
+        for _ in 0..100 {
+            let pos = world.random_pos(&mut rng);
+            assert!((0.0..=320.).contains(&pos.x) && (0.0..=200.).contains(&pos.y));
+        }
+    }
+
+    #[test]
+    fn test_wrap_pos_ignores_zero_size_bounds() {
+        let pos = vec2(-5., 900.);
+        assert_eq!(wrap_pos_checked(pos, 0., 600.), (pos, false));
+        assert_eq!(wrap_pos_checked(pos, 800., 0.), (pos, false));
+        assert_eq!(World::from_screen(0., 0.), World::new(FALLBACK_WORLD_SIZE.0, FALLBACK_WORLD_SIZE.1));
+        assert_eq!(World::from_screen(1024., 768.), World::new(1024., 768.));
+    }
+
+    #[test]
+    fn test_safe_normalize_falls_back_for_zero_vectors() {
+        let down = vec2(0., 1.);
+        assert_eq!(safe_normalize(Vec2::ZERO, down), down);
+        assert_eq!(safe_normalize(vec2(3., 4.), down), vec2(0.6, 0.8));
+        assert!(!safe_normalize(vec2(f32::NAN, 1.), down).is_nan());
+    }
 
     #[test]
     fn test_wrap_pos_inside() {
@@ -163,4 +802,125 @@ mod tests {
         let p3 = vec2(1.9, 0.);
         assert!(check_collision(p1, r1, p3, r2));
     }
+
+    #[test]
+    fn test_elastic_head_on_equal_mass_swaps() {
+        let mut a = Body { pos: vec2(0., 0.), vel: vec2(1., 0.), radius: 10., mass: 1. };
+        let mut b = Body { pos: vec2(15., 0.), vel: vec2(-1., 0.), radius: 10., mass: 1. };
+        assert!(resolve_elastic(&mut a, &mut b));
+        assert!((a.vel.x + 1.).abs() < 1e-5);
+        assert!((b.vel.x - 1.).abs() < 1e-5);
+        // Separated so they no longer overlap
+        assert!(a.pos.distance(b.pos) >= 20. - 1e-4);
+    }
+
+    #[test]
+    fn test_safe_spawn_keeps_clearance_in_cluttered_field() {
+        let world = World::new(800., 600.);
+        // Rocks everywhere except a 300x300 pocket in the bottom-right corner
+        let mut asteroids = Vec::new();
+        for x in (0..800).step_by(100) {
+            for y in (0..600).step_by(100) {
+                if x < 500 || y < 300 {
+                    asteroids.push(rock(vec2(x as f32 + 50., y as f32 + 50.), Vec2::ZERO, 30.));
+                }
+            }
+        }
+        assert!(hazard_clearance(world.center(), &asteroids, &[], &world) < 100.);
+        let spawn = find_safe_spawn(&asteroids, &[], &world, 100.);
+        assert!(hazard_clearance(spawn, &asteroids, &[], &world) >= 100.);
+        assert!(spawn.x > 500. && spawn.y > 300.);
+    }
+
+    #[test]
+    fn test_safe_spawn_prefers_center_and_falls_back_to_most_room() {
+        let world = World::new(800., 600.);
+        assert_eq!(find_safe_spawn(&[], &[], &world, 100.), world.center());
+        // Nowhere has 1000px of room: take the point farthest from the lone rock
+        let asteroids = [rock(vec2(100., 100.), Vec2::ZERO, 30.)];
+        let spawn = find_safe_spawn(&asteroids, &[], &world, 1000.);
+        let center_room = hazard_clearance(world.center(), &asteroids, &[], &world);
+        assert!(hazard_clearance(spawn, &asteroids, &[], &world) >= center_room);
+    }
+
+    #[test]
+    fn test_asteroid_collision_head_on_swaps_velocities() {
+        let mut a = rock(vec2(0., 0.), vec2(2., 0.), 20.);
+        let mut b = rock(vec2(35., 0.), vec2(-1., 0.), 20.);
+        assert!(resolve_asteroid_collision(&mut a, &mut b));
+        assert!((a.vel.x + 1.).abs() < 1e-5 && (b.vel.x - 2.).abs() < 1e-5);
+        assert!(a.pos.distance(b.pos) >= 40. - 1e-4);
+    }
+
+    #[test]
+    fn test_asteroid_collision_glancing_hit() {
+        // b is struck off-centre: the normal is 60 degrees from a's motion
+        let normal = vec2(0.5, 3f32.sqrt() / 2.);
+        let mut a = rock(vec2(0., 0.), vec2(3., 0.), 20.);
+        let mut b = rock(normal * 39., Vec2::ZERO, 20.);
+        assert!(resolve_asteroid_collision(&mut a, &mut b));
+        // Only the velocity along the normal is handed over; a keeps the tangential part
+        let expected_b = normal * 1.5;
+        assert!((b.vel - expected_b).length() < 1e-4, "{:?}", b.vel);
+        assert!((a.vel - (vec2(3., 0.) - expected_b)).length() < 1e-4, "{:?}", a.vel);
+        assert!(a.vel.dot(b.vel).abs() < 1e-4);
+        // Momentum (equal masses) is conserved
+        assert!((a.vel + b.vel - vec2(3., 0.)).length() < 1e-4);
+    }
+
+    #[test]
+    fn test_asteroid_collision_coincident_centers_stay_finite() {
+        let mut a = rock(vec2(100., 100.), vec2(1., 0.), 20.);
+        let mut b = rock(vec2(100., 100.), vec2(-1., 0.), 30.);
+        assert!(resolve_asteroid_collision(&mut a, &mut b));
+        assert!(a.pos.is_finite() && a.vel.is_finite() && b.pos.is_finite() && b.vel.is_finite());
+        assert!(a.pos.distance(b.pos) >= 50. - 1e-3);
+    }
+
+    #[test]
+    fn test_elastic_no_overlap_is_noop() {
+        let mut a = Body { pos: vec2(0., 0.), vel: vec2(1., 0.), radius: 10., mass: 1. };
+        let mut b = Body { pos: vec2(30., 0.), vel: vec2(-1., 0.), radius: 10., mass: 1. };
+        assert!(!resolve_elastic(&mut a, &mut b));
+        assert_eq!(a.vel, vec2(1., 0.));
+        assert_eq!(b.vel, vec2(-1., 0.));
+    }
+
+    #[test]
+    fn test_slow_impact_bounces_fast_impact_shatters_smaller() {
+        let small = Body { pos: vec2(0., 0.), vel: vec2(0.5, 0.), radius: 15., mass: 225. };
+        let big = Body { pos: vec2(30., 0.), vel: vec2(-0.5, 0.), radius: 40., mass: 1600. };
+        assert_eq!(classify_asteroid_impact(&small, &big, 4.0), AsteroidImpact::Bounce);
+
+        let fast_small = Body { vel: vec2(4., 0.), ..small };
+        let fast_big = Body { vel: vec2(-3., 0.), ..big };
+        assert_eq!(classify_asteroid_impact(&fast_small, &fast_big, 4.0), AsteroidImpact::ShatterFirst);
+        assert_eq!(classify_asteroid_impact(&fast_big, &fast_small, 4.0), AsteroidImpact::ShatterSecond);
+    }
+
+    #[test]
+    fn test_drag_zone_decelerates_faster_than_open_space() {
+        let bounds = vec2(800., 600.);
+        let zones = [Zone { center: vec2(0.5, 0.5), radius: 100., effect: ZoneEffect::Drag(0.05) }];
+        let inside = vec2(400., 300.);
+        let outside = vec2(50., 50.);
+        assert!(is_inside_zone(inside, &zones[0], bounds));
+        assert!(!is_inside_zone(outside, &zones[0], bounds));
+
+        let mut vel_in = vec2(5., 0.);
+        let mut vel_out = vec2(5., 0.);
+        for _ in 0..10 {
+            vel_in = apply_zones(inside, vel_in, &zones, bounds) * 0.98;
+            vel_out = apply_zones(outside, vel_out, &zones, bounds) * 0.98;
+        }
+        assert!(vel_in.length() < vel_out.length());
+    }
+
+    #[test]
+    fn test_push_zone_adds_force_only_inside() {
+        let bounds = vec2(800., 600.);
+        let zones = [Zone { center: vec2(0.25, 0.5), radius: 50., effect: ZoneEffect::Push(vec2(0., 0.1)) }];
+        assert_eq!(apply_zones(vec2(200., 300.), Vec2::ZERO, &zones, bounds), vec2(0., 0.1));
+        assert_eq!(apply_zones(vec2(600., 300.), Vec2::ZERO, &zones, bounds), Vec2::ZERO);
+    }
 }
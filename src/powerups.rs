@@ -0,0 +1,134 @@
+use macroquad::prelude::*;
+use crate::components::{PowerUp, PowerUpKind};
+
+/// Chance that an asteroid shot down by the player leaves a shield behind.
+pub const SHIELD_DROP_CHANCE: f32 = 0.05;
+/// Chance that an asteroid shot down by the player leaves a bomb behind (rolled separately).
+pub const BOMB_DROP_CHANCE: f32 = 0.02;
+/// Chance that an asteroid shot down by the player leaves a pierce pickup behind (rolled last).
+pub const PIERCE_DROP_CHANCE: f32 = 0.02;
+/// Seconds a pierce pickup lets a ship's shots pass through asteroids.
+pub const PIERCE_DURATION: f32 = 10.0;
+/// Collision radius of a power-up.
+pub const POWERUP_RADIUS: f32 = 10.0;
+/// Top drift speed of a freshly dropped power-up (px per 60 Hz tick).
+pub const POWERUP_DRIFT: f32 = 0.8;
+/// Points for collecting a UFO's point token (not multiplied by the combo).
+pub const POINT_TOKEN_VALUE: u32 = 150;
+/// Distance within which pickups are drawn towards the nearest ship.
+pub const MAGNET_RADIUS: f32 = 140.0;
+/// Speed a pickup in range gains towards the ship, in px per tick per second.
+pub const MAGNET_PULL: f32 = 6.0;
+/// Fastest a magnet can pull a pickup (px per 60 Hz tick).
+pub const MAGNET_MAX_SPEED: f32 = 5.0;
+/// Invulnerability after a shield soaks a hit, so one collision can't drain several charges.
+pub const SHIELD_GRACE: f32 = 1.0;
+
+/// Drops a shield at `pos` when `roll` (uniform in 0..1) comes in under `SHIELD_DROP_CHANCE`.
+pub fn roll_shield_drop(pos: Vec2, drift: Vec2, roll: f32) -> Option<PowerUp> {
+    roll_drop(PowerUpKind::Shield, SHIELD_DROP_CHANCE, pos, drift, roll)
+}
+
+/// Drops a bomb at `pos` when `roll` (uniform in 0..1) comes in under `BOMB_DROP_CHANCE`.
+pub fn roll_bomb_drop(pos: Vec2, drift: Vec2, roll: f32) -> Option<PowerUp> {
+    roll_drop(PowerUpKind::Bomb, BOMB_DROP_CHANCE, pos, drift, roll)
+}
+
+/// Drops a pierce pickup at `pos` when `roll` (uniform in 0..1) comes in under `PIERCE_DROP_CHANCE`.
+pub fn roll_pierce_drop(pos: Vec2, drift: Vec2, roll: f32) -> Option<PowerUp> {
+    roll_drop(PowerUpKind::Pierce, PIERCE_DROP_CHANCE, pos, drift, roll)
+}
+
+fn roll_drop(kind: PowerUpKind, chance: f32, pos: Vec2, drift: Vec2, roll: f32) -> Option<PowerUp> {
+    if roll >= chance {
+        return None;
+    }
+    Some(PowerUp {
+        pos,
+        prev_pos: pos,
+        vel: drift,
+        kind,
+        radius: POWERUP_RADIUS,
+        active: true,
+    })
+}
+
+/// Point token a destroyed UFO leaves at `pos`, drifting on with `drift`.
+pub fn point_token(pos: Vec2, drift: Vec2) -> PowerUp {
+    PowerUp {
+        pos,
+        prev_pos: pos,
+        vel: drift,
+        kind: PowerUpKind::Points,
+        radius: POWERUP_RADIUS,
+        active: true,
+    }
+}
+
+/// Velocity of a pickup at `pos` after `dt` seconds of magnet pull towards a ship at
+/// `target`: unchanged beyond `MAGNET_RADIUS`, otherwise sped towards the ship up to
+/// `MAGNET_MAX_SPEED`.
+pub fn magnet_velocity(pos: Vec2, vel: Vec2, target: Vec2, dt: f32) -> Vec2 {
+    let to_target = target - pos;
+    let distance = to_target.length();
+    if !(1e-3..=MAGNET_RADIUS).contains(&distance) {
+        return vel;
+    }
+    (vel + to_target / distance * MAGNET_PULL * dt.max(0.0)).clamp_length_max(MAGNET_MAX_SPEED)
+}
+
+/// Spends a shield charge on an incoming hit.
+///
+/// Returns true when the hit was absorbed: the ship keeps its life and position.
+pub fn absorb_hit(shield_charges: &mut u32) -> bool {
+    if *shield_charges == 0 {
+        return false;
+    }
+    *shield_charges -= 1;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shield_absorbs_one_hit_per_charge() {
+        let mut charges = 2;
+        assert!(absorb_hit(&mut charges));
+        assert!(absorb_hit(&mut charges));
+        assert_eq!(charges, 0);
+        // Out of charges: the hit goes through
+        assert!(!absorb_hit(&mut charges));
+        assert_eq!(charges, 0);
+    }
+
+    #[test]
+    fn test_magnet_pulls_only_pickups_in_range() {
+        let ship = vec2(400., 300.);
+        let drift = vec2(0.5, 0.);
+        // Out of range: drifts on untouched
+        let far = ship + vec2(MAGNET_RADIUS + 1., 0.);
+        assert_eq!(magnet_velocity(far, drift, ship, 0.5), drift);
+        // In range: speeds up towards the ship
+        let near = ship + vec2(0., MAGNET_RADIUS * 0.5);
+        let pulled = magnet_velocity(near, drift, ship, 0.5);
+        assert!(pulled.y < 0.0);
+        assert_eq!(pulled.x, drift.x);
+        // However long the pull, it never exceeds the cap
+        assert!(magnet_velocity(near, drift, ship, 10.0).length() <= MAGNET_MAX_SPEED + 1e-4);
+        // Sitting right on the ship: nothing to pull towards
+        assert_eq!(magnet_velocity(ship, drift, ship, 0.5), drift);
+    }
+
+    #[test]
+    fn test_shield_drop_roll() {
+        let pos = vec2(100., 50.);
+        let drop = roll_shield_drop(pos, vec2(0.5, 0.), 0.0).unwrap();
+        assert_eq!(drop.kind, PowerUpKind::Shield);
+        assert_eq!(drop.pos, pos);
+        assert!(roll_shield_drop(pos, vec2(0.5, 0.), SHIELD_DROP_CHANCE).is_none());
+        assert_eq!(roll_bomb_drop(pos, vec2(0.5, 0.), 0.0).unwrap().kind, PowerUpKind::Bomb);
+        assert!(roll_bomb_drop(pos, vec2(0.5, 0.), BOMB_DROP_CHANCE).is_none());
+    }
+}
@@ -0,0 +1,208 @@
+use macroquad::logging::{error, info, warn};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+
+/// Number of boss-rush times kept on file.
+pub const MAX_BOSS_RUSH_TIMES: usize = 10;
+/// Number of most recent days whose daily-challenge best is kept on file.
+pub const MAX_DAILY_DAYS: usize = 30;
+
+/// Best completion times (in seconds) for boss-rush mode, fastest first.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BossRushRecords {
+    pub times: Vec<f32>,
+}
+
+impl BossRushRecords {
+    /// Inserts a completion time, keeping the list sorted and truncated.
+    ///
+    /// Returns the 0-based rank of the new time, or `None` if it didn't make the table.
+    pub fn insert(&mut self, time: f32) -> Option<usize> {
+        if !time.is_finite() || time < 0.0 {
+            return None;
+        }
+        let rank = self.times.iter().position(|&t| time < t).unwrap_or(self.times.len());
+        if rank >= MAX_BOSS_RUSH_TIMES {
+            return None;
+        }
+        self.times.insert(rank, time);
+        self.times.truncate(MAX_BOSS_RUSH_TIMES);
+        Some(rank)
+    }
+
+    pub fn from_str(input: &str) -> Self {
+        let mut records = BossRushRecords::default();
+        for line in input.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(value) = line.strip_prefix("time=") {
+                if let Ok(t) = value.trim().parse::<f32>() {
+                    records.insert(t);
+                }
+            }
+        }
+        records
+    }
+
+    pub fn load_from_file(path: &str) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                info!("Boss rush records loaded from {}", path);
+                Ok(BossRushRecords::from_str(&contents))
+            }
+            Err(e) => {
+                warn!("Boss rush records not found or unreadable: {}. Starting fresh.", e);
+                Err(format!("Failed to read boss rush records: {}", e))
+            }
+        }
+    }
+
+    pub fn save_to_file(&self, path: &str) -> Result<(), String> {
+        if let Err(e) = fs::write(path, self.to_string()) {
+            error!("Failed to save boss rush records to {}: {}", path, e);
+            return Err(format!("Failed to write boss rush records: {}", e));
+        }
+        info!("Boss rush records saved to {}", path);
+        Ok(())
+    }
+}
+
+impl fmt::Display for BossRushRecords {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for t in &self.times {
+            writeln!(f, "time={:.2}", t)?;
+        }
+        Ok(())
+    }
+}
+
+/// Best daily-challenge score for each day, keyed by the day's `YYYYMMDD` seed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DailyRecords {
+    pub best: BTreeMap<u64, u32>,
+}
+
+impl DailyRecords {
+    pub fn best_for(&self, date: u64) -> Option<u32> {
+        self.best.get(&date).copied()
+    }
+
+    /// Records a finished run for `date`, keeping only the most recent days.
+    ///
+    /// Returns true if it is a new best for that day.
+    pub fn submit(&mut self, date: u64, score: u32) -> bool {
+        if self.best_for(date).is_some_and(|best| best >= score) {
+            return false;
+        }
+        self.best.insert(date, score);
+        while self.best.len() > MAX_DAILY_DAYS {
+            self.best.pop_first();
+        }
+        self.best.contains_key(&date)
+    }
+
+    pub fn from_str(input: &str) -> Self {
+        let mut records = DailyRecords::default();
+        for line in input.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((date, score)) = line.split_once('=') {
+                if let (Ok(date), Ok(score)) = (date.trim().parse::<u64>(), score.trim().parse::<u32>()) {
+                    records.submit(date, score);
+                }
+            }
+        }
+        records
+    }
+
+    pub fn load_from_file(path: &str) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                info!("Daily challenge records loaded from {}", path);
+                Ok(DailyRecords::from_str(&contents))
+            }
+            Err(e) => {
+                warn!("Daily challenge records not found or unreadable: {}. Starting fresh.", e);
+                Err(format!("Failed to read daily challenge records: {}", e))
+            }
+        }
+    }
+
+    pub fn save_to_file(&self, path: &str) -> Result<(), String> {
+        if let Err(e) = fs::write(path, self.to_string()) {
+            error!("Failed to save daily challenge records to {}: {}", path, e);
+            return Err(format!("Failed to write daily challenge records: {}", e));
+        }
+        info!("Daily challenge records saved to {}", path);
+        Ok(())
+    }
+}
+
+impl fmt::Display for DailyRecords {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (date, score) in &self.best {
+            writeln!(f, "{}={}", date, score)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_keeps_fastest_first() {
+        let mut r = BossRushRecords::default();
+        assert_eq!(r.insert(50.0), Some(0));
+        assert_eq!(r.insert(30.0), Some(0));
+        assert_eq!(r.insert(40.0), Some(1));
+        assert_eq!(r.times, vec![30.0, 40.0, 50.0]);
+    }
+
+    #[test]
+    fn test_insert_truncates() {
+        let mut r = BossRushRecords::default();
+        for i in 0..MAX_BOSS_RUSH_TIMES {
+            r.insert(10.0 + i as f32);
+        }
+        assert_eq!(r.insert(100.0), None);
+        assert_eq!(r.insert(5.0), Some(0));
+        assert_eq!(r.times.len(), MAX_BOSS_RUSH_TIMES);
+        assert_eq!(r.times[0], 5.0);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut r = BossRushRecords::default();
+        r.insert(42.5);
+        r.insert(61.25);
+        assert_eq!(BossRushRecords::from_str(&r.to_string()), r);
+    }
+
+    #[test]
+    fn test_daily_best_is_kept_per_date() {
+        let mut r = DailyRecords::default();
+        assert!(r.submit(20261015, 1200));
+        assert!(!r.submit(20261015, 900));
+        assert!(r.submit(20261016, 300));
+        assert_eq!(r.best_for(20261015), Some(1200));
+        assert_eq!(r.best_for(20261016), Some(300));
+        assert_eq!(DailyRecords::from_str(&r.to_string()), r);
+    }
+
+    #[test]
+    fn test_daily_drops_oldest_days() {
+        let mut r = DailyRecords::default();
+        for day in 0..MAX_DAILY_DAYS as u64 + 5 {
+            r.submit(20260101 + day, 100);
+        }
+        assert_eq!(r.best.len(), MAX_DAILY_DAYS);
+        assert_eq!(r.best_for(20260101), None);
+    }
+}
@@ -1,12 +1,109 @@
 use macroquad::audio::{load_sound_from_bytes, play_sound, PlaySoundParams, Sound};
-use macroquad::logging::{error, info, warn};
+use macroquad::logging::{error, info};
+use macroquad::time::get_time;
+use std::cell::RefCell;
+
+/// Every sound effect's name (used for settings keys), in `SoundId::ALL` order.
+pub const SOUND_NAMES: [&str; SoundId::COUNT] = ["shoot", "bang", "warp", "sweep", "slowmo", "token", "misjump"];
+
+/// A sound effect the game can play.
+///
+/// New sounds go on the end, with a matching entry in `ALL`, `SOUND_NAMES` and
+/// `Resources`; bumping `COUNT` resizes every per-sound array with them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SoundId {
+    Shoot,
+    Bang,
+    Warp,
+    /// Clean-sweep bonus chime.
+    Sweep,
+    /// Bullet time kicking in.
+    SlowMo,
+    /// Point token collected.
+    Token,
+    /// Hyperspace jump gone wrong.
+    Misjump,
+}
+
+impl SoundId {
+    /// Number of sounds: the length of every per-sound array.
+    pub const COUNT: usize = 7;
+
+    /// Every sound, in the order of the per-sound volume arrays.
+    pub const ALL: [SoundId; SoundId::COUNT] = [SoundId::Shoot, SoundId::Bang, SoundId::Warp, SoundId::Sweep, SoundId::SlowMo, SoundId::Token, SoundId::Misjump];
+
+    /// Position in `ALL`, `SOUND_NAMES` and the per-sound volume arrays.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn name(self) -> &'static str {
+        SOUND_NAMES[self.index()]
+    }
+}
+
+/// Something that can play the game's sounds: the real mixer, or a stand-in for tests.
+pub trait AudioBackend {
+    fn play(&self, clip: SoundId);
+    /// Sets the master volume (clamped to 0.0..=1.0).
+    fn set_volume(&mut self, volume: f32);
+}
+
+/// Default shortest gap, in seconds, before the same sound can play again.
+pub const DEFAULT_REPLAY_INTERVAL: f32 = 0.03;
+
+/// Coalesces rapid repeats of a sound: a clip asked for again within its replay
+/// interval of the last time it actually played is skipped, so a dozen rocks
+/// breaking in one frame make one bang instead of a clipped pile-up.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SoundThrottle {
+    /// Per-sound minimum gap in seconds, in `SoundId::ALL` order.
+    intervals: [f32; SoundId::COUNT],
+    /// When each sound last played, in seconds on the caller's clock.
+    last_played: [Option<f64>; SoundId::COUNT],
+}
+
+impl SoundThrottle {
+    pub fn new(intervals: [f32; SoundId::COUNT]) -> Self {
+        SoundThrottle { intervals: intervals.map(|i| i.max(0.0)), last_played: [None; SoundId::COUNT] }
+    }
+
+    pub fn set_interval(&mut self, clip: SoundId, seconds: f32) {
+        self.intervals[clip.index()] = seconds.max(0.0);
+    }
+
+    /// Whether `clip` may play at time `now` (seconds); if so, it counts as played then.
+    pub fn allow(&mut self, clip: SoundId, now: f64) -> bool {
+        let last = &mut self.last_played[clip.index()];
+        if last.is_some_and(|t| now - t < self.intervals[clip.index()] as f64) {
+            return false;
+        }
+        *last = Some(now);
+        true
+    }
+}
+
+/// Loudness of one sound: the master volume scaled by its own level, kept within 0.0..=1.0.
+pub fn mix_volume(master: f32, sound: f32) -> f32 {
+    (master * sound).clamp(0.0, 1.0)
+}
 
 /// Manages game audio resources.
 pub struct Resources {
     shoot: Option<Sound>,
     bang: Option<Sound>,
     warp: Option<Sound>,
+    sweep: Option<Sound>,
+    slowmo: Option<Sound>,
+    token: Option<Sound>,
+    misjump: Option<Sound>,
     volume: f32,
+    /// Silences every sound without touching `volume`.
+    muted: bool,
+    /// Per-sound levels (see `SOUND_NAMES`), applied on top of `volume`.
+    sound_volumes: [f32; SoundId::COUNT],
+    /// Drops repeats of a sound that come too soon after it last played.
+    throttle: RefCell<SoundThrottle>,
 }
 
 impl Resources {
@@ -32,40 +129,183 @@ impl Resources {
         let shoot_bytes = include_bytes!("../assets/shoot.wav");
         let bang_bytes = include_bytes!("../assets/bang.wav");
         let warp_bytes = include_bytes!("../assets/warp.wav");
+        let sweep_bytes = include_bytes!("../assets/sweep.wav");
+        let slowmo_bytes = include_bytes!("../assets/slowmo.wav");
+        let token_bytes = include_bytes!("../assets/token.wav");
+        let misjump_bytes = include_bytes!("../assets/misjump.wav");
 
         Resources {
             shoot: load_snd(shoot_bytes, "shoot").await,
             bang: load_snd(bang_bytes, "bang").await,
             warp: load_snd(warp_bytes, "warp").await,
+            sweep: load_snd(sweep_bytes, "sweep").await,
+            slowmo: load_snd(slowmo_bytes, "slowmo").await,
+            token: load_snd(token_bytes, "token").await,
+            misjump: load_snd(misjump_bytes, "misjump").await,
             volume: 1.0,
+            muted: false,
+            sound_volumes: [1.0; SoundId::COUNT],
+            throttle: RefCell::new(SoundThrottle::new([DEFAULT_REPLAY_INTERVAL; SoundId::COUNT])),
         }
     }
 
-    /// Updates the master sound volume (clamped to 0.0..=1.0).
-    pub fn set_volume(&mut self, volume: f32) {
-        self.volume = volume.clamp(0.0, 1.0);
+    /// Sets the level of one sound (clamped to 0.0..=1.0).
+    pub fn set_sound_volume(&mut self, clip: SoundId, volume: f32) {
+        self.sound_volumes[clip.index()] = volume.clamp(0.0, 1.0);
     }
 
-    /// Plays a sound by name ("shoot", "bang", "warp").
-    pub fn play(&self, sound_type: &str) {
-        let sound = match sound_type {
-            "shoot" => &self.shoot,
-            "bang" => &self.bang,
-            "warp" => &self.warp,
-            _ => {
-                warn!("Unknown sound type requested: {}", sound_type);
-                &None
-            },
+    /// Sets how long (seconds) `clip` must wait after playing before it can play again.
+    pub fn set_replay_interval(&mut self, clip: SoundId, seconds: f32) {
+        self.throttle.get_mut().set_interval(clip, seconds);
+    }
+
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+
+    /// Volume sounds actually play at: silent while muted, otherwise the master volume.
+    pub fn effective_volume(&self) -> f32 {
+        if self.muted { 0.0 } else { self.volume }
+    }
+
+}
+
+impl AudioBackend for Resources {
+    fn play(&self, clip: SoundId) {
+        let sound = match clip {
+            SoundId::Shoot => &self.shoot,
+            SoundId::Bang => &self.bang,
+            SoundId::Warp => &self.warp,
+            SoundId::Sweep => &self.sweep,
+            SoundId::SlowMo => &self.slowmo,
+            SoundId::Token => &self.token,
+            SoundId::Misjump => &self.misjump,
         };
-        
+        let level = self.sound_volumes[clip.index()];
+        if !self.throttle.borrow_mut().allow(clip, get_time()) {
+            return;
+        }
         if let Some(s) = sound {
             play_sound(
                 s,
                 PlaySoundParams {
                     looped: false,
-                    volume: self.volume,
+                    volume: mix_volume(self.effective_volume(), level),
                 },
             );
         }
     }
+
+    fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.clamp(0.0, 1.0);
+    }
+}
+
+/// Plays nothing.
+#[cfg(test)]
+pub struct NullAudio;
+
+#[cfg(test)]
+impl AudioBackend for NullAudio {
+    fn play(&self, _clip: SoundId) {}
+    fn set_volume(&mut self, _volume: f32) {}
+}
+
+/// Remembers every sound asked for, in order, so tests can check what was heard.
+#[cfg(test)]
+#[derive(Default)]
+pub struct RecordingAudio {
+    pub played: RefCell<Vec<SoundId>>,
+    pub volume: f32,
+}
+
+#[cfg(test)]
+impl AudioBackend for RecordingAudio {
+    fn play(&self, clip: SoundId) {
+        self.played.borrow_mut().push(clip);
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.clamp(0.0, 1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn silent_resources(volume: f32, muted: bool) -> Resources {
+        Resources {
+            shoot: None,
+            bang: None,
+            warp: None,
+            sweep: None,
+            slowmo: None,
+            token: None,
+            misjump: None,
+            volume,
+            muted,
+            sound_volumes: [1.0; SoundId::COUNT],
+            throttle: RefCell::new(SoundThrottle::new([DEFAULT_REPLAY_INTERVAL; SoundId::COUNT])),
+        }
+    }
+
+    #[test]
+    fn test_effective_volume_respects_mute() {
+        for volume in [0.0, 0.35, 1.0] {
+            assert_eq!(silent_resources(volume, false).effective_volume(), volume);
+            assert_eq!(silent_resources(volume, true).effective_volume(), 0.0);
+        }
+        // Unmuting restores the volume that was set while muted
+        let mut resources = silent_resources(1.0, false);
+        resources.set_muted(true);
+        resources.set_volume(0.6);
+        assert_eq!(resources.effective_volume(), 0.0);
+        resources.set_muted(false);
+        assert_eq!(resources.effective_volume(), 0.6);
+    }
+
+    #[test]
+    fn test_per_sound_volume_scales_master() {
+        assert_eq!(mix_volume(0.5, 0.5), 0.25);
+        assert_eq!(mix_volume(1.0, 0.3), 0.3);
+        assert_eq!(mix_volume(0.0, 1.0), 0.0);
+        assert_eq!(mix_volume(1.5, 1.0), 1.0);
+        assert_eq!(mix_volume(0.8, -1.0), 0.0);
+
+        let mut resources = silent_resources(0.8, false);
+        resources.set_sound_volume(SoundId::Shoot, 0.5);
+        resources.set_sound_volume(SoundId::Bang, 3.0);
+        assert_eq!(resources.sound_volumes[SoundId::Shoot.index()], 0.5);
+        assert_eq!(resources.sound_volumes[SoundId::Bang.index()], 1.0);
+    }
+
+    #[test]
+    fn test_throttle_coalesces_repeats_within_the_interval() {
+        let mut throttle = SoundThrottle::new([DEFAULT_REPLAY_INTERVAL; SoundId::COUNT]);
+        // Two bangs 10 ms apart: only the first plays
+        assert!(throttle.allow(SoundId::Bang, 5.000));
+        assert!(!throttle.allow(SoundId::Bang, 5.010));
+        // Spaced out, both play; the skipped one didn't push the window back
+        assert!(throttle.allow(SoundId::Bang, 5.035));
+        assert!(throttle.allow(SoundId::Bang, 5.100));
+        // Other sounds keep their own clocks
+        assert!(throttle.allow(SoundId::Shoot, 5.101));
+
+        throttle.set_interval(SoundId::Warp, 0.5);
+        assert!(throttle.allow(SoundId::Warp, 1.0));
+        assert!(!throttle.allow(SoundId::Warp, 1.4));
+        assert!(throttle.allow(SoundId::Warp, 1.5));
+        throttle.set_interval(SoundId::Shoot, 0.0);
+        assert!(throttle.allow(SoundId::Shoot, 5.101));
+    }
+
+    #[test]
+    fn test_sound_ids_line_up_with_names() {
+        for (i, clip) in SoundId::ALL.iter().enumerate() {
+            assert_eq!(clip.index(), i);
+        }
+        assert_eq!(SoundId::ALL.len(), SOUND_NAMES.len());
+        assert_eq!(SoundId::Warp.name(), "warp");
+    }
 }
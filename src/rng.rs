@@ -0,0 +1,137 @@
+use macroquad::rand::gen_range;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seed used for asteroid shapes when fixed shapes are enabled.
+pub const FIXED_SHAPES_SEED: u64 = 0x5EED_A57E;
+
+/// Small seedable random number generator (SplitMix64).
+///
+/// Unlike the global macroquad generator, two `GameRng`s created from the same
+/// seed always produce the same sequence, on every platform.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameRng {
+    state: u64,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform integer in `low..high` (`high` exclusive).
+    pub fn gen_range_u32(&mut self, low: u32, high: u32) -> u32 {
+        if high <= low {
+            return low;
+        }
+        low + (self.next_u64() % (high - low) as u64) as u32
+    }
+
+    /// Uniform float between `low` and `high` (`low` when the range is empty).
+    pub fn gen_range_f32(&mut self, low: f32, high: f32) -> f32 {
+        if high <= low {
+            return low;
+        }
+        // Top 24 bits, so every step is exactly representable as an f32
+        let unit = (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32;
+        low + unit * (high - low)
+    }
+
+    /// `1.0` or `-1.0` with equal odds.
+    pub fn gen_sign(&mut self) -> f32 {
+        if self.next_u64() >> 63 == 0 { 1.0 } else { -1.0 }
+    }
+}
+
+/// A fresh seed from the clock, for runs without a fixed seed.
+pub fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Hands out asteroid polygon side counts.
+///
+/// With a seed, the count for each asteroid is derived from the seed and the
+/// asteroid's ID (its spawn order within the level), so every player using the
+/// same seed sees identical shapes. Without one, shapes are random as before.
+#[derive(Clone, Debug, Default)]
+pub struct ShapeSource {
+    pub seed: Option<u64>,
+    next_id: u64,
+}
+
+impl ShapeSource {
+    pub fn new(seed: Option<u64>) -> Self {
+        Self { seed, next_id: 0 }
+    }
+
+    /// Restarts ID numbering; called when a level is (re)loaded.
+    pub fn reset(&mut self) {
+        self.next_id = 0;
+    }
+
+    /// Side count (5..=8) for the next asteroid spawned.
+    pub fn next_sides(&mut self) -> u8 {
+        let id = self.next_id;
+        self.next_id += 1;
+        match self.seed {
+            Some(seed) => GameRng::new(seed ^ id.wrapping_mul(0xD6E8_FEB8_6659_FD93)).gen_range_u32(5, 9) as u8,
+            None => gen_range(5, 9),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_rng_is_deterministic() {
+        let mut a = GameRng::new(42);
+        let mut b = GameRng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        let mut c = GameRng::new(43);
+        assert_ne!(GameRng::new(42).next_u64(), c.next_u64());
+    }
+
+    #[test]
+    fn test_gen_range_bounds() {
+        let mut rng = GameRng::new(7);
+        for _ in 0..1000 {
+            let v = rng.gen_range_u32(5, 9);
+            assert!((5..9).contains(&v));
+        }
+        assert_eq!(rng.gen_range_u32(3, 3), 3);
+        for _ in 0..1000 {
+            let v = rng.gen_range_f32(-2.5, 4.0);
+            assert!((-2.5..=4.0).contains(&v));
+        }
+        assert_eq!(rng.gen_range_f32(1.0, 1.0), 1.0);
+    }
+
+    #[test]
+    fn test_seeded_shapes_match() {
+        let mut a = ShapeSource::new(Some(FIXED_SHAPES_SEED));
+        let mut b = ShapeSource::new(Some(FIXED_SHAPES_SEED));
+        let first: Vec<u8> = (0..32).map(|_| a.next_sides()).collect();
+        let second: Vec<u8> = (0..32).map(|_| b.next_sides()).collect();
+        assert_eq!(first, second);
+        assert!(first.iter().all(|s| (5..=8).contains(s)));
+
+        // Reloading the level replays the same shapes
+        a.reset();
+        let replay: Vec<u8> = (0..32).map(|_| a.next_sides()).collect();
+        assert_eq!(first, replay);
+    }
+}
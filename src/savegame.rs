@@ -0,0 +1,352 @@
+use macroquad::logging::{error, info, warn};
+use macroquad::prelude::*;
+use std::fmt;
+use std::fs;
+use crate::boss::BossPhase;
+use crate::components::{Asteroid, Boss, Bullet, BulletOwner, Player, Ufo};
+
+/// Save file format version written in the header (2 added ship activity and the hyperspace and pierce timers).
+const SAVE_VERSION: u32 = 2;
+
+/// A run paused mid-level, written from the pause menu and resumed from the title screen.
+///
+/// Every ship is stored; other entities only while active.
+pub struct GameSnapshot {
+    pub score: u32,
+    /// Lives per pool: one count when the team shares lives, one per ship otherwise.
+    pub lives: Vec<i32>,
+    pub current_level_idx: usize,
+    /// Procedural wave being played in endless mode (`None` within the configured levels).
+    pub endless_wave: Option<usize>,
+    pub last_extra_life_score: u32,
+    pub players: Vec<Player>,
+    pub asteroids: Vec<Asteroid>,
+    pub ufos: Vec<Ufo>,
+    pub bosses: Vec<Boss>,
+    pub bullets: Vec<Bullet>,
+}
+
+/// Parses a comma-separated line of `N` numbers (or fewer/more -> error).
+fn fields<const N: usize>(kind: &str, value: &str) -> Result<[f32; N], String> {
+    let parts: Vec<f32> = value.split(',').map(|v| v.trim().parse::<f32>()).collect::<Result<_, _>>()
+        .map_err(|_| format!("invalid {} '{}'", kind, value))?;
+    parts.try_into().map_err(|_| format!("invalid {} '{}'", kind, value))
+}
+
+fn flag(value: f32) -> bool {
+    value != 0.0
+}
+
+fn parse_player(value: &str) -> Result<Player, String> {
+    let [x, y, vx, vy, rotation, radius, active, sides, r, g, b, a, invulnerable, invulnerable_timer, stun_timer, fire_cooldown,
+         hyperspace_cooldown, fuel, max_fuel, fuel_locked, shield_charges, bombs, pierce_timer] = fields::<23>("player", value)?;
+    Ok(Player {
+        pos: vec2(x, y),
+        prev_pos: vec2(x, y),
+        vel: vec2(vx, vy),
+        rotation,
+        radius,
+        active: flag(active),
+        sides: sides as u8,
+        color: Color::new(r, g, b, a),
+        invulnerable: flag(invulnerable),
+        invulnerable_timer,
+        thrusting: false,
+        stun_timer,
+        fire_cooldown,
+        hyperspace_cooldown,
+        fuel,
+        max_fuel,
+        fuel_locked: flag(fuel_locked),
+        shield_charges: shield_charges as u32,
+        bombs: bombs as u8,
+        pierce_timer,
+    })
+}
+
+fn parse_asteroid(value: &str) -> Result<Asteroid, String> {
+    let [x, y, vx, vy, radius, sides, entering, hp, max_hp, wrap_count, rotation, spin] = fields::<12>("asteroid", value)?;
+    Ok(Asteroid {
+        vel: vec2(vx, vy),
+        entering: flag(entering),
+        hp: hp as u8,
+        max_hp: max_hp as u8,
+        wrap_count: wrap_count as u32,
+        rotation,
+        spin,
+        ..Asteroid::new(vec2(x, y), radius, sides as u8)
+    })
+}
+
+fn parse_ufo(value: &str) -> Result<Ufo, String> {
+    let [x, y, vx, vy, radius, shoot_timer, change_dir_timer] = fields::<7>("ufo", value)?;
+    Ok(Ufo { pos: vec2(x, y), prev_pos: vec2(x, y), vel: vec2(vx, vy), radius, active: true, shoot_timer, change_dir_timer })
+}
+
+fn parse_boss(value: &str) -> Result<Boss, String> {
+    let [x, y, vx, vy, radius, hp, max_hp, shoot_timer, firing, phase_timer] = fields::<10>("boss", value)?;
+    Ok(Boss {
+        pos: vec2(x, y),
+        prev_pos: vec2(x, y),
+        vel: vec2(vx, vy),
+        radius,
+        active: true,
+        hp: hp as u32,
+        max_hp: max_hp as u32,
+        shoot_timer,
+        phase: if flag(firing) { BossPhase::Firing } else { BossPhase::Sweeping },
+        phase_timer,
+    })
+}
+
+/// Bullet owner: `ufo`, or `p<index>` for a ship.
+fn parse_bullet(value: &str) -> Result<Bullet, String> {
+    let (numbers, owner) = value.rsplit_once(',').ok_or_else(|| format!("invalid bullet '{}'", value))?;
+    let [x, y, vx, vy, lifetime] = fields::<5>("bullet", numbers)?;
+    let owner = match owner.trim() {
+        "ufo" => BulletOwner::Ufo,
+        other => other
+            .strip_prefix('p')
+            .and_then(|i| i.parse().ok())
+            .map(BulletOwner::Player)
+            .ok_or_else(|| format!("invalid bullet owner '{}'", other))?,
+    };
+    Ok(Bullet::new(vec2(x, y), vec2(vx, vy), lifetime, owner))
+}
+
+impl GameSnapshot {
+    pub fn from_str(input: &str) -> Result<Self, String> {
+        let mut version = None;
+        let mut snapshot = GameSnapshot {
+            score: 0,
+            lives: Vec::new(),
+            current_level_idx: 0,
+            endless_wave: None,
+            last_extra_life_score: 0,
+            players: Vec::new(),
+            asteroids: Vec::new(),
+            ufos: Vec::new(),
+            bosses: Vec::new(),
+            bullets: Vec::new(),
+        };
+        for line in input.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(|| format!("malformed line '{}'", line))?;
+            match key {
+                "version" => version = value.parse::<u32>().ok(),
+                "score" => snapshot.score = value.parse().map_err(|_| format!("invalid score '{}'", value))?,
+                "lives" => {
+                    snapshot.lives = value
+                        .split(',')
+                        .map(|v| v.trim().parse::<i32>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| format!("invalid lives '{}'", value))?
+                }
+                "level" => snapshot.current_level_idx = value.parse().map_err(|_| format!("invalid level '{}'", value))?,
+                "wave" => snapshot.endless_wave = Some(value.parse().map_err(|_| format!("invalid wave '{}'", value))?),
+                "last_extra_life" => {
+                    snapshot.last_extra_life_score = value.parse().map_err(|_| format!("invalid last_extra_life '{}'", value))?
+                }
+                "player" => snapshot.players.push(parse_player(value)?),
+                "asteroid" => snapshot.asteroids.push(parse_asteroid(value)?),
+                "ufo" => snapshot.ufos.push(parse_ufo(value)?),
+                "boss" => snapshot.bosses.push(parse_boss(value)?),
+                "bullet" => snapshot.bullets.push(parse_bullet(value)?),
+                _ => {}
+            }
+        }
+        if version != Some(SAVE_VERSION) {
+            return Err(format!("not a version {} save file", SAVE_VERSION));
+        }
+        if snapshot.players.is_empty() {
+            return Err("save has no ships".to_string());
+        }
+        Ok(snapshot)
+    }
+
+    pub fn load_from_file(path: &str) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|e| {
+            warn!("Save file {} unreadable: {}", path, e);
+            format!("Failed to read save: {}", e)
+        })?;
+        let snapshot = GameSnapshot::from_str(&contents)?;
+        info!("Game loaded from {} (level {}, score {})", path, snapshot.current_level_idx + 1, snapshot.score);
+        Ok(snapshot)
+    }
+
+    pub fn save_to_file(&self, path: &str) -> Result<(), String> {
+        if let Err(e) = fs::write(path, self.to_string()) {
+            error!("Failed to save game to {}: {}", path, e);
+            return Err(format!("Failed to write save: {}", e));
+        }
+        info!("Game saved to {}", path);
+        Ok(())
+    }
+}
+
+impl fmt::Display for GameSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# rust_asteroids save")?;
+        writeln!(f, "version={}", SAVE_VERSION)?;
+        writeln!(f, "score={}", self.score)?;
+        let lives: Vec<String> = self.lives.iter().map(|l| l.to_string()).collect();
+        writeln!(f, "lives={}", lives.join(","))?;
+        writeln!(f, "level={}", self.current_level_idx)?;
+        if let Some(wave) = self.endless_wave {
+            writeln!(f, "wave={}", wave)?;
+        }
+        writeln!(f, "last_extra_life={}", self.last_extra_life_score)?;
+        for p in &self.players {
+            writeln!(
+                f,
+                "player={},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                p.pos.x, p.pos.y, p.vel.x, p.vel.y, p.rotation, p.radius, p.active as u8, p.sides,
+                p.color.r, p.color.g, p.color.b, p.color.a,
+                p.invulnerable as u8, p.invulnerable_timer, p.stun_timer, p.fire_cooldown, p.hyperspace_cooldown,
+                p.fuel, p.max_fuel, p.fuel_locked as u8, p.shield_charges, p.bombs, p.pierce_timer,
+            )?;
+        }
+        for a in self.asteroids.iter().filter(|a| a.active) {
+            writeln!(
+                f,
+                "asteroid={},{},{},{},{},{},{},{},{},{},{},{}",
+                a.pos.x, a.pos.y, a.vel.x, a.vel.y, a.radius, a.sides, a.entering as u8, a.hp, a.max_hp, a.wrap_count,
+                a.rotation, a.spin,
+            )?;
+        }
+        for u in self.ufos.iter().filter(|u| u.active) {
+            writeln!(f, "ufo={},{},{},{},{},{},{}", u.pos.x, u.pos.y, u.vel.x, u.vel.y, u.radius, u.shoot_timer, u.change_dir_timer)?;
+        }
+        for b in self.bosses.iter().filter(|b| b.active) {
+            writeln!(
+                f,
+                "boss={},{},{},{},{},{},{},{},{},{}",
+                b.pos.x, b.pos.y, b.vel.x, b.vel.y, b.radius, b.hp, b.max_hp, b.shoot_timer,
+                (b.phase == BossPhase::Firing) as u8, b.phase_timer,
+            )?;
+        }
+        for b in self.bullets.iter().filter(|b| b.active) {
+            let owner = match b.owner {
+                BulletOwner::Player(i) => format!("p{}", i),
+                BulletOwner::Ufo => "ufo".to_string(),
+            };
+            writeln!(f, "bullet={},{},{},{},{},{}", b.pos.x, b.pos.y, b.vel.x, b.vel.y, b.lifetime, owner)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::rock;
+
+    /// A damaged, spinning rock that has wrapped a couple of times, so every saved field is set.
+    fn worn_rock(x: f32, radius: f32, hp: u8) -> Asteroid {
+        Asteroid {
+            hp,
+            max_hp: 3,
+            wrap_count: 2,
+            rotation: 12.5,
+            spin: -40.0,
+            sides: 7,
+            ..rock(vec2(x, 40.5), vec2(-1.25, 0.3), radius)
+        }
+    }
+
+    fn snapshot() -> GameSnapshot {
+        let ship = Player {
+            vel: vec2(0.5, -0.25),
+            rotation: 1.2345,
+            invulnerable: true,
+            invulnerable_timer: 1.5,
+            thrusting: true,
+            fire_cooldown: 0.1,
+            fuel: 2.75,
+            max_fuel: 4.0,
+            shield_charges: 1,
+            bombs: 3,
+            ..Player::new(vec2(400.0, 300.0))
+        };
+        GameSnapshot {
+            score: 4200,
+            lives: vec![2, 1],
+            current_level_idx: 3,
+            endless_wave: None,
+            last_extra_life_score: 0,
+            players: vec![ship],
+            asteroids: vec![worn_rock(10.0, 40.0, 3), worn_rock(220.0, 20.0, 1), worn_rock(612.125, 10.0, 1)],
+            ufos: vec![Ufo { pos: vec2(0.0, 100.0), prev_pos: vec2(0.0, 100.0), vel: vec2(2.0, 0.0), radius: 20.0, active: true, shoot_timer: 0.7, change_dir_timer: 1.1 }],
+            bosses: Vec::new(),
+            bullets: vec![
+                Bullet::new(vec2(1.0, 2.0), vec2(8.0, 0.0), 1.25, BulletOwner::Player(0)),
+                Bullet::new(vec2(3.0, 4.0), vec2(0.0, 6.0), 0.5, BulletOwner::Ufo),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let saved = snapshot();
+        let text = saved.to_string();
+        let loaded = GameSnapshot::from_str(&text).unwrap();
+        assert_eq!(loaded.to_string(), text);
+        assert_eq!(loaded.score, 4200);
+        assert_eq!(loaded.lives, [2, 1]);
+        assert_eq!(loaded.current_level_idx, 3);
+        assert_eq!(loaded.asteroids.len(), 3);
+        assert_eq!(loaded.asteroids[2].pos, vec2(612.125, 40.5));
+        assert_eq!(loaded.asteroids[0].hp, 3);
+        assert_eq!(loaded.asteroids[0].spin, -40.0);
+        assert_eq!(loaded.players[0].rotation, 1.2345);
+        assert_eq!(loaded.players[0].shield_charges, 1);
+        assert_eq!(loaded.players[0].bombs, 3);
+        assert_eq!(loaded.bullets[0].owner, BulletOwner::Player(0));
+        assert_eq!(loaded.bullets[1].owner, BulletOwner::Ufo);
+    }
+
+    #[test]
+    fn test_benched_coop_ship_stays_benched() {
+        let mut saved = snapshot();
+        let benched = Player {
+            active: false,
+            hyperspace_cooldown: 0.75,
+            pierce_timer: 4.5,
+            ..Player::new(vec2(100.0, 200.0))
+        };
+        saved.players.push(benched);
+        saved.lives = vec![2, 0];
+        let text = saved.to_string();
+        let loaded = GameSnapshot::from_str(&text).unwrap();
+        assert_eq!(loaded.to_string(), text);
+        assert!(loaded.players[0].active);
+        assert!(!loaded.players[1].active);
+        assert_eq!(loaded.players[1].hyperspace_cooldown, 0.75);
+        assert_eq!(loaded.players[1].pierce_timer, 4.5);
+        assert_eq!(loaded.lives, [2, 0]);
+    }
+
+    #[test]
+    fn test_snapshot_skips_inactive_entities() {
+        let mut saved = snapshot();
+        saved.asteroids[1].active = false;
+        saved.endless_wave = Some(4);
+        let loaded = GameSnapshot::from_str(&saved.to_string()).unwrap();
+        assert_eq!(loaded.asteroids.len(), 2);
+        assert_eq!(loaded.endless_wave, Some(4));
+    }
+
+    #[test]
+    fn test_snapshot_rejects_bad_files() {
+        assert!(GameSnapshot::from_str("score=10\n").is_err());
+        assert!(GameSnapshot::from_str("version=2\nscore=10\n").is_err());
+        // Version 1 ships lacked the activity flag and timers
+        let old = snapshot().to_string().replace("version=2", "version=1");
+        assert!(GameSnapshot::from_str(&old).is_err());
+        let broken = snapshot().to_string().replace("asteroid=10,", "asteroid=x,");
+        assert!(GameSnapshot::from_str(&broken).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+/// Seconds after a kill during which the next kill keeps the combo going.
+pub const COMBO_WINDOW: f32 = 1.5;
+/// Highest score multiplier a combo can reach.
+pub const MAX_MULTIPLIER: u32 = 5;
+
+/// Running score plus the combo multiplier for quick successive kills.
+///
+/// Each kill within `COMBO_WINDOW` of the previous one raises the multiplier by
+/// one (up to `MAX_MULTIPLIER`); letting the window run out drops it back to 1.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScoreState {
+    pub score: u32,
+    pub multiplier: u32,
+    /// Seconds left to land the next kill before the combo breaks
+    pub combo_timer: f32,
+}
+
+impl ScoreState {
+    pub fn new() -> Self {
+        Self { score: 0, multiplier: 1, combo_timer: 0.0 }
+    }
+
+    /// Starts from a saved score with no combo running.
+    pub fn with_score(score: u32) -> Self {
+        Self { score, ..Self::new() }
+    }
+
+    /// Scores a kill worth `base` points at the current multiplier and returns the points awarded.
+    pub fn add_kill(&mut self, base: u32) -> u32 {
+        if self.combo_timer > 0.0 {
+            self.multiplier = (self.multiplier + 1).min(MAX_MULTIPLIER);
+        }
+        self.combo_timer = COMBO_WINDOW;
+        let points = base * self.multiplier;
+        self.score += points;
+        points
+    }
+
+    /// Adds points that aren't kills (bonuses, mining chips); they neither use nor extend the combo.
+    pub fn add(&mut self, points: u32) {
+        self.score += points;
+    }
+
+    /// Runs down the combo window, resetting the multiplier once it expires.
+    pub fn update(&mut self, dt: f32) {
+        if self.combo_timer <= 0.0 {
+            return;
+        }
+        self.combo_timer -= dt;
+        if self.combo_timer <= 0.0 {
+            self.combo_timer = 0.0;
+            self.multiplier = 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quick_kills_ramp_the_multiplier() {
+        let mut state = ScoreState::new();
+        assert_eq!(state.add_kill(100), 100);
+        state.update(COMBO_WINDOW * 0.5);
+        assert_eq!(state.add_kill(100), 200);
+        state.update(COMBO_WINDOW * 0.5);
+        assert_eq!(state.add_kill(500), 1500);
+        assert_eq!(state.multiplier, 3);
+        assert_eq!(state.score, 1800);
+    }
+
+    #[test]
+    fn test_multiplier_caps() {
+        let mut state = ScoreState::new();
+        for _ in 0..(MAX_MULTIPLIER + 3) {
+            state.add_kill(100);
+        }
+        assert_eq!(state.multiplier, MAX_MULTIPLIER);
+        assert_eq!(state.add_kill(100), 100 * MAX_MULTIPLIER);
+    }
+
+    #[test]
+    fn test_combo_times_out() {
+        let mut state = ScoreState::new();
+        state.add_kill(100);
+        state.add_kill(100);
+        assert_eq!(state.multiplier, 2);
+        state.update(COMBO_WINDOW + 0.1);
+        assert_eq!(state.multiplier, 1);
+        assert_eq!(state.add_kill(100), 100);
+        // Bonus points don't touch the combo
+        state.add(50);
+        assert_eq!(state.multiplier, 1);
+        assert_eq!(state.score, 450);
+    }
+}
@@ -1,5 +1,24 @@
+use macroquad::input::KeyCode;
 use macroquad::logging::{error, info, warn};
+use std::fmt;
 use std::fs;
+use crate::components::SHIP_COLORS;
+use crate::input::ShipKeys;
+use crate::palette::{ColorScheme, Palette, MAX_BRIGHTNESS, MIN_BRIGHTNESS};
+use crate::resources::{SoundId, SOUND_NAMES};
+
+/// Settings file format version written by `Settings`'s `Display`.
+///
+/// Files without a `version` line predate versioning and are read as version 1.
+/// Version 2 added co-op lives, 3 the ship handling and 4 the designed ship. Each
+/// version only added keys, so an older file needs no migration: whatever it
+/// lacks keeps its default (the ship handling from `game.cfg`).
+pub const SETTINGS_VERSION: u32 = 4;
+
+/// Prefix (with format version) of settings share codes.
+const SHARE_CODE_PREFIX: &str = "RA1-";
+/// URL-safe base64 alphabet used by share codes (no padding).
+const SHARE_CODE_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Difficulty {
@@ -57,35 +76,735 @@ impl Difficulty {
             Difficulty::Hard => 1.3,
         }
     }
+
+    /// Multiplier on how fast thrusting burns fuel.
+    pub fn fuel_drain_multiplier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    /// Multiplier on how fast the overdrive meter fills.
+    pub fn overdrive_fill_rate(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.5,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 0.6,
+        }
+    }
+
+    /// Seconds between UFO shots (before enemy aggression is applied).
+    pub fn ufo_fire_interval(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 2.8,
+            Difficulty::Normal => 2.0,
+            Difficulty::Hard => 1.4,
+        }
+    }
+
+    /// Largest random error (radians) added to a UFO's aim.
+    pub fn ufo_aim_jitter(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.35,
+            Difficulty::Normal => 0.15,
+            Difficulty::Hard => 0.03,
+        }
+    }
+
+    /// Chance that a hyperspace jump misfires and destroys the ship.
+    pub fn hyperspace_misjump_chance(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.0,
+            Difficulty::Normal => 0.05,
+            Difficulty::Hard => 0.1,
+        }
+    }
+
+    /// Builds the full tuning set this preset stands for.
+    pub fn advanced(&self) -> AdvancedDifficulty {
+        let (enemy_aggression, player_lives, player_handling) = match self {
+            Difficulty::Easy => (0.8, 4, 1.15),
+            Difficulty::Normal => (1.0, 3, 1.0),
+            Difficulty::Hard => (1.25, 3, 1.0),
+        };
+        AdvancedDifficulty {
+            enemy_speed: self.speed_multiplier(),
+            enemy_aggression,
+            spawn_density: self.spawn_multiplier(),
+            player_lives,
+            player_handling,
+        }
+    }
+}
+
+/// How the ship is steered during play.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ControlMode {
+    /// Left/Right turn, Up thrusts.
+    Classic,
+    /// A single key (Right) turns continuously in one direction; Up thrusts.
+    OneButton,
+    /// The ship turns to face the mouse cursor; left click also fires.
+    Mouse,
+}
+
+impl ControlMode {
+    pub fn from_name(value: &str) -> Option<Self> {
+        match value {
+            "classic" => Some(ControlMode::Classic),
+            "one_button" => Some(ControlMode::OneButton),
+            "mouse" => Some(ControlMode::Mouse),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ControlMode::Classic => "classic",
+            ControlMode::OneButton => "one_button",
+            ControlMode::Mouse => "mouse",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            ControlMode::Classic => ControlMode::OneButton,
+            ControlMode::OneButton => ControlMode::Mouse,
+            ControlMode::Mouse => ControlMode::Classic,
+        }
+    }
+
+    /// Short description of the keys used in this scheme.
+    pub fn describe(&self, keys: &KeyBindings) -> String {
+        let (thrust, fire, warp, brake) =
+            (key_name(keys.thrust), key_name(keys.fire), key_name(keys.hyperspace), key_name(keys.brake));
+        match self {
+            ControlMode::Classic => format!(
+                "{}/{}: Turn | {}: Thrust | {}: Brake | {}: Fire | {}: Hyperspace",
+                key_name(keys.rotate_left), key_name(keys.rotate_right), thrust, brake, fire, warp
+            ),
+            ControlMode::OneButton => format!(
+                "{}: Turn (one way) | {}: Thrust | {}: Brake | {}: Fire | {}: Hyperspace",
+                key_name(keys.rotate_right), thrust, brake, fire, warp
+            ),
+            ControlMode::Mouse => format!(
+                "Mouse: Aim | Left Click/{}: Fire | {}: Thrust | {}: Brake | {}: Hyperspace",
+                fire, thrust, brake, warp
+            ),
+        }
+    }
+}
+
+/// How holding/pressing thrust changes the ship's velocity.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ThrustModel {
+    /// Velocity builds up every physics step while thrust is held.
+    Continuous,
+    /// Each key press gives a single, larger kick.
+    Impulse,
+}
+
+impl ThrustModel {
+    pub fn from_name(value: &str) -> Option<Self> {
+        match value {
+            "continuous" => Some(ThrustModel::Continuous),
+            "impulse" => Some(ThrustModel::Impulse),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThrustModel::Continuous => "continuous",
+            ThrustModel::Impulse => "impulse",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            ThrustModel::Continuous => ThrustModel::Impulse,
+            ThrustModel::Impulse => ThrustModel::Continuous,
+        }
+    }
+
+    /// Speed to add along the ship's heading this frame.
+    ///
+    /// `held`/`pressed` are the thrust key states and `steps` the physics steps run this frame.
+    pub fn thrust_delta(&self, held: bool, pressed: bool, thrust_power: f32, impulse_power: f32, steps: u32) -> f32 {
+        match self {
+            ThrustModel::Continuous if held => thrust_power * steps as f32,
+            ThrustModel::Impulse if pressed => impulse_power,
+            _ => 0.0,
+        }
+    }
+}
+
+/// What happens when the ship touches an asteroid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CollisionResponse {
+    /// The ship is destroyed and a life is lost.
+    Lethal,
+    /// The ship bounces off, losing speed and control briefly but no life.
+    Bounce,
+}
+
+impl CollisionResponse {
+    pub fn from_name(value: &str) -> Option<Self> {
+        match value {
+            "lethal" => Some(CollisionResponse::Lethal),
+            "bounce" => Some(CollisionResponse::Bounce),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CollisionResponse::Lethal => "lethal",
+            CollisionResponse::Bounce => "bounce",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            CollisionResponse::Lethal => CollisionResponse::Bounce,
+            CollisionResponse::Bounce => CollisionResponse::Lethal,
+        }
+    }
+}
+
+/// How a co-op team's lives are counted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CoopLives {
+    /// One pool for both ships; the run ends when it's empty.
+    Shared,
+    /// Each ship has its own lives and sits out once they're gone.
+    Separate,
+}
+
+impl CoopLives {
+    pub fn from_name(value: &str) -> Option<Self> {
+        match value {
+            "shared" => Some(CoopLives::Shared),
+            "separate" => Some(CoopLives::Separate),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CoopLives::Shared => "shared",
+            CoopLives::Separate => "separate",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            CoopLives::Shared => CoopLives::Separate,
+            CoopLives::Separate => CoopLives::Shared,
+        }
+    }
+}
+
+/// Independent enemy-side and player-side tuning.
+///
+/// The `Difficulty` presets are shortcuts that fill this in; players may then
+/// adjust individual values (e.g. fast enemies but forgiving controls).
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdvancedDifficulty {
+    /// Multiplier on asteroid and UFO speed.
+    pub enemy_speed: f32,
+    /// Multiplier on enemy fire rate (higher = shoots more often).
+    pub enemy_aggression: f32,
+    /// Multiplier on UFO spawn chance.
+    pub spawn_density: f32,
+    /// Lives at the start of a run.
+    pub player_lives: u32,
+    /// Multiplier on ship rotation speed and thrust.
+    pub player_handling: f32,
+}
+
+/// The tuning each `Difficulty` preset expands to (overridable from `game.cfg`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct DifficultyPresets {
+    pub easy: AdvancedDifficulty,
+    pub normal: AdvancedDifficulty,
+    pub hard: AdvancedDifficulty,
+}
+
+impl Default for DifficultyPresets {
+    fn default() -> Self {
+        DifficultyPresets {
+            easy: Difficulty::Easy.advanced(),
+            normal: Difficulty::Normal.advanced(),
+            hard: Difficulty::Hard.advanced(),
+        }
+    }
+}
+
+/// Starting values `game.cfg` can change: the difficulty presets and the ship's handling.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SettingsDefaults {
+    pub difficulty: DifficultyPresets,
+    pub ship: ShipTuning,
+}
+
+impl DifficultyPresets {
+    pub fn get(&self, difficulty: &Difficulty) -> &AdvancedDifficulty {
+        match difficulty {
+            Difficulty::Easy => &self.easy,
+            Difficulty::Normal => &self.normal,
+            Difficulty::Hard => &self.hard,
+        }
+    }
+}
+
+/// A category page of the settings screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SettingsTab {
+    General,
+    Difficulty,
+    Gameplay,
+    Controls,
+    Video,
+    Accessibility,
+}
+
+impl SettingsTab {
+    /// Every tab, in the order shown on the tab bar.
+    pub const ALL: [SettingsTab; 6] = [
+        SettingsTab::General,
+        SettingsTab::Difficulty,
+        SettingsTab::Gameplay,
+        SettingsTab::Controls,
+        SettingsTab::Video,
+        SettingsTab::Accessibility,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SettingsTab::General => "General",
+            SettingsTab::Difficulty => "Difficulty",
+            SettingsTab::Gameplay => "Gameplay",
+            SettingsTab::Controls => "Controls",
+            SettingsTab::Video => "Video",
+            SettingsTab::Accessibility => "Accessibility",
+        }
+    }
+
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|t| t == self).unwrap_or(0)
+    }
+
+    pub fn next(&self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    pub fn prev(&self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// An adjustable field of `AdvancedDifficulty` on the settings screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AdvancedField {
+    EnemySpeed,
+    EnemyAggression,
+    SpawnDensity,
+    PlayerLives,
+    PlayerHandling,
+}
+
+impl AdvancedField {
+    pub fn next(&self) -> Self {
+        match self {
+            AdvancedField::EnemySpeed => AdvancedField::EnemyAggression,
+            AdvancedField::EnemyAggression => AdvancedField::SpawnDensity,
+            AdvancedField::SpawnDensity => AdvancedField::PlayerLives,
+            AdvancedField::PlayerLives => AdvancedField::PlayerHandling,
+            AdvancedField::PlayerHandling => AdvancedField::EnemySpeed,
+        }
+    }
+}
+
+impl AdvancedDifficulty {
+    /// Nudges one field up (`steps > 0`) or down, keeping it within sane bounds.
+    pub fn adjust(&mut self, field: AdvancedField, steps: i32) {
+        let delta = steps as f32 * 0.05;
+        match field {
+            AdvancedField::EnemySpeed => self.enemy_speed += delta,
+            AdvancedField::EnemyAggression => self.enemy_aggression += delta,
+            AdvancedField::SpawnDensity => self.spawn_density += delta,
+            AdvancedField::PlayerLives => self.player_lives = (self.player_lives as i32 + steps).max(0) as u32,
+            AdvancedField::PlayerHandling => self.player_handling += delta,
+        }
+        self.clamp();
+    }
+
+    pub fn clamp(&mut self) {
+        self.enemy_speed = self.enemy_speed.clamp(0.25, 3.0);
+        self.enemy_aggression = self.enemy_aggression.clamp(0.25, 3.0);
+        self.spawn_density = self.spawn_density.clamp(0.0, 3.0);
+        self.player_lives = self.player_lives.clamp(1, 9);
+        self.player_handling = self.player_handling.clamp(0.5, 2.0);
+    }
+}
+
+/// Slowest and fastest turn rate the ship can be tuned to, in radians per second.
+pub const ROTATION_SPEED_RANGE: (f32, f32) = (1.0, 8.0);
+/// Weakest and strongest thrust, in velocity added per physics step.
+pub const THRUST_POWER_RANGE: (f32, f32) = (0.05, 0.4);
+/// Most and least drag: velocity kept per physics step (1.0 = no friction; never above,
+/// or the ship would speed up on its own).
+pub const FRICTION_RANGE: (f32, f32) = (0.9, 1.0);
+
+/// Fewest and most sides a designed ship can have.
+pub const SHIP_SIDES_RANGE: (u8, u8) = (3, 8);
+/// Smallest and largest designed ship radius, in pixels.
+pub const SHIP_RADIUS_RANGE: (f32, f32) = (5.0, 50.0);
+
+/// The ship's handling, adjustable for feel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShipTuning {
+    /// Turn rate in radians per second.
+    pub rotation_speed: f32,
+    /// Velocity added per physics step while thrusting.
+    pub thrust_power: f32,
+    /// Velocity kept per physics step, in (0, 1].
+    pub friction: f32,
+}
+
+impl Default for ShipTuning {
+    fn default() -> Self {
+        ShipTuning { rotation_speed: 4.0, thrust_power: 0.15, friction: 0.98 }
+    }
+}
+
+/// Which ship tuning value the settings screen is adjusting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShipField {
+    RotationSpeed,
+    ThrustPower,
+    Friction,
+}
+
+impl ShipField {
+    pub fn next(&self) -> Self {
+        match self {
+            ShipField::RotationSpeed => ShipField::ThrustPower,
+            ShipField::ThrustPower => ShipField::Friction,
+            ShipField::Friction => ShipField::RotationSpeed,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ShipField::RotationSpeed => "Turn Rate",
+            ShipField::ThrustPower => "Thrust",
+            ShipField::Friction => "Friction",
+        }
+    }
+}
+
+impl ShipTuning {
+    /// Nudges one value up (`steps > 0`) or down, keeping it within its range.
+    pub fn adjust(&mut self, field: ShipField, steps: i32) {
+        let steps = steps as f32;
+        match field {
+            ShipField::RotationSpeed => self.rotation_speed += steps * 0.25,
+            ShipField::ThrustPower => self.thrust_power += steps * 0.01,
+            ShipField::Friction => self.friction += steps * 0.005,
+        }
+        self.clamp();
+    }
+
+    /// Pulls every value into its range; non-numbers go back to the default.
+    pub fn clamp(&mut self) {
+        let defaults = ShipTuning::default();
+        for (value, default, (low, high)) in [
+            (&mut self.rotation_speed, defaults.rotation_speed, ROTATION_SPEED_RANGE),
+            (&mut self.thrust_power, defaults.thrust_power, THRUST_POWER_RANGE),
+            (&mut self.friction, defaults.friction, FRICTION_RANGE),
+        ] {
+            *value = if value.is_finite() { value.clamp(low, high) } else { default };
+        }
+    }
+}
+
+/// Keys that can be bound to an action, named in the settings file as in `KeyCode` (e.g. `Up`, `W`, `LeftShift`).
+const BINDABLE_KEYS: &[KeyCode] = &[
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G,
+    KeyCode::H, KeyCode::I, KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N,
+    KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R, KeyCode::S, KeyCode::T, KeyCode::U,
+    KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+    KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right,
+    KeyCode::Space, KeyCode::Enter, KeyCode::Tab, KeyCode::Backspace,
+    KeyCode::LeftShift, KeyCode::RightShift, KeyCode::LeftControl, KeyCode::RightControl,
+    KeyCode::LeftAlt, KeyCode::RightAlt,
+    KeyCode::Comma, KeyCode::Period, KeyCode::Slash, KeyCode::Semicolon, KeyCode::Apostrophe,
+    KeyCode::LeftBracket, KeyCode::RightBracket, KeyCode::Minus, KeyCode::Equal,
+];
+
+/// Settings-file name of a key.
+pub fn key_name(key: KeyCode) -> String {
+    format!("{:?}", key)
+}
+
+/// Looks up a bindable key by its settings-file name.
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    BINDABLE_KEYS.iter().copied().find(|key| key_name(*key) == name)
+}
+
+/// Parses one binding, keeping `current` (with a warning) if the key name is unknown.
+fn parse_key_binding(setting: &str, value: &str, current: KeyCode) -> KeyCode {
+    key_from_name(value).unwrap_or_else(|| {
+        warn!("Unknown key '{}' for {}; keeping {}", value, setting, key_name(current));
+        current
+    })
+}
+
+/// Keyboard layout for the in-game actions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KeyBindings {
+    pub thrust: KeyCode,
+    pub rotate_left: KeyCode,
+    pub rotate_right: KeyCode,
+    pub fire: KeyCode,
+    pub hyperspace: KeyCode,
+    pub brake: KeyCode,
+    pub bomb: KeyCode,
+    pub pause: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            thrust: KeyCode::Up,
+            rotate_left: KeyCode::Left,
+            rotate_right: KeyCode::Right,
+            fire: KeyCode::Space,
+            hyperspace: KeyCode::LeftShift,
+            brake: KeyCode::Down,
+            bomb: KeyCode::X,
+            pause: KeyCode::P,
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Player one's ship controls.
+    pub fn ship(&self) -> ShipKeys {
+        ShipKeys {
+            left: self.rotate_left,
+            right: self.rotate_right,
+            thrust: self.thrust,
+            fire: self.fire,
+            hyperspace: self.hyperspace,
+            brake: self.brake,
+            bomb: self.bomb,
+        }
+    }
+
+    /// Whether `key` is bound to an action; fixed gameplay hotkeys on the same key stand down.
+    pub fn uses(&self, key: KeyCode) -> bool {
+        [self.thrust, self.rotate_left, self.rotate_right, self.fire, self.hyperspace, self.brake, self.bomb, self.pause].contains(&key)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Settings {
     pub volume: f32,
+    /// Silence all sound while keeping `volume` for when it's turned back on.
+    pub muted: bool,
+    /// Level of each sound effect relative to `volume`, in `SOUND_NAMES` order.
+    pub sound_volumes: [f32; SoundId::COUNT],
     pub difficulty: Difficulty,
+    /// Fine-grained tuning, initialised from `difficulty`.
+    pub advanced: AdvancedDifficulty,
     pub show_fps: bool,
+    /// Fast asteroid-asteroid collisions shatter the smaller rock.
+    pub asteroid_impacts: bool,
+    /// Show a corner log of recent kills and their points.
+    pub show_kill_feed: bool,
+    /// Allow the 'S' background scan bonus during play.
+    pub enable_scanner: bool,
+    /// Steering scheme.
+    pub control_mode: ControlMode,
+    /// Hide the OS cursor (a crosshair is drawn instead) while playing in mouse mode.
+    pub hide_cursor: bool,
+    /// Skip dramatic slow-motion and screen effects.
+    pub reduced_motion: bool,
+    /// Asteroids fly in from the screen edges at level start instead of appearing in place.
+    pub edge_entry: bool,
+    /// Derive asteroid shapes from a fixed seed so every player sees the same field.
+    pub fixed_shapes: bool,
+    /// Mutator: magnetic storms occasionally scramble the HUD and knock out the scanner.
+    pub magnetic_storms: bool,
+    /// Ask "Quit? Y/N" before Escape exits the game.
+    pub confirm_quit: bool,
+    /// Holding fire keeps shooting at the weapon's cadence.
+    pub auto_fire: bool,
+    /// Flash an arrow at the screen edge where a UFO enters.
+    pub ufo_alerts: bool,
+    /// Continuous acceleration or per-press impulse thrust.
+    pub thrust_model: ThrustModel,
+    /// Full color or a monochrome CRT phosphor look.
+    pub color_scheme: ColorScheme,
+    /// Playfield colors, including colorblind-friendly sets.
+    pub palette: Palette,
+    /// Soft frame-rate cap to save power (0 = uncapped).
+    pub target_fps: u32,
+    /// Beginner assist: highlight asteroids in the line of fire.
+    pub aim_assist: bool,
+    /// Ship-asteroid contact: lose a life (default) or bounce off.
+    pub collision_response: CollisionResponse,
+    /// Rubber-band the UFO spawn chance to how well the player is doing (see `AdaptiveDirector`).
+    pub adaptive_spawning: bool,
+    /// Corner radar plotting asteroids and UFOs around player one.
+    pub show_radar: bool,
+    /// Display brightness multiplier (0.5-1.5, 1.0 = unchanged).
+    pub brightness: f32,
+    /// Co-op: players' shots can hit each other.
+    pub friendly_fire: bool,
+    /// Co-op: one lives pool for the team or one per ship (applies from the next run).
+    pub coop_lives: CoopLives,
+    /// Turn rate, thrust and friction.
+    pub ship: ShipTuning,
+    /// Hull sides chosen in design mode.
+    pub ship_sides: u8,
+    /// Hull radius chosen in design mode.
+    pub ship_radius: f32,
+    /// Hull color chosen in design mode (index into `SHIP_COLORS`).
+    pub ship_color: usize,
+    /// Player one's key bindings.
+    pub keys: KeyBindings,
+    /// Fixed seed for level layouts and particles, so runs can be replayed (random when unset).
+    pub seed: Option<u64>,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Settings {
             volume: 0.8,
+            muted: false,
+            sound_volumes: [1.0; SoundId::COUNT],
             difficulty: Difficulty::Normal,
+            advanced: Difficulty::Normal.advanced(),
             show_fps: false,
+            asteroid_impacts: false,
+            show_kill_feed: false,
+            enable_scanner: true,
+            control_mode: ControlMode::Classic,
+            hide_cursor: true,
+            reduced_motion: false,
+            edge_entry: false,
+            fixed_shapes: false,
+            magnetic_storms: false,
+            confirm_quit: true,
+            auto_fire: false,
+            ufo_alerts: true,
+            thrust_model: ThrustModel::Continuous,
+            color_scheme: ColorScheme::Standard,
+            palette: Palette::Default,
+            target_fps: 0,
+            aim_assist: false,
+            collision_response: CollisionResponse::Lethal,
+            adaptive_spawning: false,
+            show_radar: false,
+            brightness: 1.0,
+            friendly_fire: false,
+            coop_lives: CoopLives::Shared,
+            ship: ShipTuning::default(),
+            ship_sides: 3,
+            ship_radius: 15.0,
+            ship_color: 0,
+            keys: KeyBindings::default(),
+            seed: None,
         }
     }
 }
 
 impl Settings {
     pub fn clamp(&mut self) {
+        // NaN survives `f32::clamp`, so non-finite values go back to the default instead
+        let defaults = Settings::default();
+        if !self.volume.is_finite() {
+            warn!("Volume is not a number; resetting to {}", defaults.volume);
+            self.volume = defaults.volume;
+        }
+        for (name, level) in SOUND_NAMES.iter().zip(self.sound_volumes.iter_mut()) {
+            if !level.is_finite() {
+                warn!("{} volume is not a number; resetting to 1.0", name);
+                *level = 1.0;
+            }
+        }
+        if !self.brightness.is_finite() {
+            warn!("Brightness is not a number; resetting to {}", defaults.brightness);
+            self.brightness = defaults.brightness;
+        }
         if !(0.0..=1.0).contains(&self.volume) {
             warn!("Volume out of range; clamping to [0.0, 1.0]");
             self.volume = self.volume.clamp(0.0, 1.0);
         }
+        for (name, level) in SOUND_NAMES.iter().zip(self.sound_volumes.iter_mut()) {
+            if !(0.0..=1.0).contains(level) {
+                warn!("{} volume out of range; clamping to [0.0, 1.0]", name);
+                *level = level.clamp(0.0, 1.0);
+            }
+        }
+        if !(MIN_BRIGHTNESS..=MAX_BRIGHTNESS).contains(&self.brightness) {
+            warn!("Brightness out of range; clamping to [{}, {}]", MIN_BRIGHTNESS, MAX_BRIGHTNESS);
+            self.brightness = self.brightness.clamp(MIN_BRIGHTNESS, MAX_BRIGHTNESS);
+        }
+        self.advanced.clamp();
+        self.ship.clamp();
+        self.ship_sides = self.ship_sides.clamp(SHIP_SIDES_RANGE.0, SHIP_SIDES_RANGE.1);
+        self.ship_radius = if self.ship_radius.is_finite() {
+            self.ship_radius.clamp(SHIP_RADIUS_RANGE.0, SHIP_RADIUS_RANGE.1)
+        } else {
+            Settings::default().ship_radius
+        };
+        if self.ship_color >= SHIP_COLORS.len() {
+            self.ship_color = 0;
+        }
+    }
+
+    /// Whether player one steers with the mouse (ship faces the cursor, left-click fires)
+    /// instead of the rotate keys.
+    pub fn mouse_aim(&self) -> bool {
+        self.control_mode == ControlMode::Mouse
+    }
+
+    /// Switches to a difficulty preset, resetting the advanced tuning to match.
+    pub fn set_difficulty(&mut self, difficulty: Difficulty, presets: &DifficultyPresets) {
+        self.advanced = presets.get(&difficulty).clone();
+        self.difficulty = difficulty;
+    }
+
+    /// Fresh settings that start from `game.cfg`'s defaults rather than the built-in ones.
+    pub fn with_defaults(defaults: &SettingsDefaults) -> Self {
+        let mut settings = Settings { ship: defaults.ship, ..Settings::default() };
+        settings.advanced = defaults.difficulty.get(&settings.difficulty).clone();
+        settings
     }
 
     pub fn from_str(input: &str) -> Self {
-        let mut settings = Settings::default();
+        Settings::from_str_with_defaults(input, &SettingsDefaults::default())
+    }
+
+    /// Parses settings; anything the file leaves out comes from `defaults`.
+    pub fn from_str_with_defaults(input: &str, defaults: &SettingsDefaults) -> Self {
+        let mut settings = Settings::with_defaults(defaults);
+        let mut version = 1;
+        // Advanced overrides are applied on top of the preset once it is known
+        let mut enemy_speed = None;
+        let mut enemy_aggression = None;
+        let mut spawn_density = None;
+        let mut player_lives = None;
+        let mut player_handling = None;
 
         for line in input.lines() {
             let line = line.trim();
@@ -100,41 +819,219 @@ impl Settings {
             let value = parts[1].trim();
 
             match key {
+                "version" => match value.parse::<u32>() {
+                    Ok(v) => version = v,
+                    Err(_) => warn!("Unknown settings version '{}'; reading as version {}", value, version),
+                },
                 "volume" => {
                     if let Ok(v) = value.parse::<f32>() {
                         settings.volume = v;
                     }
                 }
-                "difficulty" => {
-                    if let Ok(v) = value.parse::<u8>() {
-                        settings.difficulty = Difficulty::from_u8(v);
+                _ if key.starts_with("volume_") => {
+                    let index = key.strip_prefix("volume_").and_then(|name| SOUND_NAMES.iter().position(|n| *n == name));
+                    if let (Some(i), Ok(v)) = (index, value.parse::<f32>()) {
+                        settings.sound_volumes[i] = v;
                     }
                 }
+                "muted" => {
+                    settings.muted = matches!(value, "1" | "true" | "True" | "TRUE");
+                }
+                "difficulty" => match value.parse::<u8>() {
+                    Ok(v @ 1..=3) => settings.difficulty = Difficulty::from_u8(v),
+                    _ => warn!("Unknown difficulty '{}'; keeping {:?}", value, settings.difficulty),
+                },
+                "enemy_speed" => enemy_speed = value.parse::<f32>().ok(),
+                "enemy_aggression" => enemy_aggression = value.parse::<f32>().ok(),
+                "spawn_density" => spawn_density = value.parse::<f32>().ok(),
+                "player_lives" => player_lives = value.parse::<u32>().ok(),
+                "player_handling" => player_handling = value.parse::<f32>().ok(),
                 "show_fps" => {
                     settings.show_fps = matches!(value, "1" | "true" | "True" | "TRUE");
                 }
+                "asteroid_impacts" => {
+                    settings.asteroid_impacts = matches!(value, "1" | "true" | "True" | "TRUE");
+                }
+                "show_kill_feed" => {
+                    settings.show_kill_feed = matches!(value, "1" | "true" | "True" | "TRUE");
+                }
+                "enable_scanner" => {
+                    settings.enable_scanner = matches!(value, "1" | "true" | "True" | "TRUE");
+                }
+                "hide_cursor" => {
+                    settings.hide_cursor = matches!(value, "1" | "true" | "True" | "TRUE");
+                }
+                "reduced_motion" => {
+                    settings.reduced_motion = matches!(value, "1" | "true" | "True" | "TRUE");
+                }
+                "edge_entry" => {
+                    settings.edge_entry = matches!(value, "1" | "true" | "True" | "TRUE");
+                }
+                "fixed_shapes" => {
+                    settings.fixed_shapes = matches!(value, "1" | "true" | "True" | "TRUE");
+                }
+                "magnetic_storms" => {
+                    settings.magnetic_storms = matches!(value, "1" | "true" | "True" | "TRUE");
+                }
+                "confirm_quit" => {
+                    settings.confirm_quit = matches!(value, "1" | "true" | "True" | "TRUE");
+                }
+                "auto_fire" => {
+                    settings.auto_fire = matches!(value, "1" | "true" | "True" | "TRUE");
+                }
+                "friendly_fire" => {
+                    settings.friendly_fire = matches!(value, "1" | "true" | "True" | "TRUE");
+                }
+                "ship_rotation_speed" => {
+                    if let Ok(v) = value.parse::<f32>() {
+                        settings.ship.rotation_speed = v;
+                    }
+                }
+                "ship_thrust_power" => {
+                    if let Ok(v) = value.parse::<f32>() {
+                        settings.ship.thrust_power = v;
+                    }
+                }
+                "ship_sides" => {
+                    if let Ok(v) = value.parse::<u8>() {
+                        settings.ship_sides = v;
+                    }
+                }
+                "ship_radius" => {
+                    if let Ok(v) = value.parse::<f32>() {
+                        settings.ship_radius = v;
+                    }
+                }
+                "ship_color" => {
+                    if let Ok(v) = value.parse::<usize>() {
+                        settings.ship_color = v;
+                    }
+                }
+                "ship_friction" => {
+                    if let Ok(v) = value.parse::<f32>() {
+                        settings.ship.friction = v;
+                    }
+                }
+                "coop_lives" => {
+                    if let Some(mode) = CoopLives::from_name(value) {
+                        settings.coop_lives = mode;
+                    }
+                }
+                "ufo_alerts" => {
+                    settings.ufo_alerts = matches!(value, "1" | "true" | "True" | "TRUE");
+                }
+                "thrust_model" => {
+                    if let Some(model) = ThrustModel::from_name(value) {
+                        settings.thrust_model = model;
+                    }
+                }
+                "color_scheme" => {
+                    if let Some(scheme) = ColorScheme::from_name(value) {
+                        settings.color_scheme = scheme;
+                    }
+                }
+                "palette" => {
+                    if let Some(palette) = Palette::from_name(value) {
+                        settings.palette = palette;
+                    }
+                }
+                "target_fps" => {
+                    if let Ok(v) = value.parse::<u32>() {
+                        settings.target_fps = v;
+                    }
+                }
+                "aim_assist" => {
+                    settings.aim_assist = matches!(value, "1" | "true" | "True" | "TRUE");
+                }
+                "show_radar" => {
+                    settings.show_radar = matches!(value, "1" | "true" | "True" | "TRUE");
+                }
+                "brightness" => {
+                    if let Ok(v) = value.parse::<f32>() {
+                        settings.brightness = v;
+                    }
+                }
+                "seed" => match value.parse::<u64>() {
+                    Ok(seed) => settings.seed = Some(seed),
+                    Err(_) => warn!("Invalid seed '{}'; runs will use a random seed", value),
+                },
+                "collision_response" => {
+                    if let Some(response) = CollisionResponse::from_name(value) {
+                        settings.collision_response = response;
+                    }
+                }
+                "adaptive_spawning" => {
+                    settings.adaptive_spawning = matches!(value, "1" | "true" | "True" | "TRUE");
+                }
+                "key_thrust" => settings.keys.thrust = parse_key_binding(key, value, settings.keys.thrust),
+                "key_rotate_left" => settings.keys.rotate_left = parse_key_binding(key, value, settings.keys.rotate_left),
+                "key_rotate_right" => settings.keys.rotate_right = parse_key_binding(key, value, settings.keys.rotate_right),
+                "key_fire" => settings.keys.fire = parse_key_binding(key, value, settings.keys.fire),
+                "key_hyperspace" => settings.keys.hyperspace = parse_key_binding(key, value, settings.keys.hyperspace),
+                "key_brake" => settings.keys.brake = parse_key_binding(key, value, settings.keys.brake),
+                "key_bomb" => settings.keys.bomb = parse_key_binding(key, value, settings.keys.bomb),
+                "key_pause" => settings.keys.pause = parse_key_binding(key, value, settings.keys.pause),
+                "control_mode" => {
+                    if let Some(mode) = ControlMode::from_name(value) {
+                        settings.control_mode = mode;
+                    }
+                }
+                // Written alongside `control_mode`; a file with only this key still selects the Mouse scheme
+                "mouse_aim" => match value {
+                    "1" | "true" | "True" | "TRUE" => settings.control_mode = ControlMode::Mouse,
+                    "0" | "false" | "False" | "FALSE" if settings.control_mode == ControlMode::Mouse => settings.control_mode = ControlMode::Classic,
+                    _ => {}
+                },
                 _ => {}
             }
         }
 
+        settings.advanced = defaults.difficulty.get(&settings.difficulty).clone();
+        if let Some(v) = enemy_speed { settings.advanced.enemy_speed = v; }
+        if let Some(v) = enemy_aggression { settings.advanced.enemy_aggression = v; }
+        if let Some(v) = spawn_density { settings.advanced.spawn_density = v; }
+        if let Some(v) = player_lives { settings.advanced.player_lives = v; }
+        if let Some(v) = player_handling { settings.advanced.player_handling = v; }
+
+        if version > SETTINGS_VERSION {
+            warn!(
+                "Settings file version {} is newer than this build's ({}); unknown entries are ignored",
+                version, SETTINGS_VERSION
+            );
+        }
         settings.clamp();
         settings
     }
 
-    pub fn to_string(&self) -> String {
-        format!(
-            "volume={}\ndifficulty={}\nshow_fps={}\n",
-            self.volume,
-            self.difficulty.to_u8(),
-            if self.show_fps { 1 } else { 0 }
-        )
+    /// Encodes every setting into a compact, copy-pasteable code.
+    pub fn to_share_code(&self) -> String {
+        format!("{}{}", SHARE_CODE_PREFIX, encode_base64(self.to_string().as_bytes()))
+    }
+
+    /// Decodes a code produced by `to_share_code`, rejecting anything malformed.
+    pub fn from_share_code(code: &str) -> Result<Self, String> {
+        let payload = code
+            .trim()
+            .strip_prefix(SHARE_CODE_PREFIX)
+            .ok_or_else(|| format!("Share code must start with '{}'", SHARE_CODE_PREFIX))?;
+        let bytes = decode_base64(payload)?;
+        let text = String::from_utf8(bytes).map_err(|_| "Share code is corrupted (not text)".to_string())?;
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            if !line.contains('=') {
+                return Err(format!("Share code is corrupted (bad entry '{}')", line));
+            }
+        }
+        if !text.lines().any(|l| l.starts_with("volume=")) {
+            return Err("Share code is missing settings".to_string());
+        }
+        Ok(Settings::from_str(&text))
     }
 
-    pub fn load_from_file(path: &str) -> Result<Self, String> {
+    pub fn load_from_file(path: &str, defaults: &SettingsDefaults) -> Result<Self, String> {
         match fs::read_to_string(path) {
             Ok(contents) => {
                 info!("Settings loaded from {}", path);
-                Ok(Settings::from_str(&contents))
+                Ok(Settings::from_str_with_defaults(&contents, defaults))
             }
             Err(e) => {
                 warn!("Settings file not found or unreadable: {}. Using defaults.", e);
@@ -153,6 +1050,101 @@ impl Settings {
     }
 }
 
+impl fmt::Display for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "version={}\nvolume={}\n", SETTINGS_VERSION, self.volume)?;
+        for (name, level) in SOUND_NAMES.iter().zip(self.sound_volumes) {
+            writeln!(f, "volume_{}={}", name, level)?;
+        }
+        write!(
+            f,
+            "muted={}\ndifficulty={}\nenemy_speed={}\nenemy_aggression={}\nspawn_density={}\nplayer_lives={}\nplayer_handling={}\nshow_fps={}\nasteroid_impacts={}\nshow_kill_feed={}\nenable_scanner={}\ncontrol_mode={}\nmouse_aim={}\nhide_cursor={}\nreduced_motion={}\nedge_entry={}\nfixed_shapes={}\nmagnetic_storms={}\nconfirm_quit={}\nauto_fire={}\nufo_alerts={}\nthrust_model={}\ncolor_scheme={}\npalette={}\ntarget_fps={}\naim_assist={}\ncollision_response={}\nadaptive_spawning={}\nshow_radar={}\nbrightness={}\nfriendly_fire={}\ncoop_lives={}\nship_rotation_speed={}\nship_thrust_power={}\nship_friction={}\nship_sides={}\nship_radius={}\nship_color={}\nkey_thrust={}\nkey_rotate_left={}\nkey_rotate_right={}\nkey_fire={}\nkey_hyperspace={}\nkey_brake={}\nkey_bomb={}\nkey_pause={}\n",
+            if self.muted { 1 } else { 0 },
+            self.difficulty.to_u8(),
+            self.advanced.enemy_speed,
+            self.advanced.enemy_aggression,
+            self.advanced.spawn_density,
+            self.advanced.player_lives,
+            self.advanced.player_handling,
+            if self.show_fps { 1 } else { 0 },
+            if self.asteroid_impacts { 1 } else { 0 },
+            if self.show_kill_feed { 1 } else { 0 },
+            if self.enable_scanner { 1 } else { 0 },
+            self.control_mode.name(),
+            if self.mouse_aim() { 1 } else { 0 },
+            if self.hide_cursor { 1 } else { 0 },
+            if self.reduced_motion { 1 } else { 0 },
+            if self.edge_entry { 1 } else { 0 },
+            if self.fixed_shapes { 1 } else { 0 },
+            if self.magnetic_storms { 1 } else { 0 },
+            if self.confirm_quit { 1 } else { 0 },
+            if self.auto_fire { 1 } else { 0 },
+            if self.ufo_alerts { 1 } else { 0 },
+            self.thrust_model.name(),
+            self.color_scheme.name(),
+            self.palette.name(),
+            self.target_fps,
+            if self.aim_assist { 1 } else { 0 },
+            self.collision_response.name(),
+            if self.adaptive_spawning { 1 } else { 0 },
+            if self.show_radar { 1 } else { 0 },
+            self.brightness,
+            if self.friendly_fire { 1 } else { 0 },
+            self.coop_lives.name(),
+            self.ship.rotation_speed,
+            self.ship.thrust_power,
+            self.ship.friction,
+            self.ship_sides,
+            self.ship_radius,
+            self.ship_color,
+            key_name(self.keys.thrust),
+            key_name(self.keys.rotate_left),
+            key_name(self.keys.rotate_right),
+            key_name(self.keys.fire),
+            key_name(self.keys.hyperspace),
+            key_name(self.keys.brake),
+            key_name(self.keys.bomb),
+            key_name(self.keys.pause)
+        )?;
+        if let Some(seed) = self.seed {
+            writeln!(f, "seed={}", seed)?;
+        }
+        Ok(())
+    }
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |acc, (i, b)| acc | (*b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            out.push(SHARE_CODE_ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+        }
+    }
+    out
+}
+
+fn decode_base64(input: &str) -> Result<Vec<u8>, String> {
+    if input.len() % 4 == 1 {
+        return Err("Share code has an invalid length".to_string());
+    }
+    let mut out = Vec::new();
+    for chunk in input.as_bytes().chunks(4) {
+        let mut n = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let v = SHARE_CODE_ALPHABET
+                .iter()
+                .position(|a| a == c)
+                .ok_or_else(|| format!("Share code contains an invalid character '{}'", *c as char))?;
+            n |= (v as u32) << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            out.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+    Ok(out)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -171,10 +1163,247 @@ mod tests {
         assert!(s.show_fps);
     }
 
+    #[test]
+    fn test_settings_round_trip() {
+        let s = Settings {
+            muted: true,
+            sound_volumes: std::array::from_fn(|i| 0.25 + 0.125 * i as f32),
+            asteroid_impacts: true,
+            show_kill_feed: true,
+            enable_scanner: false,
+            control_mode: ControlMode::Mouse,
+            hide_cursor: false,
+            reduced_motion: true,
+            edge_entry: true,
+            fixed_shapes: true,
+            magnetic_storms: true,
+            confirm_quit: false,
+            auto_fire: true,
+            ufo_alerts: false,
+            thrust_model: ThrustModel::Impulse,
+            color_scheme: ColorScheme::AmberPhosphor,
+            palette: Palette::Deuteranopia,
+            target_fps: 60,
+            aim_assist: true,
+            collision_response: CollisionResponse::Bounce,
+            adaptive_spawning: true,
+            show_radar: true,
+            brightness: 1.3,
+            friendly_fire: true,
+            coop_lives: CoopLives::Separate,
+            ship: ShipTuning { rotation_speed: 5.5, thrust_power: 0.2, friction: 0.995 },
+            ship_sides: 6,
+            ship_radius: 22.0,
+            ship_color: 4,
+            keys: KeyBindings {
+                thrust: KeyCode::W,
+                rotate_left: KeyCode::A,
+                rotate_right: KeyCode::D,
+                fire: KeyCode::J,
+                hyperspace: KeyCode::RightShift,
+                brake: KeyCode::S,
+                bomb: KeyCode::Q,
+                pause: KeyCode::Key0,
+            },
+            seed: Some(1534),
+            ..Settings::default()
+        };
+        assert_eq!(Settings::from_str(&s.to_string()), s);
+    }
+
+    #[test]
+    fn test_unknown_key_binding_keeps_default() {
+        let s = Settings::from_str("key_thrust=W\nkey_fire=NotAKey\n");
+        assert_eq!(s.keys.thrust, KeyCode::W);
+        assert_eq!(s.keys.fire, KeyCode::Space);
+        assert!(s.to_string().contains("key_thrust=W\nkey_rotate_left=Left\n"));
+    }
+
+    #[test]
+    fn test_version_one_file_gets_new_fields_defaulted() {
+        let s = Settings::from_str("version=1\nvolume=0.5\nfriendly_fire=1\n");
+        assert_eq!(s.volume, 0.5);
+        assert!(s.friendly_fire);
+        assert_eq!(s.coop_lives, CoopLives::Shared);
+        // A value the file did set is kept
+        let s = Settings::from_str("version=1\ncoop_lives=separate\n");
+        assert_eq!(s.coop_lives, CoopLives::Separate);
+        assert!(Settings::default().to_string().starts_with(&format!("version={}\n", SETTINGS_VERSION)));
+    }
+
+    #[test]
+    fn test_full_version_one_file_loads() {
+        // Everything a version 1 build wrote; later keys come from the defaults
+        let v1 = "version=1\nvolume=0.6\nvolume_shoot=0.5\nvolume_bang=1\nvolume_warp=0.25\nmuted=0\ndifficulty=3\n\
+            enemy_speed=1.2\nenemy_aggression=1.3\nspawn_density=1.2\nplayer_lives=2\nplayer_handling=1\nshow_fps=1\n\
+            asteroid_impacts=0\nshow_kill_feed=1\nenable_scanner=1\ncontrol_mode=one_button\nhide_cursor=0\n\
+            reduced_motion=0\nedge_entry=1\nfixed_shapes=0\nmagnetic_storms=1\nconfirm_quit=1\nauto_fire=1\n\
+            ufo_alerts=1\nthrust_model=impulse\ncolor_scheme=amber\npalette=high_contrast\ntarget_fps=60\n\
+            aim_assist=0\ncollision_response=bounce\nshow_radar=1\nbrightness=1.2\nfriendly_fire=1\n\
+            key_thrust=W\nkey_rotate_left=A\nkey_rotate_right=D\nkey_fire=Space\nkey_hyperspace=LeftShift\n\
+            key_brake=S\nkey_bomb=X\nkey_pause=P\n";
+        let mut defaults = SettingsDefaults::default();
+        defaults.ship.rotation_speed = 5.0;
+        let s = Settings::from_str_with_defaults(v1, &defaults);
+        assert_eq!((s.volume, s.difficulty.clone(), s.advanced.player_lives), (0.6, Difficulty::Hard, 2));
+        assert_eq!(s.control_mode, ControlMode::OneButton);
+        assert_eq!(s.thrust_model, ThrustModel::Impulse);
+        assert_eq!((s.color_scheme, s.palette), (ColorScheme::AmberPhosphor, Palette::HighContrast));
+        assert_eq!(s.keys.thrust, KeyCode::W);
+        assert!(s.friendly_fire && s.auto_fire);
+        // Added in versions 2-4
+        assert_eq!(s.coop_lives, CoopLives::Shared);
+        assert_eq!(s.ship.rotation_speed, 5.0);
+        let design = Settings::default();
+        assert_eq!((s.ship_sides, s.ship_radius, s.ship_color), (design.ship_sides, design.ship_radius, design.ship_color));
+    }
+
+    #[test]
+    fn test_versionless_legacy_file_loads() {
+        let s = Settings::from_str("volume=0.49999988\ndifficulty=1\nshow_fps=1\n");
+        assert_eq!(s.volume, 0.49999988);
+        assert_eq!(s.difficulty, Difficulty::Easy);
+        assert!(s.show_fps);
+        assert_eq!(s.coop_lives, CoopLives::Shared);
+        assert_eq!(Settings::from_str("coop_lives=separate\n").coop_lives, CoopLives::Separate);
+    }
+
+    #[test]
+    fn test_future_version_loads_what_it_can() {
+        let s = Settings::from_str("version=99\nvolume=0.3\nwarp_drive=1\ncoop_lives=separate\n");
+        assert_eq!(s.volume, 0.3);
+        assert_eq!(s.coop_lives, CoopLives::Separate);
+    }
+
+    #[test]
+    fn test_ship_design_round_trip_and_clamp() {
+        let s = Settings::from_str("ship_sides=5\nship_radius=30\nship_color=2\n");
+        assert_eq!((s.ship_sides, s.ship_radius, s.ship_color), (5, 30.0, 2));
+        assert_eq!(Settings::from_str(&s.to_string()), s);
+        // Out of range: clamped to the nearest shape, unknown colors back to white
+        let s = Settings::from_str("ship_sides=12\nship_radius=2\nship_color=99\n");
+        assert_eq!((s.ship_sides, s.ship_radius, s.ship_color), (8, 5.0, 0));
+        let s = Settings::from_str("ship_sides=1\nship_radius=500\n");
+        assert_eq!((s.ship_sides, s.ship_radius), (3, 50.0));
+        assert_eq!(Settings::from_str("ship_radius=NaN\n").ship_radius, 15.0);
+    }
+
+    #[test]
+    fn test_ship_friction_stays_in_range() {
+        let s = Settings::from_str("version=3\nship_friction=1.5\nship_rotation_speed=NaN\n");
+        assert_eq!(s.ship.friction, 1.0);
+        assert_eq!(s.ship.rotation_speed, ShipTuning::default().rotation_speed);
+        assert_eq!(Settings::from_str("version=3\nship_friction=0\n").ship.friction, FRICTION_RANGE.0);
+        let mut ship = ShipTuning::default();
+        ship.adjust(ShipField::Friction, 100);
+        assert_eq!(ship.friction, 1.0);
+        ship.adjust(ShipField::Friction, -1);
+        assert!(ship.friction > 0.0 && ship.friction < 1.0);
+    }
+
+    #[test]
+    fn test_settings_tabs_cycle() {
+        let mut tab = SettingsTab::General;
+        for _ in 0..SettingsTab::ALL.len() {
+            assert_eq!(tab.next().prev(), tab);
+            tab = tab.next();
+        }
+        assert_eq!(tab, SettingsTab::General);
+        assert_eq!(SettingsTab::General.prev(), SettingsTab::Accessibility);
+    }
+
     #[test]
     fn test_settings_clamp() {
-        let s = Settings::from_str("volume=2.5\n");
+        let s = Settings::from_str("volume=2.5\nvolume_shoot=-1\nvolume_warp=0.4\n");
         assert_eq!(s.volume, 1.0);
+        assert_eq!(s.sound_volumes[SoundId::Shoot.index()], 0.0);
+        assert_eq!(s.sound_volumes[SoundId::Bang.index()], 1.0);
+        assert_eq!(s.sound_volumes[SoundId::Warp.index()], 0.4);
+    }
+
+    #[test]
+    fn test_settings_reject_non_finite_values() {
+        let s = Settings::from_str("volume=nan\nvolume_bang=NaN\nbrightness=nan\n");
+        assert_eq!(s.volume, 0.8);
+        assert_eq!(s.sound_volumes, [1.0; SoundId::COUNT]);
+        assert_eq!(s.brightness, 1.0);
+        let s = Settings::from_str("volume=inf\n");
+        assert!(s.volume.is_finite() && (0.0..=1.0).contains(&s.volume));
+        let s = Settings::from_str("volume=-inf\ndifficulty=banana\n");
+        assert!(s.volume.is_finite() && (0.0..=1.0).contains(&s.volume));
+        assert_eq!(s.difficulty, Difficulty::Normal);
+        assert_eq!(Settings::from_str("difficulty=42\n").difficulty, Difficulty::Normal);
+    }
+
+    #[test]
+    fn test_share_code_round_trip() {
+        let s = Settings {
+            volume: 0.35,
+            difficulty: Difficulty::Hard,
+            control_mode: ControlMode::OneButton,
+            show_fps: true,
+            ..Settings::default()
+        };
+        let code = s.to_share_code();
+        assert!(code.starts_with(SHARE_CODE_PREFIX));
+        assert_eq!(Settings::from_share_code(&code), Ok(s));
+    }
+
+    #[test]
+    fn test_share_code_rejects_malformed() {
+        assert!(Settings::from_share_code("hello").is_err());
+        assert!(Settings::from_share_code("RA1-!!!!").is_err());
+        assert!(Settings::from_share_code("RA1-A").is_err());
+        // Valid base64 but not a settings payload
+        let junk = format!("{}{}", SHARE_CODE_PREFIX, encode_base64(b"nonsense"));
+        assert!(Settings::from_share_code(&junk).is_err());
+    }
+
+    #[test]
+    fn test_base64_round_trip() {
+        for input in [&b""[..], b"a", b"ab", b"abc", b"abcd", b"volume=0.8\n"] {
+            assert_eq!(decode_base64(&encode_base64(input)).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn test_control_mode_parse() {
+        assert_eq!(Settings::from_str("control_mode=one_button\n").control_mode, ControlMode::OneButton);
+        assert_eq!(Settings::from_str("control_mode=joystick\n").control_mode, ControlMode::Classic);
+        assert_eq!(Settings::from_str("control_mode=mouse\n").control_mode, ControlMode::Mouse);
+        assert_eq!(ControlMode::OneButton.next(), ControlMode::Mouse);
+        assert_eq!(ControlMode::Mouse.next(), ControlMode::Classic);
+        assert!(Settings::from_str("control_mode=mouse\n").mouse_aim());
+        assert!(!Settings::from_str("control_mode=one_button\n").mouse_aim());
+        // The mouse_aim key still works on its own and never overrides the One-Button scheme
+        assert!(Settings::from_str("mouse_aim=1\n").mouse_aim());
+        assert!(!Settings::from_str("control_mode=mouse\nmouse_aim=0\n").mouse_aim());
+        assert_eq!(Settings::from_str("control_mode=one_button\nmouse_aim=0\n").control_mode, ControlMode::OneButton);
+        let mouse = Settings { control_mode: ControlMode::Mouse, ..Settings::default() };
+        assert!(mouse.to_string().contains("\nmouse_aim=1\n"));
+        assert_eq!(Settings::from_str(&mouse.to_string()).control_mode, ControlMode::Mouse);
+    }
+
+    #[test]
+    fn test_impulse_thrust_once_per_press() {
+        let model = ThrustModel::Impulse;
+        // Key pressed this frame, then held for two more frames
+        let total: f32 = [(true, true), (true, false), (true, false)]
+            .iter()
+            .map(|(held, pressed)| model.thrust_delta(*held, *pressed, 0.15, 2.5, 1))
+            .sum();
+        assert_eq!(total, 2.5);
+    }
+
+    #[test]
+    fn test_continuous_thrust_accumulates() {
+        let model = ThrustModel::Continuous;
+        let total: f32 = [(true, true), (true, false), (true, false)]
+            .iter()
+            .map(|(held, pressed)| model.thrust_delta(*held, *pressed, 0.15, 2.5, 1))
+            .sum();
+        assert!((total - 0.45).abs() < 1e-6);
+        assert_eq!(model.thrust_delta(false, false, 0.15, 2.5, 1), 0.0);
     }
 
     #[test]
@@ -187,4 +1416,77 @@ mod tests {
         assert_eq!(Difficulty::Normal.prev(), Difficulty::Easy);
         assert_eq!(Difficulty::Hard.prev(), Difficulty::Normal);
     }
+
+    #[test]
+    fn test_ufo_shooting_scales_with_difficulty() {
+        assert_eq!(Difficulty::Easy.ufo_fire_interval(), 2.8);
+        assert_eq!(Difficulty::Normal.ufo_fire_interval(), 2.0);
+        assert_eq!(Difficulty::Hard.ufo_fire_interval(), 1.4);
+        assert_eq!(Difficulty::Easy.ufo_aim_jitter(), 0.35);
+        assert_eq!(Difficulty::Normal.ufo_aim_jitter(), 0.15);
+        assert_eq!(Difficulty::Hard.ufo_aim_jitter(), 0.03);
+    }
+
+    #[test]
+    fn test_misjump_chance_grows_with_difficulty() {
+        assert_eq!(Difficulty::Easy.hyperspace_misjump_chance(), 0.0);
+        assert_eq!(Difficulty::Normal.hyperspace_misjump_chance(), 0.05);
+        assert_eq!(Difficulty::Hard.hyperspace_misjump_chance(), 0.1);
+    }
+
+    #[test]
+    fn test_presets_map_to_advanced() {
+        let easy = Difficulty::Easy.advanced();
+        assert_eq!(easy.enemy_speed, 0.85);
+        assert_eq!(easy.enemy_aggression, 0.8);
+        assert_eq!(easy.spawn_density, 0.7);
+        assert_eq!(easy.player_lives, 4);
+        assert_eq!(easy.player_handling, 1.15);
+
+        let normal = Difficulty::Normal.advanced();
+        assert_eq!(normal.enemy_speed, 1.0);
+        assert_eq!(normal.enemy_aggression, 1.0);
+        assert_eq!(normal.spawn_density, 1.0);
+        assert_eq!(normal.player_lives, 3);
+        assert_eq!(normal.player_handling, 1.0);
+
+        let hard = Difficulty::Hard.advanced();
+        assert_eq!(hard.enemy_speed, 1.2);
+        assert_eq!(hard.enemy_aggression, 1.25);
+        assert_eq!(hard.spawn_density, 1.3);
+        assert_eq!(hard.player_lives, 3);
+        assert_eq!(hard.player_handling, 1.0);
+    }
+
+    #[test]
+    fn test_advanced_overrides_preset() {
+        let s = Settings::from_str("difficulty=3\nplayer_handling=1.5\nplayer_lives=5\n");
+        assert_eq!(s.advanced.enemy_speed, 1.2);
+        assert_eq!(s.advanced.player_handling, 1.5);
+        assert_eq!(s.advanced.player_lives, 5);
+    }
+
+    #[test]
+    fn test_loaded_settings_fill_gaps_from_configured_defaults() {
+        let mut defaults = SettingsDefaults::default();
+        defaults.difficulty.hard.enemy_speed = 2.5;
+        defaults.difficulty.hard.player_lives = 2;
+        defaults.ship.rotation_speed = 5.0;
+        let s = Settings::from_str_with_defaults("difficulty=3\nplayer_lives=5\nship_friction=0.95\n", &defaults);
+        assert_eq!(s.advanced.enemy_speed, 2.5);
+        assert_eq!(s.ship.rotation_speed, 5.0);
+        // Values the file sets still win over the defaults
+        assert_eq!(s.advanced.player_lives, 5);
+        assert_eq!(s.ship.friction, 0.95);
+        assert_eq!(Settings::with_defaults(&defaults).ship.rotation_speed, 5.0);
+    }
+
+    #[test]
+    fn test_advanced_adjust_clamps() {
+        let mut adv = Difficulty::Normal.advanced();
+        adv.adjust(AdvancedField::PlayerLives, -10);
+        assert_eq!(adv.player_lives, 1);
+        adv.adjust(AdvancedField::PlayerHandling, 100);
+        assert_eq!(adv.player_handling, 2.0);
+    }
 }
@@ -0,0 +1,970 @@
+//! One frame of play: ship controls, fixed-step physics, enemy fire, collisions,
+//! scoring, cleanup, UFO arrivals and level progression.
+//!
+//! `GameWorld::step` reads no devices and draws nothing: controls come in as a
+//! `FrameInput`, sounds go out through an `AudioBackend` and every random roll comes
+//! from a seeded `GameRng`, so tests drive the same update the game runs.
+
+use macroquad::prelude::*;
+use crate::bomb::{detonate, BOMB_RADIUS, MAX_BOMBS};
+use crate::boss::{spread_directions, BossPhase, BOSS_SPREAD_ANGLE, BOSS_SPREAD_SHOTS};
+use crate::components::*;
+use crate::config::{next_level_idx, LevelConfig, PlayerTuning, ScoreTable};
+use crate::daily::seed_daily_level;
+use crate::director::AdaptiveDirector;
+use crate::events::{EventLog, GameEvent};
+use crate::input::Controls;
+use crate::killfeed::{asteroid_label, KillFeed};
+use crate::level::{split_asteroid, start_level, Campaign};
+use crate::lives::{hit_cost, lose_ship, LifeLoss, Lives};
+use crate::overdrive::Overdrive;
+use crate::palette::Role;
+use crate::particles::{explosion_preset, ParticleSpawnBridge, SpawnRequest};
+use crate::physics::{apply_brake, check_collision, classify_asteroid_impact, find_safe_spawn, resolve_asteroid_collision, safe_normalize, ship_hits_circle, update_fuel, AsteroidImpact};
+use crate::powerups::{absorb_hit, magnet_velocity, point_token, roll_bomb_drop, roll_pierce_drop, roll_shield_drop, PIERCE_DURATION, POINT_TOKEN_VALUE, POWERUP_DRIFT, SHIELD_GRACE};
+use crate::resources::{AudioBackend, SoundId};
+use crate::rng::{GameRng, ShapeSource};
+use crate::score::ScoreState;
+use crate::settings::{Settings, ThrustModel};
+use crate::sweep::{SweepTracker, SWEEP_BONUS};
+use crate::world::{bullet_hits_player, can_hyperspace, cancel_bullets, choose_ufo_target, fire_bullet, is_misjump, jitter_aim, nearest_player_pos, player_asteroid_collisions, shoot_asteroids, FixedStep, GameWorld, PlayerImpact, BOUNCE_STUN, FIXED_DT, HYPERSPACE_COOLDOWN, TARGET_FPS};
+
+/// Relative speed above which colliding asteroids shatter instead of bouncing.
+const ASTEROID_SHATTER_SPEED: f32 = 6.0;
+/// Upper bound on impact shatters per frame so a dense field can't cascade.
+const MAX_SHATTERS_PER_FRAME: usize = 2;
+/// Seconds of continuous-thrust fuel one impulse kick burns.
+const IMPULSE_FUEL_BURN: f32 = 0.25;
+/// Seconds between boss spreads while it is firing.
+const BOSS_FIRE_INTERVAL: f32 = 0.6;
+/// Open space (px) wanted around a ship's respawn point.
+pub const SAFE_SPAWN_CLEARANCE: f32 = 120.0;
+/// Pause between boss-rush encounters, in seconds (not counted towards the run time).
+const BOSS_RUSH_BREATHER: f32 = 2.0;
+
+/// One frame of controls, one entry per ship (missing entries are treated as idle).
+#[derive(Clone, Debug, Default)]
+pub struct FrameInput {
+    pub ships: Vec<Controls>,
+}
+
+/// The run state outside the world that a frame reads and updates.
+pub struct StepContext<'a> {
+    pub settings: &'a Settings,
+    pub tuning: &'a PlayerTuning,
+    pub scores: &'a ScoreTable,
+    /// Level being played, already scaled by the enemy speed and spawn density settings.
+    pub level: &'a LevelConfig,
+    /// Every configured level, for moving on once the field is cleared.
+    pub levels: &'a [LevelConfig],
+    pub campaign: &'a mut Campaign,
+    pub director: &'a mut AdaptiveDirector,
+    pub score: &'a mut ScoreState,
+    pub lives: &'a mut Lives,
+    pub overdrive: &'a mut Overdrive,
+    pub sweeps: &'a mut SweepTracker,
+    pub kill_feed: &'a mut KillFeed,
+    pub event_log: &'a mut EventLog,
+    pub particles: &'a ParticleSpawnBridge,
+    pub audio: &'a dyn AudioBackend,
+    pub shapes: &'a mut ShapeSource,
+    pub rng: &'a mut GameRng,
+    pub fixed: &'a mut FixedStep,
+    /// Practice run: hits respawn the ship but never cost a life.
+    pub sandbox: bool,
+    pub coop: bool,
+    /// Endless mode: procedural waves follow the last configured level.
+    pub endless: bool,
+    /// Daily challenge seed; each level reseeds `rng` from it.
+    pub daily: Option<u64>,
+    /// Bring on a UFO this frame whatever the odds (the `ufo` console command).
+    pub force_ufo: bool,
+    /// Seconds since the run began, for the event log and clean sweeps.
+    pub run_time: f64,
+    /// Frame time the controls turn by; bullet time slows the world but not the controls.
+    pub control_dt: f32,
+}
+
+/// What happened during one `GameWorld::step`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StepReport {
+    pub shots_fired: usize,
+    /// Asteroids broken by player shots.
+    pub asteroids_destroyed: usize,
+    /// The last life was lost.
+    pub game_over: bool,
+    /// Where a UFO entered this frame.
+    pub ufo_spawned: Option<Vec2>,
+    /// The field was cleared and the next level or wave loaded.
+    pub level_cleared: bool,
+    /// The field was cleared with no level left to play.
+    pub game_won: bool,
+}
+
+/// A lethal hit on ship `index`: a shield charge soaks it up (with `SHIELD_GRACE` of
+/// cover), otherwise the ship blows up and `lose_ship` takes `lives_lost` lives,
+/// respawning it at `respawn`. `sound` plays either way; `cause` finishes
+/// "Player 1 ..." in the log.
+///
+/// Returns true if the hit ended the game.
+fn damage_ship(player: &mut Player, index: usize, lives_lost: i32, respawn: Option<Vec2>, sound: SoundId, cause: &str, ctx: &mut StepContext) -> bool {
+    ctx.audio.play(sound);
+    if absorb_hit(&mut player.shield_charges) {
+        player.invulnerable = true;
+        player.invulnerable_timer = SHIELD_GRACE;
+        info!("Player {} shield absorbed a hit ({}). Charges left: {}", index + 1, cause, player.shield_charges);
+        return false;
+    }
+    ctx.event_log.push(ctx.run_time, GameEvent::PlayerHit { player: index });
+    ctx.particles.request(SpawnRequest {
+        pos: player.pos,
+        drift: player.vel * TARGET_FPS,
+        ..explosion_preset(player.radius, ctx.settings.palette.color(Role::Particle))
+    });
+    lose_ship(ctx.lives, index, hit_cost(ctx.sandbox, lives_lost), player, respawn, cause) == LifeLoss::GameOver
+}
+
+impl GameWorld {
+    /// Advances play by one rendered frame of `dt` seconds.
+    pub fn step(&mut self, input: &FrameInput, dt: f32, ctx: &mut StepContext) -> StepReport {
+        let mut report = StepReport::default();
+        let settings = ctx.settings;
+        let debris = settings.palette.color(Role::Particle);
+
+        for (index, player) in self.players.iter_mut().enumerate() {
+            // A ship whose own lives ran out sits out the rest of the run
+            if ctx.lives.is_out(index) {
+                player.active = false;
+            }
+            // Invulnerability countdown (used after respawn to avoid instant re-hit)
+            if player.invulnerable {
+                player.invulnerable_timer -= dt;
+                if player.invulnerable_timer <= 0.0 {
+                    player.invulnerable = false;
+                    info!("Invulnerability ended.");
+                }
+            }
+            player.stun_timer = (player.stun_timer - dt).max(0.0);
+            player.fire_cooldown = (player.fire_cooldown - dt).max(0.0);
+            player.pierce_timer = (player.pierce_timer - dt).max(0.0);
+            player.hyperspace_cooldown = (player.hyperspace_cooldown - dt).max(0.0);
+        }
+
+        let physics_steps = ctx.fixed.advance(dt);
+        let rotation_speed = settings.ship.rotation_speed * settings.advanced.player_handling;
+        let thrust_power = settings.ship.thrust_power * settings.advanced.player_handling;
+        let impulse_power = ctx.tuning.impulse_power * settings.advanced.player_handling;
+        let fuel_drain = ctx.tuning.fuel_drain * settings.difficulty.fuel_drain_multiplier();
+
+        for index in 0..self.players.len() {
+            let player = &mut self.players[index];
+            if !player.active { continue; }
+            let mut input = input.ships.get(index).copied().unwrap_or_default().disabled(player.stun_timer > 0.0);
+            if player.fuel_locked {
+                input.thrust_held = false;
+                input.thrust_pressed = false;
+            }
+            player.rotation += input.turn * rotation_speed * ctx.control_dt;
+
+            // Hyperspace (Shift by default, Y on a controller): lands in open space, but can misfire
+            if input.hyperspace && can_hyperspace(player.hyperspace_cooldown) {
+                player.hyperspace_cooldown = HYPERSPACE_COOLDOWN;
+                let landing = find_safe_spawn(&self.asteroids, &self.ufos, &self.bounds, SAFE_SPAWN_CLEARANCE);
+                if is_misjump(settings.difficulty.hyperspace_misjump_chance(), ctx.rng.gen_range_f32(0.0, 1.0)) {
+                    // The ship breaks up where it jumped from and comes back at the landing point
+                    report.game_over |= damage_ship(player, index, 1, Some(landing), SoundId::Misjump, "lost in a hyperspace misjump", ctx);
+                } else {
+                    ctx.audio.play(SoundId::Warp);
+                    info!("Player {} hyperspace jump to ({:.1}, {:.1})", index + 1, landing.x, landing.y);
+                }
+                player.pos = landing;
+                player.prev_pos = player.pos;
+                player.vel = vec2(0., 0.); // Reset velocity for safety
+            }
+
+            let thrust = settings.thrust_model.thrust_delta(
+                input.thrust_held,
+                input.thrust_pressed,
+                thrust_power,
+                impulse_power,
+                physics_steps,
+            );
+            // The flame follows the thrust actually applied: held for continuous, the kick frame for impulse
+            player.thrusting = match settings.thrust_model {
+                ThrustModel::Continuous => input.thrust_held,
+                ThrustModel::Impulse => thrust > 0.0,
+            };
+            if thrust > 0.0 {
+                let direction = vec2(player.rotation.cos(), player.rotation.sin());
+                player.vel += direction * thrust;
+            }
+            // Brake (Down by default, B on a controller): bleeds off speed but never reverses
+            if input.brake {
+                player.vel = apply_brake(player.vel, dt);
+            }
+
+            let burn = match settings.thrust_model {
+                ThrustModel::Continuous if player.thrusting => dt,
+                ThrustModel::Impulse if player.thrusting => IMPULSE_FUEL_BURN,
+                _ => 0.0,
+            };
+            let was_locked = player.fuel_locked;
+            (player.fuel, player.fuel_locked) =
+                update_fuel(player.fuel, player.max_fuel, player.fuel_locked, burn, fuel_drain, ctx.tuning.fuel_regen, dt);
+            if player.fuel_locked && !was_locked {
+                info!("Player {} out of fuel.", index + 1);
+            }
+
+            // Shoot: single press by default, or hold to fire at the weapon's cadence with auto-fire
+            if let Some(muzzle) = input.fire.then(|| fire_bullet(player, index, &mut self.bullets, input.fire_repeat)).flatten() {
+                ctx.audio.play(SoundId::Shoot);
+                report.shots_fired += 1;
+                info!("Player {} fired. Bullets active: {}", index + 1, self.bullets.len());
+                ctx.event_log.push(ctx.run_time, GameEvent::ShotFired { player: index });
+
+                ctx.particles.request(SpawnRequest {
+                    pos: muzzle,
+                    color: YELLOW,
+                    count: 8,
+                    speed: 80.0,
+                    life: 0.25,
+                    size: 2.0,
+                    ..Default::default()
+                });
+            }
+
+            // Bomb (X by default, left bumper on a controller): clears every asteroid and UFO around the ship
+            if input.bomb && player.active && player.bombs > 0 {
+                player.bombs -= 1;
+                let (center, bombs_left) = (player.pos, player.bombs);
+                ctx.audio.play(SoundId::Bang);
+                let blast = detonate(self, center);
+                ctx.event_log.push(ctx.run_time, GameEvent::BombDetonated { player: index });
+                let mut points = 0;
+                for (pos, radius) in &blast.asteroids {
+                    points += ctx.scores.asteroid_points(AsteroidSize::from_radius(*radius));
+                    ctx.event_log.push(ctx.run_time, GameEvent::AsteroidDestroyed { radius: *radius });
+                    ctx.particles.request(SpawnRequest { pos: *pos, ..explosion_preset(*radius, debris) });
+                }
+                for (pos, radius) in &blast.ufos {
+                    points += ctx.scores.ufo;
+                    ctx.event_log.push(ctx.run_time, GameEvent::UfoDestroyed);
+                    ctx.particles.request(SpawnRequest { pos: *pos, ..explosion_preset(*radius, debris) });
+                }
+                ctx.score.add(points);
+                ctx.kill_feed.push("Bomb", points);
+                // The ring's particles fly out to about the edge of the blast
+                ctx.particles.request(SpawnRequest {
+                    pos: center,
+                    color: ORANGE,
+                    count: 140,
+                    speed: BOMB_RADIUS / 0.6,
+                    life: 0.6,
+                    size: 2.5,
+                    ..Default::default()
+                });
+                info!(
+                    "Player {} bomb: {} asteroids, {} UFOs, +{} pts. Bombs left: {}",
+                    index + 1,
+                    blast.asteroids.len(),
+                    blast.ufos.len(),
+                    points,
+                    bombs_left
+                );
+            }
+        }
+
+        // Motion runs at a fixed rate; leftover frame time is used to interpolate the draw
+        self.zones.clone_from(&ctx.level.zones);
+        self.max_wraps = ctx.level.max_wraps;
+        self.gravity = ctx.level.gravity;
+        self.friction = settings.ship.friction;
+        // Pickup magnet: loose pickups drift towards a ship that comes close
+        for powerup in self.powerups.iter_mut() {
+            let target = nearest_player_pos(&self.players, powerup.pos);
+            powerup.vel = magnet_velocity(powerup.pos, powerup.vel, target, dt);
+        }
+        for _ in 0..physics_steps {
+            self.update_world(FIXED_DT);
+        }
+        ctx.score.update(dt);
+
+        // UFO Logic
+        for ufo in self.ufos.iter_mut() {
+            if ufo.change_dir_timer > 2.0 {
+                ufo.vel.y = ctx.rng.gen_range_f32(-1.0, 1.0) * ctx.level.ufo_speed;
+                ufo.change_dir_timer = 0.;
+            }
+
+            if ufo.shoot_timer > settings.difficulty.ufo_fire_interval() / settings.advanced.enemy_aggression {
+                ufo.shoot_timer = 0.;
+                // AI Aiming: respawning ships get a break until their invulnerability runs out
+                let Some(target) = choose_ufo_target(&self.players, ufo.pos, &self.asteroids, ctx.rng.gen_range_u32(0, 3)) else {
+                    continue;
+                };
+                ctx.audio.play(SoundId::Shoot);
+
+                // Easy UFOs spray their shots; Hard ones barely miss
+                let aim_dir = jitter_aim(safe_normalize(target - ufo.pos, vec2(0., 1.)), settings.difficulty.ufo_aim_jitter(), ctx.rng.gen_range_f32(-1.0, 1.0));
+                self.bullets.push(Bullet::new(ufo.pos + aim_dir * ufo.radius, aim_dir * 6.0, 2.0, BulletOwner::Ufo));
+            }
+        }
+
+        // Boss Logic: fire spreads at the nearest ship during the firing phase
+        for boss in self.bosses.iter_mut() {
+            if boss.phase == BossPhase::Firing && boss.shoot_timer > BOSS_FIRE_INTERVAL / settings.advanced.enemy_aggression {
+                boss.shoot_timer = 0.;
+                ctx.audio.play(SoundId::Shoot);
+                let aim_dir = safe_normalize(nearest_player_pos(&self.players, boss.pos) - boss.pos, vec2(0., 1.));
+                for dir in spread_directions(aim_dir, BOSS_SPREAD_SHOTS, BOSS_SPREAD_ANGLE) {
+                    self.bullets.push(Bullet::new(boss.pos + dir * boss.radius, dir * 6.0, 2.0, BulletOwner::Ufo));
+                }
+            }
+        }
+
+        // --- COLLISIONS ---
+        ctx.sweeps.expire(ctx.run_time);
+
+        let mut new_asteroids = Vec::new();
+
+        // Asteroid vs Asteroid (optional): slow contacts bounce, fast ones shatter the smaller rock
+        if settings.asteroid_impacts {
+            let mut shatters = 0;
+            for i in 0..self.asteroids.len() {
+                let (head, tail) = self.asteroids.split_at_mut(i + 1);
+                let a = &mut head[i];
+                for b in tail.iter_mut() {
+                    if !a.active || !b.active { continue; }
+                    if !check_collision(a.pos, a.radius, b.pos, b.radius) { continue; }
+
+                    let impact = classify_asteroid_impact(&a.body(), &b.body(), ASTEROID_SHATTER_SPEED);
+                    if impact == AsteroidImpact::Bounce || shatters >= MAX_SHATTERS_PER_FRAME {
+                        resolve_asteroid_collision(a, b);
+                        continue;
+                    }
+
+                    let victim = if impact == AsteroidImpact::ShatterFirst { &mut *a } else { &mut *b };
+                    victim.active = false;
+                    shatters += 1;
+                    new_asteroids.extend(split_asteroid(victim, ctx.shapes, ctx.rng));
+                    ctx.audio.play(SoundId::Bang);
+                    info!("Asteroid shattered by high-speed impact (radius {:.1}).", victim.radius);
+                    ctx.particles.request(SpawnRequest { pos: victim.pos, drift: victim.vel * TARGET_FPS, ..explosion_preset(victim.radius, debris) });
+                }
+            }
+        }
+
+        // Each ship loses at most one life per frame, however many things hit it
+        let mut player_hit_this_frame = vec![false; self.players.len()];
+        // Where a ship that dies this frame comes back: clear of rocks and UFOs
+        let safe_spawn = find_safe_spawn(&self.asteroids, &self.ufos, &self.bounds, SAFE_SPAWN_CLEARANCE);
+
+        // Player and UFO shots shoot each other down
+        for spark in cancel_bullets(&mut self.bullets) {
+            ctx.particles.request(SpawnRequest {
+                pos: spark,
+                color: WHITE,
+                count: 5,
+                speed: 60.0,
+                life: 0.2,
+                size: 1.5,
+                ..Default::default()
+            });
+        }
+
+        // Bullet vs Asteroid
+        for shot in shoot_asteroids(&mut self.bullets, &mut self.asteroids, ctx.shapes, ctx.rng) {
+            let asteroid = &self.asteroids[shot.asteroid];
+            let by_player = matches!(shot.owner, BulletOwner::Player(_));
+            if !shot.destroyed {
+                // Chipped but not broken: mining points (if any) for the player
+                if by_player {
+                    ctx.score.add(ctx.scores.asteroid_hit(asteroid.size(), asteroid.hp, asteroid.max_hp));
+                }
+                ctx.particles.request(SpawnRequest {
+                    pos: shot.bullet_pos,
+                    color: debris,
+                    count: 6,
+                    speed: 60.0,
+                    life: 0.4,
+                    size: 1.5,
+                    ..Default::default()
+                });
+                continue;
+            }
+            ctx.audio.play(SoundId::Bang);
+            ctx.event_log.push(ctx.run_time, GameEvent::AsteroidDestroyed { radius: asteroid.radius });
+
+            if by_player {
+                report.asteroids_destroyed += 1;
+                let points = ctx.score.add_kill(ctx.scores.asteroid_hit(asteroid.size(), 0, asteroid.max_hp));
+                ctx.overdrive.add_kill(settings.difficulty.overdrive_fill_rate());
+                ctx.kill_feed.push(asteroid_label(asteroid.radius), points);
+                info!("Asteroid destroyed. Score: {} (x{})", ctx.score.score, ctx.score.multiplier);
+                if asteroid.family.is_some_and(|family| ctx.sweeps.record_kill(family, ctx.run_time)) {
+                    ctx.score.add(SWEEP_BONUS);
+                    ctx.kill_feed.push("Clean Sweep", SWEEP_BONUS);
+                    ctx.audio.play(SoundId::Sweep);
+                    ctx.particles.request(SpawnRequest {
+                        pos: asteroid.pos,
+                        color: SKYBLUE,
+                        count: 40,
+                        speed: 160.0,
+                        life: 0.6,
+                        size: 2.0,
+                        ..Default::default()
+                    });
+                    info!("Clean sweep! +{} pts", SWEEP_BONUS);
+                }
+                ctx.particles.request(SpawnRequest { pos: asteroid.pos, drift: asteroid.vel * TARGET_FPS, ..explosion_preset(asteroid.radius, debris) });
+                let drift = vec2(ctx.rng.gen_range_f32(-POWERUP_DRIFT, POWERUP_DRIFT), ctx.rng.gen_range_f32(-POWERUP_DRIFT, POWERUP_DRIFT));
+                let drop = roll_shield_drop(asteroid.pos, drift, ctx.rng.gen_range_f32(0.0, 1.0))
+                    .or_else(|| roll_bomb_drop(asteroid.pos, drift, ctx.rng.gen_range_f32(0.0, 1.0)))
+                    .or_else(|| roll_pierce_drop(asteroid.pos, drift, ctx.rng.gen_range_f32(0.0, 1.0)));
+                if let Some(powerup) = drop {
+                    info!("{:?} power-up dropped at ({:.1}, {:.1})", powerup.kind, powerup.pos.x, powerup.pos.y);
+                    self.powerups.push(powerup);
+                }
+            }
+
+            // Split asteroid; a player's split is tracked for the clean-sweep bonus
+            let mut fragments = shot.fragments;
+            if by_player && !fragments.is_empty() {
+                let family = ctx.sweeps.register(fragments.len(), ctx.run_time);
+                for fragment in fragments.iter_mut() {
+                    fragment.family = Some(family);
+                }
+            }
+            new_asteroids.extend(fragments);
+        }
+
+        for bullet in self.bullets.iter_mut() {
+            if !bullet.active { continue; }
+
+            // Bullet vs UFO
+            if bullet.active && matches!(bullet.owner, BulletOwner::Player(_)) {
+                for ufo in self.ufos.iter_mut() {
+                    if !ufo.active { continue; }
+                    if check_collision(bullet.pos, 0., ufo.pos, ufo.radius) {
+                        ufo.active = false;
+                        bullet.active = false;
+                        ctx.audio.play(SoundId::Bang);
+                        let points = ctx.score.add_kill(ctx.scores.ufo);
+                        ctx.kill_feed.push("UFO", points);
+                        info!("UFO destroyed. Score: {} (x{})", ctx.score.score, ctx.score.multiplier);
+                        ctx.event_log.push(ctx.run_time, GameEvent::UfoDestroyed);
+                        ctx.particles.request(SpawnRequest { pos: ufo.pos, drift: ufo.vel * TARGET_FPS, ..explosion_preset(ufo.radius, debris) });
+                        self.powerups.push(point_token(ufo.pos, ufo.vel * 0.5));
+                    }
+                }
+            }
+
+            // Bullet vs Boss
+            if bullet.active && matches!(bullet.owner, BulletOwner::Player(_)) {
+                for boss in self.bosses.iter_mut() {
+                    if !boss.active { continue; }
+                    if check_collision(bullet.pos, 0., boss.pos, boss.radius) {
+                        bullet.active = false;
+                        boss.hp = boss.hp.saturating_sub(1);
+                        if boss.hp == 0 {
+                            boss.active = false;
+                            ctx.audio.play(SoundId::Bang);
+                            let points = ctx.score.add_kill(ctx.scores.boss);
+                            ctx.kill_feed.push("Boss", points);
+                            info!("Boss destroyed. Score: {} (x{})", ctx.score.score, ctx.score.multiplier);
+                            ctx.event_log.push(ctx.run_time, GameEvent::BossDestroyed);
+                            ctx.particles.request(SpawnRequest { pos: boss.pos, drift: boss.vel * TARGET_FPS, ..explosion_preset(boss.radius, debris) });
+                            // Finale: a wide white shock ring around the wreck
+                            ctx.particles.request(SpawnRequest {
+                                pos: boss.pos,
+                                color: WHITE,
+                                count: 120,
+                                speed: 300.0,
+                                life: 1.2,
+                                size: 2.5,
+                                ..Default::default()
+                            });
+                        } else {
+                            ctx.particles.request(SpawnRequest {
+                                pos: bullet.pos,
+                                color: MAGENTA,
+                                count: 6,
+                                speed: 80.0,
+                                life: 0.3,
+                                size: 2.0,
+                                ..Default::default()
+                            });
+                        }
+                        break;
+                    }
+                }
+            }
+
+            // Bullet vs Player (a teammate's shots only count with friendly fire)
+            for (index, player) in self.players.iter_mut().enumerate() {
+                if !bullet.active || !player.active || player.invulnerable || player_hit_this_frame[index] { continue; }
+                if !bullet_hits_player(bullet.owner, index, settings.friendly_fire) { continue; }
+                if !check_collision(bullet.pos, 0., player.pos, player.radius) { continue; }
+                player_hit_this_frame[index] = true;
+                bullet.active = false;
+                report.game_over |= damage_ship(player, index, 1, Some(safe_spawn), SoundId::Bang, "shot down", ctx);
+            }
+        }
+
+        // Physical Collisions (Ship vs Asteroid / UFO vs Asteroid / Ship vs UFO)
+        // Player vs Asteroid
+        let contacts = player_asteroid_collisions(
+            settings.collision_response,
+            &mut self.players,
+            &mut self.asteroids,
+            &mut player_hit_this_frame,
+        );
+        for contact in contacts {
+            let player = &mut self.players[contact.player];
+            let asteroid = &self.asteroids[contact.asteroid];
+            match contact.impact {
+                PlayerImpact::None => continue,
+                PlayerImpact::Bounced => {
+                    ctx.audio.play(SoundId::Bang);
+                    player.stun_timer = BOUNCE_STUN;
+                    ctx.particles.request(SpawnRequest {
+                        pos: player.pos,
+                        color: LIGHTGRAY,
+                        count: 10,
+                        speed: 90.0,
+                        life: 0.4,
+                        size: 2.0,
+                        ..Default::default()
+                    });
+                    info!("Player bounced off asteroid. Stunned for {:.1}s.", BOUNCE_STUN);
+                    continue;
+                }
+                PlayerImpact::Lethal => {}
+            }
+            report.game_over |= damage_ship(player, contact.player, contact.impact.lives_lost(), Some(safe_spawn), SoundId::Bang, "hit by an asteroid", ctx);
+            // Split implicitly if large, whether the shield took the hit or not
+            new_asteroids.extend(split_asteroid(asteroid, ctx.shapes, ctx.rng));
+        }
+
+        // UFO vs Asteroid: flying into a rock destroys the UFO and breaks the rock
+        for ufo in self.ufos.iter_mut() {
+            if !ufo.active { continue; }
+            for asteroid in self.asteroids.iter_mut() {
+                if !asteroid.active { continue; }
+                if check_collision(ufo.pos, ufo.radius, asteroid.pos, asteroid.radius) {
+                    ufo.active = false;
+                    asteroid.active = false;
+
+                    new_asteroids.extend(split_asteroid(asteroid, ctx.shapes, ctx.rng));
+
+                    ctx.audio.play(SoundId::Bang);
+                    info!("UFO collided with asteroid.");
+                    ctx.particles.request(SpawnRequest { pos: asteroid.pos, drift: asteroid.vel * TARGET_FPS, ..explosion_preset(asteroid.radius, debris) });
+                }
+            }
+        }
+
+        // Player vs UFO
+        for ufo in self.ufos.iter_mut() {
+            for (index, player) in self.players.iter_mut().enumerate() {
+                if ufo.active && player.active && !player.invulnerable && !player_hit_this_frame[index] && ship_hits_circle(player, ufo.pos, ufo.radius) {
+                    player_hit_this_frame[index] = true;
+                    ufo.active = false; // Destroy UFO
+                    report.game_over |= damage_ship(player, index, 1, Some(safe_spawn), SoundId::Bang, "hit by a UFO", ctx);
+                }
+            }
+        }
+
+        // Player vs Boss (the boss survives ramming)
+        for boss in self.bosses.iter() {
+            for (index, player) in self.players.iter_mut().enumerate() {
+                if boss.active && player.active && !player.invulnerable && !player_hit_this_frame[index] && ship_hits_circle(player, boss.pos, boss.radius) {
+                    player_hit_this_frame[index] = true;
+                    report.game_over |= damage_ship(player, index, 1, Some(safe_spawn), SoundId::Bang, "rammed the boss", ctx);
+                }
+            }
+        }
+
+        // Player vs Power-up
+        for powerup in self.powerups.iter_mut() {
+            for (index, player) in self.players.iter_mut().enumerate() {
+                if !powerup.active || !player.active || !check_collision(player.pos, player.radius, powerup.pos, powerup.radius) { continue; }
+                powerup.active = false;
+                ctx.audio.play(if powerup.kind == PowerUpKind::Points { SoundId::Token } else { SoundId::Warp });
+                match powerup.kind {
+                    PowerUpKind::Shield => {
+                        player.shield_charges += 1;
+                        info!("Player {} picked up a shield. Charges: {}", index + 1, player.shield_charges);
+                    }
+                    PowerUpKind::Bomb => {
+                        player.bombs = (player.bombs + 1).min(MAX_BOMBS);
+                        info!("Player {} picked up a bomb. Bombs: {}", index + 1, player.bombs);
+                    }
+                    PowerUpKind::Points => {
+                        ctx.score.add(POINT_TOKEN_VALUE);
+                        ctx.kill_feed.push("Token", POINT_TOKEN_VALUE);
+                        info!("Player {} picked up a point token. Score: {}", index + 1, ctx.score.score);
+                    }
+                    PowerUpKind::Pierce => {
+                        player.pierce_timer = PIERCE_DURATION;
+                        info!("Player {} picked up piercing shots for {}s", index + 1, PIERCE_DURATION);
+                    }
+                }
+            }
+        }
+
+        // Clean up
+        self.bullets.retain(|b| b.active);
+        self.asteroids.retain(|a| a.active);
+        self.ufos.retain(|u| u.active);
+        self.bosses.retain(|b| b.active);
+        self.powerups.retain(|p| p.active);
+        self.asteroids.append(&mut new_asteroids);
+
+        // --- SPAWN LOGIC (UFO) ---
+        let start_lives = settings.advanced.player_lives as i32 * ctx.lives.counts().len() as i32;
+        ctx.director.update(dt, ctx.score.score, ctx.lives.total());
+        let ufo_chance = if settings.adaptive_spawning {
+            ctx.level.ufo_spawn_chance * ctx.director.multiplier(start_lives)
+        } else {
+            ctx.level.ufo_spawn_chance
+        };
+        if ctx.force_ufo || (self.ufos.is_empty() && ctx.rng.gen_range_f32(0.0, 1.0) < ufo_chance) {
+            ctx.audio.play(SoundId::Warp); // Sound cue for UFO entry
+            let spawn_pos = vec2(0., ctx.rng.gen_range_f32(0., self.bounds.height));
+            self.ufos.push(Ufo {
+                pos: spawn_pos,
+                prev_pos: spawn_pos,
+                vel: vec2(ctx.level.ufo_speed, 0.),
+                radius: 20.,
+                active: true,
+                shoot_timer: 0.,
+                change_dir_timer: 0.,
+            });
+            report.ufo_spawned = Some(spawn_pos);
+        }
+
+        // --- LEVEL PROGRESSION ---
+        if self.asteroids.is_empty() && self.ufos.is_empty() && self.bosses.is_empty() {
+            self.advance_level(dt, ctx, &mut report);
+        } else if ctx.campaign.boss_rush {
+            ctx.campaign.boss_rush_time += dt;
+        }
+        report
+    }
+
+    /// Moves a cleared field on to the next level, endless wave or the win.
+    fn advance_level(&mut self, dt: f32, ctx: &mut StepContext, report: &mut StepReport) {
+        let settings = ctx.settings;
+        let levels = ctx.levels;
+        let campaign = &mut *ctx.campaign;
+        let (speed, density) = (settings.advanced.enemy_speed, settings.advanced.spawn_density);
+        let next_idx = next_level_idx(levels, campaign.level_idx, campaign.boss_rush);
+        // Boss rush gives a short breather (off the clock) before each new boss
+        match (campaign.boss_rush, next_idx, campaign.boss_rush_breather) {
+            (true, Some(_), None) => {
+                campaign.boss_rush_cleared += 1;
+                campaign.boss_rush_breather = Some(BOSS_RUSH_BREATHER);
+                let total = levels.iter().filter(|l| l.is_boss()).count();
+                info!("Boss rush: {}/{} cleared at {:.2}s", campaign.boss_rush_cleared, total, campaign.boss_rush_time);
+                return;
+            }
+            (true, Some(_), Some(t)) if t > 0.0 => {
+                campaign.boss_rush_breather = Some(t - dt);
+                return;
+            }
+            _ => {}
+        }
+
+        campaign.boss_rush_breather = None;
+        ctx.event_log.push(ctx.run_time, GameEvent::LevelComplete { level: campaign.level_number(levels) });
+        let next_cfg = match next_idx {
+            // Endless mode carries on with generated waves (not in boss rush or the daily)
+            None if ctx.endless && !campaign.boss_rush && ctx.daily.is_none() => {
+                let wave = campaign.endless_wave.map_or(0, |w| w + 1);
+                let wave_cfg = LevelConfig::procedural(wave);
+                if let Err(e) = wave_cfg.validate(levels.len() + wave) {
+                    error!("Endless wave rejected: {}", e);
+                    report.game_won = true;
+                    return;
+                }
+                campaign.endless_wave = Some(wave);
+                info!("Wave cleared. Advancing to wave {}", levels.len() + wave + 1);
+                wave_cfg.scaled(speed, density)
+            }
+            None => {
+                report.game_won = true;
+                info!("All levels cleared. Game won.");
+                if campaign.boss_rush {
+                    campaign.boss_rush_cleared += 1;
+                    info!("Boss rush complete in {:.2}s", campaign.boss_rush_time);
+                }
+                return;
+            }
+            Some(idx) => {
+                campaign.level_idx = idx;
+                info!("Level complete. Advancing to level {}", idx + 1);
+                if let Some(seed) = ctx.daily {
+                    *ctx.rng = seed_daily_level(seed, idx);
+                }
+                levels[idx].scaled(speed, density)
+            }
+        };
+        start_level(self, &next_cfg, ctx.coop, settings.edge_entry, ctx.shapes, ctx.rng, SAFE_SPAWN_CLEARANCE);
+        report.level_cleared = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::{NullAudio, RecordingAudio};
+    use crate::settings::{CoopLives, Difficulty};
+    use crate::test_support::{rock, test_world};
+
+    /// Everything a run keeps outside the world, owned so tests can hand it to `step`.
+    struct Run {
+        settings: Settings,
+        tuning: PlayerTuning,
+        scores: ScoreTable,
+        level: LevelConfig,
+        levels: Vec<LevelConfig>,
+        campaign: Campaign,
+        director: AdaptiveDirector,
+        score: ScoreState,
+        lives: Lives,
+        overdrive: Overdrive,
+        sweeps: SweepTracker,
+        kill_feed: KillFeed,
+        event_log: EventLog,
+        particles: ParticleSpawnBridge,
+        shapes: ShapeSource,
+        rng: GameRng,
+        fixed: FixedStep,
+        daily: Option<u64>,
+    }
+
+    impl Run {
+        fn new(seed: u64) -> Self {
+            Run {
+                settings: Settings::default(),
+                tuning: PlayerTuning::default(),
+                scores: ScoreTable::default(),
+                level: LevelConfig::default(),
+                levels: vec![LevelConfig::default()],
+                campaign: Campaign::default(),
+                director: AdaptiveDirector::new(3),
+                score: ScoreState::new(),
+                lives: Lives::new(CoopLives::Shared, 3, 1),
+                overdrive: Overdrive::new(),
+                sweeps: SweepTracker::default(),
+                kill_feed: KillFeed::new(),
+                event_log: EventLog::default(),
+                particles: ParticleSpawnBridge::new(seed),
+                shapes: ShapeSource::new(Some(seed)),
+                rng: GameRng::new(seed),
+                fixed: FixedStep::new(),
+                daily: None,
+            }
+        }
+
+        fn step(&mut self, world: &mut GameWorld, input: &FrameInput, audio: &dyn AudioBackend) -> StepReport {
+            let mut ctx = StepContext {
+                settings: &self.settings,
+                tuning: &self.tuning,
+                scores: &self.scores,
+                level: &self.level,
+                levels: &self.levels,
+                campaign: &mut self.campaign,
+                director: &mut self.director,
+                score: &mut self.score,
+                lives: &mut self.lives,
+                overdrive: &mut self.overdrive,
+                sweeps: &mut self.sweeps,
+                kill_feed: &mut self.kill_feed,
+                event_log: &mut self.event_log,
+                particles: &self.particles,
+                audio,
+                shapes: &mut self.shapes,
+                rng: &mut self.rng,
+                fixed: &mut self.fixed,
+                sandbox: false,
+                coop: false,
+                endless: false,
+                daily: self.daily,
+                force_ufo: false,
+                run_time: 0.0,
+                control_dt: FIXED_DT,
+            };
+            world.step(input, FIXED_DT, &mut ctx)
+        }
+    }
+
+    fn fire() -> FrameInput {
+        FrameInput { ships: vec![Controls { fire: true, ..Default::default() }] }
+    }
+
+    #[test]
+    fn test_shooting_an_asteroid_splits_it_and_scores() {
+        let mut world = test_world();
+        world.asteroids.push(rock(vec2(500., 300.), Vec2::ZERO, 20.));
+        let mut run = Run::new(1562);
+
+        // Hold fire at the medium rock dead ahead until it breaks
+        let mut destroyed = 0;
+        for _ in 0..300 {
+            destroyed += run.step(&mut world, &fire(), &NullAudio).asteroids_destroyed;
+            if destroyed > 0 { break; }
+        }
+
+        assert_eq!(destroyed, 1, "the asteroid never broke");
+        assert_eq!(world.asteroids.len(), 2);
+        assert!(world.asteroids.iter().all(|a| a.radius < 20. && a.active));
+        assert_eq!(run.score.score, run.scores.asteroid_points(AsteroidSize::Medium));
+        assert!(run.overdrive.charge > 0.0);
+    }
+
+    #[test]
+    fn test_coop_ships_share_the_score_and_can_both_be_destroyed() {
+        let mut world = test_world();
+        world.respawn_team(true);
+        for (player, y) in world.players.iter_mut().zip([150., 450.]) {
+            player.pos = vec2(400., y);
+            player.prev_pos = player.pos;
+            world.asteroids.push(rock(vec2(500., y), Vec2::ZERO, 20.));
+        }
+        let mut run = Run::new(1481);
+        run.lives = Lives::new(CoopLives::Separate, 1, 2);
+
+        // Each ship breaks the rock ahead of it; both kills land in the one score
+        let both_fire = FrameInput { ships: vec![Controls { fire: true, ..Default::default() }; 2] };
+        let mut destroyed = 0;
+        for _ in 0..120 {
+            destroyed += run.step(&mut world, &both_fire, &NullAudio).asteroids_destroyed;
+            if destroyed == 2 { break; }
+        }
+        assert_eq!(destroyed, 2);
+        // The second kill rides the first one's combo
+        assert_eq!(run.score.score, 3 * run.scores.asteroid_points(AsteroidSize::Medium));
+
+        // A UFO shot on each ship: the first is benched, the second ends the run
+        world.asteroids.clear();
+        world.bullets.clear();
+        for player in world.players.iter() {
+            world.bullets.push(Bullet::new(player.pos, Vec2::ZERO, 1.0, BulletOwner::Ufo));
+        }
+        let report = run.step(&mut world, &FrameInput::default(), &NullAudio);
+        assert!(report.game_over);
+        assert!(!world.players[0].active);
+        assert_eq!(run.lives.counts(), [0, 0]);
+    }
+
+    #[test]
+    fn test_misjump_warns_and_costs_a_life_unless_shielded() {
+        let mut run = Run::new(1525);
+        run.settings.difficulty = Difficulty::Hard;
+        let jump = FrameInput { ships: vec![Controls { hyperspace: true, ..Default::default() }] };
+        let audio = RecordingAudio::default();
+        let mut world = test_world();
+        world.players[0].shield_charges = 1;
+
+        // Jump until one misfires; the first misjump only burns the shield
+        let mut jumps = 0;
+        while !audio.played.borrow().contains(&SoundId::Misjump) && jumps < 200 {
+            world.players[0].hyperspace_cooldown = 0.0;
+            world.players[0].invulnerable = false;
+            run.step(&mut world, &jump, &audio);
+            jumps += 1;
+        }
+        assert!(audio.played.borrow().contains(&SoundId::Misjump), "no misjump in {} jumps", jumps);
+        assert!(!audio.played.borrow().contains(&SoundId::Bang));
+        assert_eq!(world.players[0].shield_charges, 0);
+        assert!(world.players[0].invulnerable);
+        assert_eq!(run.lives.total(), 3);
+
+        // Unshielded, the next misjump costs a life
+        let misjumps = || audio.played.borrow().iter().filter(|&&s| s == SoundId::Misjump).count();
+        for _ in 0..200 {
+            if misjumps() == 2 { break; }
+            world.players[0].hyperspace_cooldown = 0.0;
+            run.step(&mut world, &jump, &audio);
+        }
+        assert_eq!(misjumps(), 2);
+        assert_eq!(run.lives.total(), 2);
+        assert!(world.players[0].active);
+    }
+
+    #[test]
+    fn test_ufo_arrivals_are_rolled_from_the_run_seed() {
+        let arrival = |seed| {
+            let mut run = Run::new(seed);
+            run.level = LevelConfig { ufo_spawn_chance: 0.05, ufo_speed: 2.0, ..LevelConfig::default() };
+            let mut world = test_world();
+            world.asteroids.push(rock(vec2(700., 500.), Vec2::ZERO, 10.));
+            let audio = RecordingAudio::default();
+            for frame in 0..600 {
+                if let Some(pos) = run.step(&mut world, &FrameInput::default(), &audio).ufo_spawned {
+                    assert_eq!(world.ufos.len(), 1);
+                    assert_eq!(pos.x, 0.0);
+                    assert_eq!(world.ufos[0].vel, vec2(2.0, 0.0));
+                    assert!(audio.played.borrow().contains(&SoundId::Warp));
+                    return (frame, pos);
+                }
+            }
+            panic!("no UFO arrived");
+        };
+        // The same seed brings the same UFO at the same moment
+        assert_eq!(arrival(1534), arrival(1534));
+        assert_ne!(arrival(1534), arrival(1474));
+    }
+
+    #[test]
+    fn test_clearing_the_field_loads_the_next_level_then_wins() {
+        let mut run = Run::new(1562);
+        run.levels = vec![LevelConfig::default(), LevelConfig { asteroid_count: 6, ..LevelConfig::default() }];
+        let mut world = test_world();
+
+        let report = run.step(&mut world, &FrameInput::default(), &NullAudio);
+        assert!(report.level_cleared && !report.game_won);
+        assert_eq!(run.campaign.level_idx, 1);
+        assert_eq!(world.asteroids.len(), 6);
+        assert_eq!(run.event_log.events.last().map(|(_, e)| e), Some(&GameEvent::LevelComplete { level: 1 }));
+
+        // Rocks left on the field hold the level; clearing the last one wins the run
+        assert!(!run.step(&mut world, &FrameInput::default(), &NullAudio).level_cleared);
+        world.asteroids.clear();
+        let report = run.step(&mut world, &FrameInput::default(), &NullAudio);
+        assert!(report.game_won && !report.level_cleared);
+        assert_eq!(run.campaign.level_idx, 1);
+    }
+
+    #[test]
+    fn test_daily_levels_are_the_same_whatever_came_before() {
+        // Two players reach level 2 of the same daily after different runs
+        let layouts: Vec<Vec<Vec2>> = [1, 2]
+            .map(|seed| {
+                let mut run = Run::new(seed);
+                run.levels = vec![LevelConfig::default(); 2];
+                run.daily = Some(20240229);
+                let mut world = test_world();
+                run.step(&mut world, &FrameInput::default(), &NullAudio);
+                assert_eq!(run.campaign.level_idx, 1);
+                world.asteroids.iter().map(|a| a.pos).collect()
+            })
+            .into();
+        assert_eq!(layouts[0], layouts[1]);
+    }
+
+    #[test]
+    fn test_firing_plays_the_shoot_sound() {
+        let mut world = test_world();
+        let mut run = Run::new(1563);
+        let audio = RecordingAudio::default();
+        let report = run.step(&mut world, &fire(), &audio);
+        assert_eq!(report.shots_fired, 1);
+        assert_eq!(*audio.played.borrow(), vec![SoundId::Shoot]);
+        // Idle frames stay quiet
+        run.step(&mut world, &FrameInput::default(), &audio);
+        assert_eq!(audio.played.borrow().len(), 1);
+    }
+}
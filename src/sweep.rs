@@ -0,0 +1,87 @@
+/// Seconds after a split within which every fragment must be shot for a clean sweep.
+pub const SWEEP_WINDOW: f64 = 2.0;
+/// Bonus points for a clean sweep (not multiplied by the combo).
+pub const SWEEP_BONUS: u32 = 250;
+
+/// Whether a split into `children` fragments at `split_at` has been cleanly swept:
+/// every fragment destroyed (one entry per kill in `kills`) within `window` seconds.
+pub fn is_clean_sweep(split_at: f64, kills: &[f64], children: usize, window: f64) -> bool {
+    children > 0 && kills.len() == children && kills.iter().all(|t| t - split_at <= window)
+}
+
+/// The fragments of one split, and when each was shot.
+#[derive(Clone, Debug, PartialEq)]
+struct Family {
+    id: u32,
+    split_at: f64,
+    children: usize,
+    kills: Vec<f64>,
+}
+
+/// Tracks asteroids the players just split, to reward shooting every fragment quickly.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SweepTracker {
+    families: Vec<Family>,
+    next_id: u32,
+}
+
+impl SweepTracker {
+    /// Starts tracking a split into `children` fragments at `now`; tag the fragments with the returned id.
+    pub fn register(&mut self, children: usize, now: f64) -> u32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.families.push(Family { id, split_at: now, children, kills: Vec::new() });
+        id
+    }
+
+    /// Records a player shooting a fragment of `family` at `now`; true when that completes a clean sweep.
+    pub fn record_kill(&mut self, family: u32, now: f64) -> bool {
+        let Some(index) = self.families.iter().position(|f| f.id == family) else { return false };
+        let entry = &mut self.families[index];
+        entry.kills.push(now);
+        if is_clean_sweep(entry.split_at, &entry.kills, entry.children, SWEEP_WINDOW) {
+            self.families.swap_remove(index);
+            return true;
+        }
+        false
+    }
+
+    /// Forgets splits whose window has closed at `now`.
+    pub fn expire(&mut self, now: f64) {
+        self.families.retain(|f| now - f.split_at <= SWEEP_WINDOW);
+    }
+
+    pub fn clear(&mut self) {
+        self.families.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_sweep_needs_every_child_in_time() {
+        assert!(is_clean_sweep(10.0, &[10.4, 11.2], 2, SWEEP_WINDOW));
+        // Only one of the two fragments shot
+        assert!(!is_clean_sweep(10.0, &[10.4], 2, SWEEP_WINDOW));
+        // The second one came too late
+        assert!(!is_clean_sweep(10.0, &[10.4, 12.5], 2, SWEEP_WINDOW));
+        assert!(!is_clean_sweep(10.0, &[], 0, SWEEP_WINDOW));
+    }
+
+    #[test]
+    fn test_tracker_rewards_only_complete_sweeps() {
+        let mut sweeps = SweepTracker::default();
+        let quick = sweeps.register(2, 1.0);
+        let slow = sweeps.register(2, 1.0);
+        assert!(!sweeps.record_kill(quick, 1.5));
+        assert!(!sweeps.record_kill(slow, 1.6));
+        assert!(sweeps.record_kill(quick, 2.0));
+        // A completed family isn't rewarded twice
+        assert!(!sweeps.record_kill(quick, 2.1));
+        sweeps.expire(3.5);
+        assert!(!sweeps.record_kill(slow, 3.5));
+        assert!(sweeps.families.is_empty());
+    }
+}
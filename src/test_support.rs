@@ -0,0 +1,16 @@
+//! Fixtures shared by the unit tests.
+
+use macroquad::prelude::*;
+use crate::components::{Asteroid, Player};
+use crate::physics::World;
+use crate::world::GameWorld;
+
+/// An 800x600 world with one ship at its center.
+pub fn test_world() -> GameWorld {
+    GameWorld::new(Player::new(vec2(400., 300.)), World::new(800., 600.), 1.0)
+}
+
+/// A six-sided asteroid of `radius` at `pos`, drifting at `vel`.
+pub fn rock(pos: Vec2, vel: Vec2, radius: f32) -> Asteroid {
+    Asteroid { vel, ..Asteroid::new(pos, radius, 6) }
+}
@@ -0,0 +1,334 @@
+use std::time::Duration;
+
+/// Accumulated gameplay time.
+///
+/// Unlike `get_time()`, the clock only advances when `tick` is called, which the
+/// game loop does exclusively while in `GameState::Playing`. A paused clock
+/// ignores ticks altogether.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GameClock {
+    elapsed: f64,
+    paused: bool,
+}
+
+impl GameClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advances the clock by `dt` seconds of gameplay, unless paused.
+    pub fn tick(&mut self, dt: f32) {
+        if !self.paused {
+            self.elapsed += dt.max(0.0) as f64;
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Total gameplay seconds so far.
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+
+    /// Returns a deadline `seconds` of gameplay from now.
+    pub fn deadline_after(&self, seconds: f64) -> f64 {
+        self.elapsed + seconds
+    }
+
+    /// Whether gameplay time has reached `deadline`.
+    pub fn reached(&self, deadline: f64) -> bool {
+        self.elapsed >= deadline
+    }
+}
+
+/// Formats gameplay seconds as `m:ss` for the run timer.
+pub fn format_run_time(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    format!("{}:{:02}", total / 60, total % 60)
+}
+
+/// Real-time seconds the last-stand slow motion lasts.
+pub const LAST_STAND_DURATION: f32 = 2.0;
+/// Gameplay speed at the height of the last-stand effect.
+pub const LAST_STAND_TIME_SCALE: f32 = 0.4;
+
+/// Returns true when a life loss took the player from more lives down to their final one.
+pub fn dropped_to_last_life(lives_before: i32, lives_after: i32) -> bool {
+    lives_before > 1 && lives_after == 1
+}
+
+/// Brief slow-motion, desaturated moment when the player is down to one life.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LastStand {
+    remaining: f32,
+}
+
+impl LastStand {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn trigger(&mut self) {
+        self.remaining = LAST_STAND_DURATION;
+    }
+
+    pub fn clear(&mut self) {
+        self.remaining = 0.0;
+    }
+
+    /// Counts down using real (unscaled) frame time.
+    pub fn update(&mut self, real_dt: f32) {
+        self.remaining = (self.remaining - real_dt.max(0.0)).max(0.0);
+    }
+
+    /// Effect strength: 1.0 at the start, easing out to 0.0 over the last half.
+    pub fn intensity(&self) -> f32 {
+        (self.remaining / (LAST_STAND_DURATION * 0.5)).min(1.0)
+    }
+
+    /// Multiplier for gameplay `dt` (1.0 when inactive).
+    pub fn time_scale(&self) -> f32 {
+        1.0 - (1.0 - LAST_STAND_TIME_SCALE) * self.intensity()
+    }
+}
+
+/// Gameplay speed while bullet time is on.
+pub const BULLET_TIME_SCALE: f32 = 0.35;
+/// Meter spent per real second of bullet time (a full meter lasts 2.5s).
+pub const BULLET_TIME_DRAIN: f32 = 0.4;
+/// Meter regained per real second while bullet time is off.
+pub const BULLET_TIME_RECHARGE: f32 = 0.1;
+/// Real seconds after bullet time ends before it can be used again.
+pub const BULLET_TIME_COOLDOWN: f32 = 3.0;
+
+/// Meter level (0.0 empty .. 1.0 full) after `real_dt` seconds, draining while
+/// `active` and recharging otherwise.
+pub fn step_meter(meter: f32, active: bool, real_dt: f32) -> f32 {
+    let rate = if active { -BULLET_TIME_DRAIN } else { BULLET_TIME_RECHARGE };
+    (meter + rate * real_dt.max(0.0)).clamp(0.0, 1.0)
+}
+
+/// Gameplay `dt` for a frame of `real_dt` seconds played at `time_scale`.
+pub fn scaled_dt(real_dt: f32, time_scale: f32) -> f32 {
+    real_dt.max(0.0) * time_scale.clamp(0.0, 1.0)
+}
+
+/// Player-triggered slow motion, limited by a draining meter and a cooldown.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BulletTime {
+    meter: f32,
+    active: bool,
+    cooldown: f32,
+}
+
+impl Default for BulletTime {
+    fn default() -> Self {
+        BulletTime { meter: 1.0, active: false, cooldown: 0.0 }
+    }
+}
+
+impl BulletTime {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 0.0 (empty) to 1.0 (full).
+    pub fn meter(&self) -> f32 {
+        self.meter
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Whether a press would start bullet time.
+    pub fn is_ready(&self) -> bool {
+        !self.active && self.cooldown <= 0.0 && self.meter > 0.0
+    }
+
+    /// Starts bullet time when ready, or ends it early when it's on. Returns true if it started.
+    pub fn toggle(&mut self) -> bool {
+        if self.active {
+            self.stop();
+            return false;
+        }
+        self.active = self.is_ready();
+        self.active
+    }
+
+    fn stop(&mut self) {
+        self.active = false;
+        self.cooldown = BULLET_TIME_COOLDOWN;
+    }
+
+    /// Drains the meter, or recharges it once the cooldown is over, using real
+    /// (unscaled) frame time; an empty meter ends bullet time.
+    pub fn update(&mut self, real_dt: f32) {
+        if self.active {
+            self.meter = step_meter(self.meter, true, real_dt);
+            if self.meter <= 0.0 {
+                self.stop();
+            }
+        } else if self.cooldown > 0.0 {
+            self.cooldown = (self.cooldown - real_dt.max(0.0)).max(0.0);
+        } else {
+            self.meter = step_meter(self.meter, false, real_dt);
+        }
+    }
+
+    /// Multiplier for gameplay `dt` (1.0 when off).
+    pub fn time_scale(&self) -> f32 {
+        if self.active { BULLET_TIME_SCALE } else { 1.0 }
+    }
+}
+
+/// Frame rates offered for the soft FPS cap (0 = uncapped).
+pub const FPS_CAP_CHOICES: [u32; 5] = [0, 30, 60, 120, 144];
+
+/// Target duration of one frame for `target_fps`, or `None` when uncapped.
+pub fn frame_budget(target_fps: u32) -> Option<Duration> {
+    if target_fps == 0 {
+        None
+    } else {
+        Some(Duration::from_secs_f64(1.0 / target_fps as f64))
+    }
+}
+
+/// How long to sleep when a frame took `elapsed` of a `target` budget (never negative).
+pub fn frame_sleep(target: Duration, elapsed: Duration) -> Duration {
+    target.saturating_sub(elapsed)
+}
+
+/// The cap that follows `current` in `FPS_CAP_CHOICES` (wrapping back to uncapped).
+pub fn next_fps_cap(current: u32) -> u32 {
+    FPS_CAP_CHOICES
+        .iter()
+        .copied()
+        .find(|&fps| fps > current)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadline_only_advances_with_ticks() {
+        let mut clock = GameClock::new();
+        let deadline = clock.deadline_after(2.0);
+        clock.tick(1.5);
+        assert!(!clock.reached(deadline));
+        // Time spent paused is never ticked, so the deadline stays pending
+        assert!(!clock.reached(deadline));
+        clock.tick(0.5);
+        assert!(clock.reached(deadline));
+    }
+
+    #[test]
+    fn test_negative_dt_ignored() {
+        let mut clock = GameClock::new();
+        clock.tick(-1.0);
+        assert_eq!(clock.elapsed(), 0.0);
+    }
+
+    #[test]
+    fn test_paused_clock_ignores_ticks() {
+        let mut clock = GameClock::new();
+        clock.tick(1.0);
+        clock.pause();
+        clock.tick(5.0);
+        assert_eq!(clock.elapsed(), 1.0);
+        clock.resume();
+        clock.tick(0.5);
+        assert_eq!(clock.elapsed(), 1.5);
+    }
+
+    #[test]
+    fn test_format_run_time() {
+        assert_eq!(format_run_time(0.0), "0:00");
+        assert_eq!(format_run_time(59.9), "0:59");
+        assert_eq!(format_run_time(125.0), "2:05");
+        assert_eq!(format_run_time(-3.0), "0:00");
+    }
+
+    #[test]
+    fn test_dropped_to_last_life_only_on_transition() {
+        assert!(dropped_to_last_life(2, 1));
+        assert!(!dropped_to_last_life(1, 1));
+        assert!(!dropped_to_last_life(1, 0));
+        assert!(!dropped_to_last_life(3, 2));
+    }
+
+    #[test]
+    fn test_last_stand_scales_then_recovers() {
+        let mut stand = LastStand::new();
+        assert_eq!(stand.time_scale(), 1.0);
+        stand.trigger();
+        assert!((stand.time_scale() - LAST_STAND_TIME_SCALE).abs() < 1e-5);
+        stand.update(LAST_STAND_DURATION * 0.75);
+        assert!(stand.time_scale() > LAST_STAND_TIME_SCALE && stand.time_scale() < 1.0);
+        stand.update(LAST_STAND_DURATION);
+        assert_eq!(stand.time_scale(), 1.0);
+        assert_eq!(stand.intensity(), 0.0);
+    }
+
+    #[test]
+    fn test_bullet_time_meter_never_goes_negative() {
+        assert_eq!(step_meter(0.1, true, 10.0), 0.0);
+        let mut bullet_time = BulletTime::new();
+        assert!(bullet_time.toggle());
+        assert_eq!(scaled_dt(1.0, bullet_time.time_scale()), BULLET_TIME_SCALE);
+        for _ in 0..180 {
+            bullet_time.update(1. / 60.);
+            assert!(bullet_time.meter() >= 0.0);
+        }
+        // Running dry ends it and starts the cooldown
+        assert!(!bullet_time.is_active());
+        assert!(!bullet_time.toggle());
+        assert_eq!(scaled_dt(1.0, bullet_time.time_scale()), 1.0);
+    }
+
+    #[test]
+    fn test_bullet_time_meter_recharges_to_max() {
+        assert_eq!(step_meter(0.95, false, 10.0), 1.0);
+        let mut bullet_time = BulletTime::new();
+        bullet_time.toggle();
+        bullet_time.update(1.0);
+        // Ending early keeps what's left, then recharges
+        assert!(!bullet_time.toggle());
+        let left = bullet_time.meter();
+        assert!(left > 0.0 && left < 1.0);
+        // Nothing comes back during the cooldown
+        bullet_time.update(BULLET_TIME_COOLDOWN);
+        assert_eq!(bullet_time.meter(), left);
+        assert!(bullet_time.is_ready());
+        bullet_time.update(1.0 / BULLET_TIME_RECHARGE);
+        assert_eq!(bullet_time.meter(), 1.0);
+        assert_eq!(scaled_dt(-1.0, 0.5), 0.0);
+    }
+
+    #[test]
+    fn test_frame_sleep_fills_remaining_budget() {
+        let target = frame_budget(60).unwrap();
+        let elapsed = Duration::from_millis(10);
+        assert_eq!(frame_sleep(target, elapsed), target - elapsed);
+        // Overrunning the budget never sleeps
+        assert_eq!(frame_sleep(target, Duration::from_millis(40)), Duration::ZERO);
+        assert_eq!(frame_budget(0), None);
+    }
+
+    #[test]
+    fn test_next_fps_cap_cycles() {
+        assert_eq!(next_fps_cap(0), 30);
+        assert_eq!(next_fps_cap(60), 120);
+        assert_eq!(next_fps_cap(144), 0);
+        // Custom values from the settings file snap to the next choice
+        assert_eq!(next_fps_cap(75), 120);
+    }
+}
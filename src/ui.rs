@@ -1,40 +1,747 @@
 use macroquad::prelude::*;
-use crate::settings::{Settings, Difficulty};
+use macroquad::rand::gen_range;
+use crate::boss::BossPhase;
+use crate::console::{Console, CONSOLE_SCROLLBACK};
+use crate::components::{Asteroid, BulletOwner, Player, PowerUpKind, Ufo, TRAIL_LEN};
+use crate::highscores::HighScores;
+use crate::lives::Lives;
+use crate::score::ScoreState;
+use crate::resources::SOUND_NAMES;
+use crate::palette::{brightness_overlay, ColorScheme, Palette, Role};
+use crate::time::format_run_time;
+use crate::settings::{key_name, AdvancedField, CollisionResponse, ControlMode, CoopLives, Settings, SettingsTab, ShipField, Difficulty, ThrustModel};
+use crate::physics::{segment_circle_intersect, World};
+use crate::world::{lerp_wrapped, GameWorld};
 
-/// Draw the title screen with basic controls.
-pub fn draw_title_screen() {
+/// Draw the title screen with basic controls and today's daily-challenge best.
+pub fn draw_title_screen(settings: &Settings, coop: bool, endless: bool, sandbox: bool, has_save: bool, daily_best: Option<u32>) {
     clear_background(BLACK);
     draw_text("RUST ASTEROIDS", screen_width() / 2. - 140., screen_height() / 2. - 80., 40., WHITE);
     draw_text("Press Enter to Start", screen_width() / 2. - 130., screen_height() / 2. - 20., 24., GRAY);
-    draw_text("Press S for Settings", screen_width() / 2. - 120., screen_height() / 2. + 10., 20., GRAY);
-    draw_text("Press Esc to Quit", screen_width() / 2. - 115., screen_height() / 2. + 40., 20., GRAY);
+    if has_save {
+        draw_text("Press C to Continue your saved game", screen_width() / 2. - 170., screen_height() / 2. - 50., 20., SKYBLUE);
+    }
+    draw_text("Press B for Boss Rush", screen_width() / 2. - 120., screen_height() / 2. + 10., 20., GRAY);
+    let coop_label = format!("Press 2 for Two-Player Co-op: {}", if coop { "On" } else { "Off" });
+    draw_text(&coop_label, screen_width() / 2. - 120., screen_height() / 2. + 40., 20., if coop { SKYBLUE } else { GRAY });
+    let endless_label = format!("Press E for Endless Waves: {}", if endless { "On" } else { "Off" });
+    draw_text(&endless_label, screen_width() / 2. - 120., screen_height() / 2. + 70., 20., if endless { SKYBLUE } else { GRAY });
+    let daily = match daily_best {
+        Some(best) => format!("Press D for the Daily Challenge (today's best: {})", best),
+        None => "Press D for the Daily Challenge".to_string(),
+    };
+    draw_text(&daily, screen_width() / 2. - 120., screen_height() / 2. + 100., 20., GRAY);
+    let sandbox_label = format!("Press P for Sandbox Practice (no lives lost): {}", if sandbox { "On" } else { "Off" });
+    draw_text(&sandbox_label, screen_width() / 2. - 120., screen_height() / 2. + 130., 20., if sandbox { ORANGE } else { GRAY });
+    draw_text("Press S for Settings", screen_width() / 2. - 120., screen_height() / 2. + 160., 20., GRAY);
+    draw_text("Press Esc to Quit", screen_width() / 2. - 115., screen_height() / 2. + 190., 20., GRAY);
+    draw_text("Press V to import a settings code from the clipboard", screen_width() / 2. - 190., screen_height() / 2. + 215., 16., DARKGRAY);
+    draw_text("Press G to load a shared ghost (ghost_YYYYMMDD_import.txt) to race in the Daily Challenge", screen_width() / 2. - 270., screen_height() / 2. + 235., 16., DARKGRAY);
+    let controls = settings.control_mode.describe(&settings.keys);
+    let width = measure_text(&controls, None, 18, 1.0).width;
+    draw_text(&controls, screen_width() / 2. - width / 2., screen_height() / 2. + 260., 18., DARKGRAY);
 }
 
-/// Draw the pause overlay.
-pub fn draw_pause_screen() {
+/// Draw the pause overlay (with the save option when the run can be saved).
+pub fn draw_pause_screen(pause_key: KeyCode, can_save: bool) {
     draw_text("PAUSED", screen_width() / 2. - 60., screen_height() / 2. - 60., 36., WHITE);
-    draw_text("Press P to Resume", screen_width() / 2. - 120., screen_height() / 2. - 20., 22., GRAY);
+    draw_text(&format!("Press {} to Resume", key_name(pause_key)), screen_width() / 2. - 120., screen_height() / 2. - 20., 22., GRAY);
     draw_text("Press S for Settings", screen_width() / 2. - 120., screen_height() / 2. + 10., 20., GRAY);
     draw_text("Press Esc for Title", screen_width() / 2. - 115., screen_height() / 2. + 40., 20., GRAY);
+    draw_text("Press R to Restart Level", screen_width() / 2. - 125., screen_height() / 2. + 70., 20., GRAY);
+    if can_save {
+        draw_text("Press F to Save Game", screen_width() / 2. - 120., screen_height() / 2. + 100., 20., GRAY);
+    }
+}
+
+/// Draw the high-score table down the left of the game-over screen, highlighting the row at `new_rank`.
+pub fn draw_high_scores(scores: &HighScores, new_rank: Option<usize>) {
+    draw_text("HIGH SCORES", 40., 80., 24., WHITE);
+    if scores.entries.is_empty() {
+        draw_text("No scores yet", 40., 110., 18., DARKGRAY);
+    }
+    for (i, entry) in scores.entries.iter().enumerate() {
+        let color = if Some(i) == new_rank { YELLOW } else { LIGHTGRAY };
+        let row = format!("{:>2}. {:>7}  {:<16} {:?}", i + 1, entry.score, entry.name, entry.difficulty);
+        draw_text(&row, 40., 110. + i as f32 * 22., 18., color);
+    }
+}
+
+/// Draw the name prompt for a run that made the high-score table.
+pub fn draw_name_prompt(name: &str) {
+    draw_text("NEW HIGH SCORE! Type your name and press Enter", screen_width() / 2. - 200., screen_height() - 90., 20., YELLOW);
+    draw_text(&format!("> {}_", name), screen_width() / 2. - 200., screen_height() - 60., 24., WHITE);
 }
 
-/// Draw the settings screen.
-pub fn draw_settings_screen(settings: &Settings) {
+/// Draw the tuning console across the top of the screen: scrollback above the prompt.
+pub fn draw_console(console: &Console) {
+    let height = 36. + CONSOLE_SCROLLBACK as f32 * 20.;
+    draw_rectangle(0., 0., screen_width(), height, Color::new(0., 0., 0., 0.8));
+    draw_line(0., height, screen_width(), height, 1., GRAY);
+    for (i, line) in console.lines().enumerate() {
+        let color = if line.starts_with('>') {
+            GRAY
+        } else if line.starts_with("error:") {
+            RED
+        } else {
+            WHITE
+        };
+        draw_text(line, 12., 20. + i as f32 * 20., 18., color);
+    }
+    draw_text(&format!("> {}_", console.input), 12., height - 10., 20., YELLOW);
+}
+
+/// Draw the "Quit? Y/N" confirmation overlay.
+pub fn draw_quit_prompt() {
+    draw_rectangle(0., 0., screen_width(), screen_height(), Color::new(0., 0., 0., 0.6));
+    draw_text("Quit? Y/N", screen_width() / 2. - 75., screen_height() / 2., 36., WHITE);
+}
+
+/// Draw the settings screen: a tab bar of categories and the options of the active one.
+pub fn draw_settings_screen(settings: &Settings, tab: SettingsTab, advanced_field: AdvancedField, selected_sound: usize, ship_field: ShipField) {
     clear_background(BLACK);
     draw_text("SETTINGS", screen_width() / 2. - 80., 60., 36., WHITE);
 
-    let difficulty = match settings.difficulty {
-        Difficulty::Easy => "Easy",
-        Difficulty::Normal => "Normal",
-        Difficulty::Hard => "Hard",
+    let mut x = 40.;
+    for t in SettingsTab::ALL {
+        let label = t.label();
+        let width = measure_text(label, None, 22, 1.0).width;
+        let color = if t == tab { YELLOW } else { GRAY };
+        if t == tab {
+            draw_rectangle_lines(x - 6., 82., width + 12., 28., 2., YELLOW);
+        }
+        draw_text(label, x, 102., 22., color);
+        x += width + 28.;
+    }
+    draw_text("Left/Right: category | X: copy share code | Enter: save & return", 40., 140., 18., GRAY);
+
+    let on_off = |on: bool| if on { "On" } else { "Off" };
+    let controls_hint = settings.control_mode.describe(&settings.keys);
+    let (rows, hint): (Vec<String>, &str) = match tab {
+        SettingsTab::General => (
+            vec![
+                format!("Volume (Up/Down): {:.2}", settings.volume),
+                format!("Mute (M): {}", on_off(settings.muted)),
+                format!(
+                    "Sound Level (Tab: pick, [ / ]: adjust): {} {:.0}%",
+                    SOUND_NAMES[selected_sound],
+                    settings.sound_volumes[selected_sound] * 100.
+                ),
+                format!("Confirm Quit (Q): {}", on_off(settings.confirm_quit)),
+                format!("Kill Feed (K): {}", on_off(settings.show_kill_feed)),
+                format!("Background Scanner (N): {}", on_off(settings.enable_scanner)),
+            ],
+            "Press the letter shown next to an option to change it",
+        ),
+        SettingsTab::Difficulty => {
+            let difficulty = match settings.difficulty {
+                Difficulty::Easy => "Easy",
+                Difficulty::Normal => "Normal",
+                Difficulty::Hard => "Hard",
+            };
+            let collisions = match settings.collision_response {
+                CollisionResponse::Lethal => "Lethal",
+                CollisionResponse::Bounce => "Bounce",
+            };
+            (
+                vec![
+                    format!("Difficulty (Up/Down): {}", difficulty),
+                    format!("Collisions (O): {}", collisions),
+                    format!("Adaptive UFOs (A): {}", on_off(settings.adaptive_spawning)),
+                ],
+                "Up/Down: preset (resets tuning) | Tab: select tuning | [ / ]: adjust",
+            )
+        }
+        SettingsTab::Gameplay => (
+            vec![
+                format!("Asteroid Impacts (I): {}", on_off(settings.asteroid_impacts)),
+                format!("Edge Entry (E): {}", on_off(settings.edge_entry)),
+                format!("Fixed Shapes (G): {}", on_off(settings.fixed_shapes)),
+                format!("Magnetic Storms (W): {}", on_off(settings.magnetic_storms)),
+                format!("Co-op Friendly Fire (R): {}", on_off(settings.friendly_fire)),
+                format!("Co-op Lives (L): {}", if settings.coop_lives == CoopLives::Shared { "Shared" } else { "Separate" }),
+            ],
+            "Press the letter shown next to an option to change it",
+        ),
+        SettingsTab::Controls => {
+            let controls = match settings.control_mode {
+                ControlMode::Classic => "Classic",
+                ControlMode::OneButton => "One-Button",
+                ControlMode::Mouse => "Mouse",
+            };
+            let thrust = match settings.thrust_model {
+                ThrustModel::Continuous => "Continuous",
+                ThrustModel::Impulse => "Impulse",
+            };
+            let ship_value = match ship_field {
+                ShipField::RotationSpeed => format!("{:.2} rad/s", settings.ship.rotation_speed),
+                ShipField::ThrustPower => format!("{:.2}", settings.ship.thrust_power),
+                ShipField::Friction => format!("{:.3}", settings.ship.friction),
+            };
+            (
+                vec![
+                    format!("Controls (C): {}", controls),
+                    format!("Hide Cursor in Mouse Mode (H): {}", on_off(settings.hide_cursor)),
+                    format!("Thrust (T): {}", thrust),
+                    format!("Auto-Fire (A): {}", on_off(settings.auto_fire)),
+                    format!("Ship Feel (Tab: pick, [ / ]: adjust): {} {}", ship_field.label(), ship_value),
+                ],
+                &controls_hint,
+            )
+        }
+        SettingsTab::Video => {
+            let fps_cap = if settings.target_fps == 0 { "Off".to_string() } else { settings.target_fps.to_string() };
+            (
+                vec![
+                    format!("Color Scheme (L): {}", settings.color_scheme.label()),
+                    format!("FPS Cap (Z): {}", fps_cap),
+                    format!("Show FPS (F): {}", on_off(settings.show_fps)),
+                    format!("Radar (R): {}", on_off(settings.show_radar)),
+                    format!("Brightness (- / =): {:.0}%", settings.brightness * 100.),
+                ],
+                "Press the letter shown next to an option to change it",
+            )
+        }
+        SettingsTab::Accessibility => (
+            vec![
+                format!("Reduced Motion (M): {}", on_off(settings.reduced_motion)),
+                format!("Aim Assist (J): {}", on_off(settings.aim_assist)),
+                format!("UFO Alerts (U): {}", on_off(settings.ufo_alerts)),
+                format!("Palette (P): {}", settings.palette.label()),
+            ],
+            "Press the letter shown next to an option to change it",
+        ),
     };
 
-    draw_text("Use Left/Right to change values", 40., 120., 20., GRAY);
-    draw_text("Use Up/Down to change difficulty", 40., 145., 20., GRAY);
-    draw_text("Press F to toggle FPS display", 40., 170., 20., GRAY);
-    draw_text("Press Enter to Save/Return", 40., 195., 20., GRAY);
+    let mut y = 210.;
+    for row in &rows {
+        draw_text(row, 60., y, 24., WHITE);
+        y += 32.;
+    }
+    draw_text(hint, 60., y, 16., GRAY);
+
+    if tab == SettingsTab::Difficulty {
+        let adv = &settings.advanced;
+        let tuning = [
+            (AdvancedField::EnemySpeed, format!("Enemy Speed: x{:.2}", adv.enemy_speed)),
+            (AdvancedField::EnemyAggression, format!("Enemy Aggression: x{:.2}", adv.enemy_aggression)),
+            (AdvancedField::SpawnDensity, format!("Spawn Density: x{:.2}", adv.spawn_density)),
+            (AdvancedField::PlayerLives, format!("Player Lives: {}", adv.player_lives)),
+            (AdvancedField::PlayerHandling, format!("Player Handling: x{:.2}", adv.player_handling)),
+        ];
+        for (i, (field, text)) in tuning.iter().enumerate() {
+            let selected = *field == advanced_field;
+            let color = if selected { YELLOW } else { LIGHTGRAY };
+            let prefix = if selected { "> " } else { "  " };
+            draw_text(&format!("{}{}", prefix, text), 60., y + 36. + i as f32 * 26., 20., color);
+        }
+        let preview_y = y + 36. + tuning.len() as f32 * 26. + 14.;
+        for (i, line) in difficulty_preview(&settings.difficulty).iter().enumerate() {
+            draw_text(line, 60., preview_y + i as f32 * 22., 18., SKYBLUE);
+        }
+    }
+}
+
+/// What a difficulty preset does, as lines for the settings screen.
+pub fn difficulty_preview(difficulty: &Difficulty) -> Vec<String> {
+    vec![
+        "Preset effects:".to_string(),
+        format!(
+            "  Enemy Speed x{:.2} | Spawn Rate x{:.2}",
+            difficulty.speed_multiplier(),
+            difficulty.spawn_multiplier()
+        ),
+        format!(
+            "  UFO Fires Every {:.1}s | UFO Aim Error {:.0} deg",
+            difficulty.ufo_fire_interval(),
+            difficulty.ufo_aim_jitter().to_degrees()
+        ),
+    ]
+}
+
+/// Draw an arrow at the screen edge pointing along `direction` (towards an incoming threat).
+pub fn draw_edge_arrow(anchor: Vec2, direction: Vec2, alpha: f32, bounds: Vec2, scheme: ColorScheme) {
+    let color = scheme.apply(Color::new(1.0, 0.2, 0.2, alpha));
+    // Keep the arrow fully on screen, a little in from the edge
+    let tip = vec2(anchor.x.clamp(12., bounds.x - 12.), anchor.y.clamp(12., bounds.y - 12.));
+    let base = tip - direction * 22.;
+    let side = vec2(-direction.y, direction.x) * 10.;
+    draw_triangle(tip, base + side, base - side, color);
+    draw_text("!", base.x - direction.x * 14. - 3., base.y - direction.y * 14. + 7., 22., color);
+}
+
+/// Draw a rival's ghost ship as a faint outline.
+pub fn draw_ghost(pos: Vec2, rotation: f32, sides: u8, radius: f32, scheme: ColorScheme) {
+    let color = scheme.apply(Color::new(0.6, 0.8, 1.0, 0.35));
+    draw_poly_lines(pos.x, pos.y, sides, radius, rotation * 180. / std::f32::consts::PI, 1.5, color);
+}
+
+/// Camera that maps the logical playfield onto the whole window, y pointing down like the HUD.
+///
+/// The rect is given bottom-up because `from_display_rect` flips y when drawing to the screen.
+pub fn world_camera(bounds: &World) -> Camera2D {
+    Camera2D::from_display_rect(Rect::new(0., bounds.height, bounds.width, -bounds.height))
+}
+
+/// Shield pickups and the ring around a shielded ship.
+const SHIELD_COLOR: Color = Color::new(0.3, 0.8, 1.0, 0.9);
+
+/// Draw the ship, asteroids, enemies, power-ups and bullets.
+///
+/// Positions are interpolated between the last two physics steps by `alpha`
+/// so motion stays smooth when the render rate differs from the physics rate.
+/// `clock` is gameplay time, so effects like the respawn blink freeze while paused.
+pub fn draw_world(world: &GameWorld, alpha: f32, clock: f64, scheme: ColorScheme, palette: Palette) {
+    let bounds = world.bounds.size();
+    for player in world.players.iter().filter(|p| p.active) {
+        let pos = lerp_wrapped(player.prev_pos, player.pos, alpha, bounds);
+        // Reach covers the shield ring and the engine flame
+        draw_wrapped(|player_pos| {
+            let mut player_draw_color = palette.ship_color(player.color);
+            if player.invulnerable {
+                 // Blink transparency effect
+                 player_draw_color.a = if (clock * 10.0) as i32 % 2 == 0 { 0.5 } else { 0.2 };
+            }
+            draw_poly_lines(player_pos.x, player_pos.y, player.sides, player.radius, player.rotation * 180. / std::f32::consts::PI, 2., scheme.apply(player_draw_color));
+            if player.shield_charges > 0 {
+                draw_circle_lines(player_pos.x, player_pos.y, player.radius + 8., 1.5, scheme.apply(SHIELD_COLOR));
+            }
+
+            // Calculate direction vectors for visual effects
+            let rotation_rad = player.rotation;
+            let forward = vec2(rotation_rad.cos(), rotation_rad.sin());
+
+            // --- 1. SHIP TIP HIGHLIGHT ---
+            // The "nose" is at the perimeter in the direction of rotation
+            let nose_pos = player_pos + forward * player.radius;
+            draw_circle(nose_pos.x, nose_pos.y, 3.0, scheme.apply(RED));
+
+            // --- 2. ENGINE FLAME ---
+            // Only draw if thrusting and the player is active
+            if player.thrusting && player.active {
+                // The flame comes out of the back
+                // We use a slight offset so it looks like it's coming from the engine, not the center
+                let flame_base = player_pos - forward * (player.radius * 0.8);
+
+                // Randomize flame length for flickering effect
+                let flickr_len = gen_range(10.0, 20.0);
+                let flame_tip = flame_base - forward * flickr_len;
+
+                // Draw a simple triangle for the flame
+                // We need two side points at the base to make it a triangle
+                // Right vector is forward rotated 90 degrees
+                let right = vec2(forward.y, -forward.x);
+                let side_width = 5.0;
+                let p1 = flame_base + right * side_width;
+                let p2 = flame_base - right * side_width;
+
+                draw_triangle(p1, p2, flame_tip, scheme.apply(ORANGE));
+                draw_triangle(p1 + forward*2., p2 + forward*2., flame_tip + forward*5., scheme.apply(YELLOW)); // Inner flame
+            }
+        }, pos, player.radius + 25., &world.bounds);
+    }
+
+    for a in world.asteroids.iter() {
+        let pos = lerp_wrapped(a.prev_pos, a.pos, alpha, bounds);
+        let draw_rock = |pos: Vec2| {
+            draw_poly_lines(pos.x, pos.y, a.sides, a.radius, a.rotation, 2., scheme.apply(palette.color(Role::Asteroid)));
+            if a.hp < a.max_hp {
+                draw_health_bar(pos - vec2(0., a.radius + 8.), a.hp as f32 / a.max_hp as f32, a.radius, scheme);
+            }
+        };
+        // Rocks still flying in from off-screen haven't started wrapping yet
+        if a.entering {
+            draw_rock(pos);
+        } else {
+            draw_wrapped(draw_rock, pos, a.radius + 10., &world.bounds);
+        }
+    }
+
+    for u in world.ufos.iter() {
+        let pos = lerp_wrapped(u.prev_pos, u.pos, alpha, bounds);
+        let color = scheme.apply(palette.color(Role::Ufo));
+        draw_wrapped(|pos| {
+            draw_poly_lines(pos.x, pos.y, 8, u.radius, 0., 2., color);
+            draw_line(pos.x - u.radius, pos.y, pos.x + u.radius, pos.y, 2., color);
+        }, pos, u.radius, &world.bounds);
+    }
+
+    for b in world.bosses.iter() {
+        let pos = lerp_wrapped(b.prev_pos, b.pos, alpha, bounds);
+        draw_poly_lines(pos.x, pos.y, 10, b.radius, 0., 3., scheme.apply(MAGENTA));
+        draw_poly_lines(pos.x, pos.y, 6, b.radius * 0.5, 0., 2., scheme.apply(MAGENTA));
+        draw_line(pos.x - b.radius, pos.y, pos.x + b.radius, pos.y, 2., scheme.apply(MAGENTA));
+        if b.phase == BossPhase::Firing {
+            // Glowing core while it is firing
+            draw_circle(pos.x, pos.y, b.radius * 0.25, scheme.apply(MAGENTA));
+        }
+        draw_text(&format!("{}/{}", b.hp, b.max_hp), pos.x - 15., pos.y - b.radius - 8., 18., scheme.apply(MAGENTA));
+        if b.hp < b.max_hp {
+            draw_health_bar(pos + vec2(0., b.radius + 10.), b.hp as f32 / b.max_hp as f32, b.radius * 1.5, scheme);
+        }
+    }
+
+    for p in world.powerups.iter() {
+        let pos = lerp_wrapped(p.prev_pos, p.pos, alpha, bounds);
+        match p.kind {
+            PowerUpKind::Shield => {
+                draw_poly_lines(pos.x, pos.y, 6, p.radius, 0., 2., scheme.apply(SHIELD_COLOR));
+                draw_circle(pos.x, pos.y, p.radius * 0.4, scheme.apply(SHIELD_COLOR));
+            }
+            PowerUpKind::Bomb => {
+                draw_circle_lines(pos.x, pos.y, p.radius, 2., scheme.apply(ORANGE));
+                draw_line(pos.x, pos.y - p.radius, pos.x + 4., pos.y - p.radius - 5., 2., scheme.apply(YELLOW));
+            }
+            PowerUpKind::Points => {
+                draw_poly_lines(pos.x, pos.y, 4, p.radius, 0., 2., scheme.apply(GOLD));
+                draw_circle(pos.x, pos.y, p.radius * 0.3, scheme.apply(GOLD));
+            }
+            PowerUpKind::Pierce => {
+                draw_circle_lines(pos.x, pos.y, p.radius, 2., scheme.apply(SKYBLUE));
+                draw_line(pos.x - p.radius, pos.y, pos.x + p.radius, pos.y, 2., scheme.apply(SKYBLUE));
+                draw_triangle(
+                    vec2(pos.x + p.radius + 4., pos.y),
+                    vec2(pos.x + p.radius - 2., pos.y - 4.),
+                    vec2(pos.x + p.radius - 2., pos.y + 4.),
+                    scheme.apply(SKYBLUE),
+                );
+            }
+        }
+    }
+
+    for b in world.bullets.iter() {
+        let pos = lerp_wrapped(b.prev_pos, b.pos, alpha, bounds);
+        let role = if matches!(b.owner, BulletOwner::Player(_)) { Role::PlayerBullet } else { Role::UfoBullet };
+        let color = palette.color(role);
+        draw_wrapped(|mirrored| {
+            // Afterimage: older points shrink and fade; stop where the bullet wrapped
+            let shift = mirrored - pos;
+            let mut last = pos;
+            for (age, point) in b.trail.iter().enumerate() {
+                if point.distance(last) > TRAIL_BREAK {
+                    break;
+                }
+                let fade = 1.0 - (age + 1) as f32 / (TRAIL_LEN + 1) as f32;
+                let mut faded = color;
+                faded.a = 0.6 * fade;
+                draw_circle(point.x + shift.x, point.y + shift.y, 0.5 + 1.5 * fade, scheme.apply(faded));
+                last = point;
+            }
+            draw_circle(mirrored.x, mirrored.y, 2., scheme.apply(color));
+        }, pos, 2., &world.bounds);
+    }
+}
+
+/// Offsets at which something of `radius` at `pos` is drawn so that, while it overlaps a
+/// playfield edge, it also shows poking in from the opposite side. Always includes
+/// `Vec2::ZERO` (the entity itself), plus up to three mirrors near an edge or corner.
+pub fn wrap_offsets(pos: Vec2, radius: f32, size: Vec2) -> Vec<Vec2> {
+    let mirror = |p: f32, extent: f32| {
+        if p < radius {
+            Some(extent)
+        } else if p > extent - radius {
+            Some(-extent)
+        } else {
+            None
+        }
+    };
+    let xs = [Some(0.0), mirror(pos.x, size.x)];
+    let ys = [Some(0.0), mirror(pos.y, size.y)];
+    xs.iter()
+        .flatten()
+        .flat_map(|x| ys.iter().flatten().map(move |y| vec2(*x, *y)))
+        .collect()
+}
+
+/// Calls `draw` at `pos` and at every mirrored position `wrap_offsets` asks for, so an
+/// entity crossing an edge of `world` is seen on both sides.
+pub fn draw_wrapped(mut draw: impl FnMut(Vec2), pos: Vec2, radius: f32, world: &World) {
+    for offset in wrap_offsets(pos, radius, world.size()) {
+        draw(pos + offset);
+    }
+}
+
+/// Beginner assist: outline every asteroid a ship's next shot would hit within `range`.
+pub fn draw_aim_assist(world: &GameWorld, alpha: f32, range: f32, scheme: ColorScheme) {
+    for player in world.players.iter().filter(|p| p.active) {
+        let forward = vec2(player.rotation.cos(), player.rotation.sin());
+        let nose = player.pos + forward * player.radius;
+        let end = nose + forward * range;
+        for a in world.asteroids.iter() {
+            if segment_circle_intersect(nose, end, a.pos, a.radius) {
+                let pos = lerp_wrapped(a.prev_pos, a.pos, alpha, world.bounds.size());
+                draw_poly_lines(pos.x, pos.y, a.sides, a.radius + 4., a.rotation, 1.5, scheme.apply(LIME));
+            }
+        }
+    }
+}
+
+/// Draw the mouse-mode aiming crosshair at `pos`.
+pub fn draw_crosshair(pos: Vec2, color: Color) {
+    let gap = 4.;
+    let arm = 10.;
+    draw_circle_lines(pos.x, pos.y, 8., 1.5, color);
+    draw_line(pos.x - gap - arm, pos.y, pos.x - gap, pos.y, 1.5, color);
+    draw_line(pos.x + gap, pos.y, pos.x + gap + arm, pos.y, 1.5, color);
+    draw_line(pos.x, pos.y - gap - arm, pos.x, pos.y - gap, 1.5, color);
+    draw_line(pos.x, pos.y + gap, pos.x, pos.y + gap + arm, 1.5, color);
+}
+
+/// Replaces most characters of `text` with static so it can't be read.
+fn garble(text: &str) -> String {
+    const GLYPHS: &[char] = &['#', '%', '&', '?', '/', '\\', '*', '@', '$'];
+    text.chars()
+        .map(|c| if c == ' ' || gen_range(0, 4) == 0 { c } else { GLYPHS[gen_range(0, GLYPHS.len())] })
+        .collect()
+}
+
+/// Lives readout: the team pool, or each ship's own count in separate-lives co-op.
+pub fn lives_label(lives: &Lives) -> String {
+    if lives.is_shared() || lives.counts().len() == 1 {
+        return format!("Lives: {}", lives.total());
+    }
+    let pools: Vec<String> = lives.counts().iter().enumerate().map(|(i, c)| format!("P{} {}", i + 1, c.max(&0))).collect();
+    format!("Lives: {}", pools.join(" | "))
+}
+
+/// Draw the top-left HUD (level or boss-rush heading, score, lives).
+///
+/// While `scrambled` (magnetic storm) the values jitter and turn to static,
+/// with a "SIGNAL LOST" banner explaining why.
+pub fn draw_hud(heading: &str, score: &ScoreState, lives: &Lives, run_time: f64, scrambled: bool, scheme: ColorScheme) {
+    // The combo readout only shows while a chain is running
+    let combo = if score.multiplier > 1 { format!("Combo x{}", score.multiplier) } else { String::new() };
+    let lines = [
+        (heading.to_string(), 20., 30., WHITE),
+        (format!("Score: {}", score.score), 20., 55., GREEN),
+        (combo, 160., 55., YELLOW),
+        (lives_label(lives), 20., 80., RED),
+        (format!("Time: {}", format_run_time(run_time)), 200., 80., LIGHTGRAY),
+    ];
+    for (text, x, y, color) in lines {
+        if scrambled {
+            let jitter = vec2(gen_range(-3., 3.), gen_range(-2., 2.));
+            draw_text(&garble(&text), x + jitter.x, y + jitter.y, 20., scheme.apply(Color::new(color.r, color.g, color.b, 0.5)));
+        } else {
+            draw_text(&text, x, y, 20., scheme.apply(color));
+        }
+    }
+    if scrambled {
+        let banner = "SIGNAL LOST";
+        let width = measure_text(banner, None, 32, 1.0).width;
+        let alpha = if (get_time() * 4.0) as i32 % 2 == 0 { 0.9 } else { 0.5 };
+        draw_text(banner, screen_width() / 2. - width / 2., 60., 32., scheme.apply(Color::new(1.0, 0.3, 0.3, alpha)));
+    }
+}
+
+/// Draw a fuel bar per ship under the lives counter; it turns red while the tank is locked out.
+pub fn draw_fuel_gauges(players: &[Player], scheme: ColorScheme) {
+    for (i, player) in players.iter().enumerate() {
+        let (x, y, w, h) = (20., 85. + i as f32 * 8., 100., 5.);
+        let fill = if player.fuel_locked { RED } else if i == 0 { ORANGE } else { player.color };
+        draw_rectangle_lines(x, y, w, h, 1., scheme.apply(GRAY));
+        let level = if player.max_fuel > 0.0 { player.fuel / player.max_fuel } else { 0.0 };
+        draw_rectangle(x + 1., y + 1., (w - 2.) * level.clamp(0.0, 1.0), h - 2., scheme.apply(fill));
+    }
+}
+
+/// Draw each ship's remaining bombs beside its fuel bar.
+pub fn draw_bomb_counts(players: &[Player], scheme: ColorScheme) {
+    for (i, player) in players.iter().enumerate() {
+        let color = if player.bombs == 0 { GRAY } else { ORANGE };
+        draw_text(&format!("Bombs: {}", player.bombs), 128., 91. + i as f32 * 8., 14., scheme.apply(color));
+    }
+}
+
+/// Draw the overdrive meter in the bottom-left corner; it pulses when ready.
+pub fn draw_overdrive_meter(charge: f32, scheme: ColorScheme) {
+    let (x, y, w, h) = (20., screen_height() - 30., 160., 10.);
+    let full = charge >= 1.0;
+    let label = if full { "OVERDRIVE READY (B)" } else { "OVERDRIVE" };
+    draw_text(label, x, y - 6., 16., scheme.apply(if full { SKYBLUE } else { GRAY }));
+    draw_rectangle_lines(x, y, w, h, 1., scheme.apply(GRAY));
+    let fill = if full && (get_time() * 4.0) as i32 % 2 == 0 { WHITE } else { SKYBLUE };
+    draw_rectangle(x + 1., y + 1., (w - 2.) * charge.clamp(0.0, 1.0), h - 2., scheme.apply(fill));
+}
+
+/// Bullet-time meter beside the overdrive meter; `ready` when a press would start it.
+pub fn draw_bullet_time_meter(meter: f32, active: bool, ready: bool, scheme: ColorScheme) {
+    let (x, y, w, h) = (200., screen_height() - 30., 120., 10.);
+    let label = if active { "BULLET TIME" } else if ready { "BULLET TIME (V)" } else { "RECHARGING" };
+    draw_text(label, x, y - 6., 16., scheme.apply(if active || ready { GOLD } else { GRAY }));
+    draw_rectangle_lines(x, y, w, h, 1., scheme.apply(GRAY));
+    draw_rectangle(x + 1., y + 1., (w - 2.) * meter.clamp(0.0, 1.0), h - 2., scheme.apply(if active { WHITE } else { GOLD }));
+}
+
+/// Health bar color at `fraction` of full health: green when full, through yellow, to red.
+pub fn health_color(fraction: f32) -> Color {
+    let f = fraction.clamp(0.0, 1.0);
+    Color::new(1.0 - f, f, 0.0, 1.0)
+}
+
+/// Small bar centred on `pos` showing `fraction` of health left, `width` pixels across.
+pub fn draw_health_bar(pos: Vec2, fraction: f32, width: f32, scheme: ColorScheme) {
+    let (x, y, h) = (pos.x - width / 2., pos.y - 2., 4.);
+    draw_rectangle(x, y, width, h, scheme.apply(Color::new(0.2, 0.2, 0.2, 0.8)));
+    draw_rectangle(x, y, width * fraction.clamp(0.0, 1.0), h, scheme.apply(health_color(fraction)));
+}
+
+/// Gap between trail points (world pixels) taken to mean the bullet wrapped across the screen.
+const TRAIL_BREAK: f32 = 60.;
+
+/// Half-width of the square area (in world pixels) around the ship covered by the radar.
+const RADAR_RANGE: f32 = 600.;
+/// Half-width of the radar box on screen.
+const RADAR_HALF_SIZE: f32 = 60.;
+
+/// Maps `target` onto a radar centred on `center`, returning the offset from the
+/// radar's middle (within +/-`half_size`) and whether the target is out of range.
+///
+/// Offsets take the shortest way across the screen wrap; out-of-range targets are
+/// pinned to the radar's edge in their direction.
+pub fn radar_point(center: Vec2, target: Vec2, world: &World, range: f32, half_size: f32) -> (Vec2, bool) {
+    let size = world.size();
+    let mut delta = target - center;
+    if delta.x.abs() > size.x / 2. { delta.x -= size.x * delta.x.signum(); }
+    if delta.y.abs() > size.y / 2. { delta.y -= size.y * delta.y.signum(); }
+    let scaled = delta / range;
+    let overshoot = scaled.x.abs().max(scaled.y.abs());
+    if overshoot > 1.0 {
+        (scaled / overshoot * half_size, true)
+    } else {
+        (scaled * half_size, false)
+    }
+}
+
+/// Draw a corner radar of the asteroids (gray) and UFOs (red) around ship `index`;
+/// each ship's radar sits to the left of the one before it.
+///
+/// Hazards beyond the radar's range show as hollow markers on its border.
+pub fn draw_radar(player: &Player, index: usize, asteroids: &[Asteroid], ufos: &[Ufo], world: &World, scheme: ColorScheme) {
+    let side = RADAR_HALF_SIZE * 2.;
+    let middle = vec2(
+        screen_width() - RADAR_HALF_SIZE - 20. - index as f32 * (side + 10.),
+        screen_height() - RADAR_HALF_SIZE - 20.,
+    );
+    draw_rectangle(middle.x - RADAR_HALF_SIZE, middle.y - RADAR_HALF_SIZE, side, side, Color::new(0., 0., 0., 0.5));
+    draw_rectangle_lines(middle.x - RADAR_HALF_SIZE, middle.y - RADAR_HALF_SIZE, side, side, 1., scheme.apply(GRAY));
+    draw_circle(middle.x, middle.y, 2., scheme.apply(player.color));
+
+    let blips = asteroids.iter().filter(|a| a.active).map(|a| (a.pos, GRAY))
+        .chain(ufos.iter().filter(|u| u.active).map(|u| (u.pos, RED)));
+    for (pos, color) in blips {
+        let (offset, outside) = radar_point(player.pos, pos, world, RADAR_RANGE, RADAR_HALF_SIZE);
+        let at = middle + offset;
+        if outside {
+            draw_circle_lines(at.x, at.y, 2.5, 1., scheme.apply(color));
+        } else {
+            draw_circle(at.x, at.y, 2., scheme.apply(color));
+        }
+    }
+}
+
+/// Darken or brighten everything drawn so far by the brightness setting.
+///
+/// Drawn after the playfield but before the HUD so text stays readable at any setting.
+pub fn draw_brightness_overlay(brightness: f32) {
+    let color = brightness_overlay(brightness);
+    if color.a > 0.0 {
+        draw_rectangle(0., 0., screen_width(), screen_height(), color);
+    }
+}
+
+/// Draw faint CRT scanlines over the whole screen (phosphor color schemes).
+///
+/// `flicker` adds a slow brightness wobble; pass false to keep the overlay static.
+pub fn draw_scanlines(flicker: bool) {
+    let mut alpha = 0.18;
+    if flicker {
+        alpha += 0.04 * (get_time() as f32 * 7.0).sin();
+    }
+    let color = Color::new(0., 0., 0., alpha);
+    let mut y = 0.;
+    while y < screen_height() {
+        draw_line(0., y, screen_width(), y, 1., color);
+        y += 3.;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_difficulty_preview_shows_preset_values() {
+        let hard = difficulty_preview(&Difficulty::Hard);
+        assert_eq!(hard[1], "  Enemy Speed x1.20 | Spawn Rate x1.30");
+        assert_eq!(hard[2], "  UFO Fires Every 1.4s | UFO Aim Error 2 deg");
+        let easy = difficulty_preview(&Difficulty::Easy);
+        assert_eq!(easy[1], "  Enemy Speed x0.85 | Spawn Rate x0.70");
+        assert_ne!(easy, hard);
+    }
+
+    #[test]
+    fn test_wrap_offsets_mirror_across_nearby_edges() {
+        let size = vec2(800., 600.);
+        // Well inside: drawn once
+        assert_eq!(wrap_offsets(vec2(400., 300.), 30., size), vec![Vec2::ZERO]);
+        // Straddling the left edge: also drawn past the right edge
+        assert_eq!(wrap_offsets(vec2(10., 300.), 30., size), vec![Vec2::ZERO, vec2(800., 0.)]);
+        assert_eq!(wrap_offsets(vec2(400., 590.), 30., size), vec![Vec2::ZERO, vec2(0., -600.)]);
+        // In a corner it shows in all four
+        let corner = wrap_offsets(vec2(790., 5.), 30., size);
+        assert_eq!(corner, vec![Vec2::ZERO, vec2(0., 600.), vec2(-800., 0.), vec2(-800., 600.)]);
+    }
+
+    #[test]
+    fn test_world_camera_keeps_the_origin_top_left() {
+        let camera = world_camera(&World::new(800., 600.));
+        // Clip space runs -1..1 with +y at the top of the window
+        let to_clip = |p: Vec2| camera.matrix().transform_point3(vec3(p.x, p.y, 0.)).truncate();
+        assert!(to_clip(vec2(0., 0.)).abs_diff_eq(vec2(-1., 1.), 1e-6));
+        assert!(to_clip(vec2(800., 600.)).abs_diff_eq(vec2(1., -1.), 1e-6));
+        assert!(to_clip(vec2(400., 150.)).abs_diff_eq(vec2(0., 0.5), 1e-6));
+    }
+
+    #[test]
+    fn test_health_color_fades_from_green_to_red() {
+        assert_eq!(health_color(1.0), Color::new(0.0, 1.0, 0.0, 1.0));
+        assert_eq!(health_color(0.5), Color::new(0.5, 0.5, 0.0, 1.0));
+        assert_eq!(health_color(0.0), Color::new(1.0, 0.0, 0.0, 1.0));
+        // Overkill and overheal stay at the ends
+        assert_eq!(health_color(-0.5), health_color(0.0));
+        assert_eq!(health_color(2.0), health_color(1.0));
+    }
+
+    #[test]
+    fn test_lives_label_shows_each_pool_in_separate_coop() {
+        assert_eq!(lives_label(&Lives::new(CoopLives::Shared, 3, 2)), "Lives: 3");
+        assert_eq!(lives_label(&Lives::from_counts(vec![2, -1])), "Lives: P1 2 | P2 0");
+    }
+
+    #[test]
+    fn test_radar_point_scales_offsets() {
+        let world = World::new(2000., 2000.);
+        let center = vec2(1000., 1000.);
+        assert_eq!(radar_point(center, center, &world, 600., 60.), (Vec2::ZERO, false));
+        assert_eq!(radar_point(center, vec2(1300., 1000.), &world, 600., 60.), (vec2(30., 0.), false));
+        assert_eq!(radar_point(center, vec2(1000., 400.), &world, 600., 60.), (vec2(0., -60.), false));
+    }
+
+    #[test]
+    fn test_radar_point_pins_far_targets_to_the_edge() {
+        let world = World::new(4000., 4000.);
+        let (offset, outside) = radar_point(vec2(1000., 1000.), vec2(2200., 1600.), &world, 600., 60.);
+        assert!(outside);
+        assert_eq!(offset, vec2(60., 30.));
+    }
 
-    draw_text(&format!("Volume: {:.2}", settings.volume), 60., 260., 24., WHITE);
-    draw_text(&format!("Difficulty: {}", difficulty), 60., 290., 24., WHITE);
-    draw_text(&format!("Show FPS: {}", if settings.show_fps { "On" } else { "Off" }), 60., 320., 24., WHITE);
+    #[test]
+    fn test_radar_point_looks_across_the_wrap() {
+        let world = World::new(800., 600.);
+        // 50px left of the ship once the screen edge is wrapped
+        let (offset, outside) = radar_point(vec2(20., 300.), vec2(770., 300.), &world, 600., 60.);
+        assert!(!outside);
+        assert_eq!(offset, vec2(-5., 0.));
+    }
 }
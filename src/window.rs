@@ -0,0 +1,131 @@
+use macroquad::logging::{info, warn};
+use macroquad::window::Conf;
+use std::fs;
+
+/// Optional window setup file, read once at launch.
+pub const WINDOW_CONFIG_PATH: &str = "window.cfg";
+/// Environment variable that overrides the window size for one launch, as `WIDTHxHEIGHT`.
+pub const RESOLUTION_ENV: &str = "ASTEROIDS_RESOLUTION";
+/// Smallest window the HUD and menus still fit in.
+pub const MIN_WINDOW_SIZE: (i32, i32) = (640, 480);
+
+/// Title, size and fullscreen mode of the game window.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowConfig {
+    pub title: String,
+    pub width: i32,
+    pub height: i32,
+    pub fullscreen: bool,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig { title: "Rust Asteroids".to_string(), width: 800, height: 600, fullscreen: false }
+    }
+}
+
+/// Reads a `WIDTHxHEIGHT` size such as `1280x720`; `None` if it isn't one or is below `MIN_WINDOW_SIZE`.
+pub fn parse_resolution(value: &str) -> Option<(i32, i32)> {
+    let (w, h) = value.trim().split_once(['x', 'X'])?;
+    let (w, h) = (w.trim().parse::<i32>().ok()?, h.trim().parse::<i32>().ok()?);
+    (w >= MIN_WINDOW_SIZE.0 && h >= MIN_WINDOW_SIZE.1).then_some((w, h))
+}
+
+impl WindowConfig {
+    pub fn from_str(input: &str) -> Self {
+        let mut config = WindowConfig::default();
+        for line in input.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else { continue };
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "title" => {
+                    if !value.is_empty() {
+                        config.title = value.to_string();
+                    }
+                }
+                "width" => match value.parse::<i32>() {
+                    Ok(w) if w >= MIN_WINDOW_SIZE.0 => config.width = w,
+                    _ => warn!("Invalid window width '{}'; using {}", value, config.width),
+                },
+                "height" => match value.parse::<i32>() {
+                    Ok(h) if h >= MIN_WINDOW_SIZE.1 => config.height = h,
+                    _ => warn!("Invalid window height '{}'; using {}", value, config.height),
+                },
+                "fullscreen" => {
+                    config.fullscreen = matches!(value, "1" | "true" | "True" | "TRUE");
+                }
+                _ => warn!("Unknown window setting '{}'", key),
+            }
+        }
+        config
+    }
+
+    /// Reads `path`, falling back to the defaults when it's missing.
+    pub fn load_from_file(path: &str) -> Self {
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                info!("Window config loaded from {}", path);
+                WindowConfig::from_str(&contents)
+            }
+            Err(_) => WindowConfig::default(),
+        }
+    }
+
+    /// Applies a `WIDTHxHEIGHT` override (from `RESOLUTION_ENV`), ignoring it if it doesn't parse.
+    pub fn with_resolution(mut self, value: Option<&str>) -> Self {
+        if let Some(value) = value {
+            match parse_resolution(value) {
+                Some((w, h)) => (self.width, self.height) = (w, h),
+                None => warn!("Ignoring {}='{}'; expected e.g. 1280x720", RESOLUTION_ENV, value),
+            }
+        }
+        self
+    }
+
+    pub fn to_conf(&self) -> Conf {
+        Conf {
+            window_title: self.title.clone(),
+            window_width: self.width,
+            window_height: self.height,
+            fullscreen: self.fullscreen,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_file_uses_defaults() {
+        assert_eq!(WindowConfig::load_from_file("no-such-window.cfg"), WindowConfig::default());
+        assert_eq!(WindowConfig::from_str(""), WindowConfig::default());
+    }
+
+    #[test]
+    fn test_partial_keys_keep_the_other_defaults() {
+        let config = WindowConfig::from_str("# big window\nwidth=1280\nfullscreen=1\n");
+        assert_eq!(config.width, 1280);
+        assert!(config.fullscreen);
+        assert_eq!(config.height, WindowConfig::default().height);
+        assert_eq!(config.title, "Rust Asteroids");
+        // Too small or not a number: the default stays
+        assert_eq!(WindowConfig::from_str("width=100\nheight=tall\n"), WindowConfig::default());
+    }
+
+    #[test]
+    fn test_resolution_override() {
+        assert_eq!(parse_resolution("1920x1080"), Some((1920, 1080)));
+        assert_eq!(parse_resolution(" 1024 X 768 "), Some((1024, 768)));
+        assert_eq!(parse_resolution("320x200"), None);
+        assert_eq!(parse_resolution("big"), None);
+        let config = WindowConfig::default().with_resolution(Some("1280x720"));
+        assert_eq!((config.width, config.height), (1280, 720));
+        assert_eq!(WindowConfig::default().with_resolution(Some("huge")), WindowConfig::default());
+    }
+}
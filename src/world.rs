@@ -0,0 +1,893 @@
+use macroquad::prelude::*;
+use crate::components::*;
+use crate::physics::{apply_gravity, apply_zones, check_collision, find_safe_spawn, hazard_clearance, GravityWell, resolve_elastic, ship_hits_circle, update_rotation, wrap_pos_checked, wrap_world, SpatialGrid, World, Zone};
+use crate::bomb::STARTING_BOMBS;
+use crate::boss::{advance_phase, BossPhase};
+use crate::level::split_asteroid;
+use crate::rng::{GameRng, ShapeSource};
+use crate::settings::CollisionResponse;
+
+/// Rate the physics simulation runs at.
+///
+/// Entity velocities are in pixels per 1/60 s step, whatever the real step length:
+/// `integrate` scales them by elapsed time, so motion doesn't depend on frame rate.
+pub const TARGET_FPS: f32 = 60.0;
+/// Duration of one physics step in seconds.
+pub const FIXED_DT: f32 = 1.0 / TARGET_FPS;
+/// Upper bound on physics steps per rendered frame, so a long stall can't spiral.
+pub const MAX_STEPS_PER_FRAME: u32 = 5;
+/// Longest frame time (seconds) the simulation will accept; anything longer is treated as this.
+pub const MAX_FRAME_DT: f32 = 1.0 / 20.0;
+/// Fraction of its speed the ship keeps after bouncing off an asteroid.
+pub const BOUNCE_SPEED_KEPT: f32 = 0.5;
+/// Seconds the ship's controls are dead after bouncing off an asteroid.
+pub const BOUNCE_STUN: f32 = 0.6;
+/// Horizontal gap between the two ships when a co-op team spawns.
+pub const COOP_SPAWN_GAP: f32 = 80.0;
+/// Most bullets one ship can have in flight, so holding fire can't flood the field.
+pub const MAX_PLAYER_BULLETS: usize = 8;
+/// Player bullet speed, in pixels per physics step.
+pub const PLAYER_BULLET_SPEED: f32 = 8.0;
+/// Seconds a player bullet lives.
+pub const PLAYER_BULLET_LIFETIME: f32 = 1.5;
+/// Seconds between auto-fire repeats; separate presses aren't throttled.
+pub const PLAYER_FIRE_COOLDOWN: f32 = 0.15;
+/// Extra asteroids a shot fired under a pierce pickup passes through.
+pub const PIERCE_SHOTS: u8 = 2;
+/// Cell size (pixels) of the spatial grid used for bullet collisions.
+pub const COLLISION_CELL_SIZE: f32 = 64.0;
+/// Collision radius of a bullet when it meets another bullet.
+pub const BULLET_RADIUS: f32 = 3.0;
+/// Seconds between hyperspace jumps.
+pub const HYPERSPACE_COOLDOWN: f32 = 1.0;
+
+/// Fixed-timestep accumulator.
+///
+/// Rendered frame times are added with `advance`, which reports how many whole
+/// physics steps to run; the leftover fraction is exposed as `alpha` for interpolation.
+pub struct FixedStep {
+    accumulator: f32,
+}
+
+impl FixedStep {
+    pub fn new() -> Self {
+        Self { accumulator: 0.0 }
+    }
+
+    /// Adds `frame_dt` seconds and returns the number of physics steps to run.
+    pub fn advance(&mut self, frame_dt: f32) -> u32 {
+        self.accumulator += frame_dt.max(0.0);
+        let mut steps = 0;
+        while self.accumulator >= FIXED_DT && steps < MAX_STEPS_PER_FRAME {
+            self.accumulator -= FIXED_DT;
+            steps += 1;
+        }
+        // Drop any backlog we couldn't work through
+        self.accumulator = self.accumulator.min(FIXED_DT);
+        steps
+    }
+
+    /// Fraction (0.0..1.0) of a step between the last physics state and the next.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / FIXED_DT).clamp(0.0, 1.0)
+    }
+}
+
+/// Moves `pos` by `vel` (pixels per 1/60 s step) over `dt` seconds.
+pub fn integrate(pos: Vec2, vel: Vec2, dt: f32) -> Vec2 {
+    pos + vel * dt * TARGET_FPS
+}
+
+/// Interpolates between the previous and current positions of an entity.
+///
+/// If the entity wrapped around the playfield during the step (it moved more than
+/// half the playfield on an axis), the current position is used instead of
+/// streaking across the screen.
+pub fn lerp_wrapped(prev: Vec2, curr: Vec2, alpha: f32, bounds: Vec2) -> Vec2 {
+    let delta = curr - prev;
+    if delta.x.abs() > bounds.x / 2. || delta.y.abs() > bounds.y / 2. {
+        return curr;
+    }
+    prev + delta * alpha
+}
+
+/// All simulated entities plus the state the movement step depends on.
+pub struct GameWorld {
+    /// Ships in play: the first player, plus a second one in co-op.
+    pub players: Vec<Player>,
+    pub bullets: Vec<Bullet>,
+    pub asteroids: Vec<Asteroid>,
+    pub ufos: Vec<Ufo>,
+    pub bosses: Vec<Boss>,
+    /// Pickups drifting in the field.
+    pub powerups: Vec<PowerUp>,
+    /// Playfield size in logical pixels.
+    pub bounds: World,
+    /// Space-weather zones of the current level.
+    pub zones: Vec<Zone>,
+    /// Ship velocity multiplier applied every step.
+    pub friction: f32,
+    /// Screen wraps after which an asteroid despawns (`None` = unlimited).
+    pub max_wraps: Option<u32>,
+    /// The current level's central star, if it has one.
+    pub gravity: Option<GravityWell>,
+}
+
+impl GameWorld {
+    pub fn new(player: Player, bounds: World, friction: f32) -> Self {
+        GameWorld {
+            players: vec![player],
+            bullets: Vec::new(),
+            asteroids: Vec::new(),
+            ufos: Vec::new(),
+            bosses: Vec::new(),
+            powerups: Vec::new(),
+            bounds,
+            zones: Vec::new(),
+            friction,
+            max_wraps: None,
+            gravity: None,
+        }
+    }
+
+    /// Puts the team back at the centre of the playfield, adding or dropping the
+    /// second ship so exactly two fly in co-op and one otherwise.
+    pub fn respawn_team(&mut self, coop: bool) {
+        let count = if coop { 2 } else { 1 };
+        self.players.truncate(count);
+        while self.players.len() < count {
+            let mut wingman = self.players[0].clone();
+            wingman.color = SKYBLUE;
+            self.players.push(wingman);
+        }
+        let center = self.bounds.center();
+        let offsets: &[f32] = if coop { &[-COOP_SPAWN_GAP / 2., COOP_SPAWN_GAP / 2.] } else { &[0.] };
+        for (player, offset) in self.players.iter_mut().zip(offsets) {
+            player.pos = center + vec2(*offset, 0.);
+            player.prev_pos = player.pos;
+            player.vel = vec2(0., 0.);
+            player.rotation = 0.;
+            player.active = true;
+            player.invulnerable = false;
+            player.invulnerable_timer = 0.;
+            player.thrusting = false;
+            player.stun_timer = 0.0;
+            player.fire_cooldown = 0.0;
+            player.hyperspace_cooldown = 0.0;
+            player.fuel = player.max_fuel;
+            player.fuel_locked = false;
+        }
+    }
+
+    /// Gives every ship the hull drawn in design mode; player one also takes its color
+    /// (the co-op wingman keeps its own so the two stay apart).
+    pub fn apply_ship_design(&mut self, sides: u8, radius: f32, color: Color) {
+        for player in self.players.iter_mut() {
+            player.sides = sides;
+            player.radius = radius;
+        }
+        self.players[0].color = color;
+    }
+
+    /// Moves any ship left in a crowded spot (e.g. by a freshly loaded level) to open space.
+    pub fn move_team_to_safety(&mut self, min_clearance: f32) {
+        for player in self.players.iter_mut() {
+            if hazard_clearance(player.pos, &self.asteroids, &self.ufos, &self.bounds) < min_clearance {
+                player.pos = find_safe_spawn(&self.asteroids, &self.ufos, &self.bounds, min_clearance);
+                player.prev_pos = player.pos;
+            }
+        }
+    }
+
+    /// Drops pickups in flight and every ship's shield charges, and refills the
+    /// bomb racks to `STARTING_BOMBS`, for a fresh run.
+    pub fn reset_powerups(&mut self) {
+        self.powerups.clear();
+        for player in self.players.iter_mut() {
+            player.shield_charges = 0;
+            player.bombs = STARTING_BOMBS;
+        }
+    }
+
+    /// Advances entity motion and timers by `dt` seconds.
+    ///
+    /// Previous positions are recorded first so the renderer can interpolate.
+    /// `dt` is clamped to `MAX_FRAME_DT` so a stall can't fling entities across the screen.
+    pub fn update_world(&mut self, dt: f32) {
+        let dt = dt.min(MAX_FRAME_DT);
+        let ticks = dt * TARGET_FPS;
+        let (w, h) = (self.bounds.width, self.bounds.height);
+        let size = self.bounds.size();
+
+        // Player
+        for player in self.players.iter_mut() {
+            player.prev_pos = player.pos;
+            player.pos = integrate(player.pos, player.vel, dt);
+            player.vel *= self.friction.powf(ticks);
+            player.vel = apply_zones(player.pos, player.vel, &self.zones, size);
+            if let Some(well) = &self.gravity {
+                player.vel = apply_gravity(player.pos, player.vel, well, size, dt);
+            }
+            player.pos = wrap_world(player.pos, &self.bounds);
+        }
+
+        // Bullets
+        for bullet in self.bullets.iter_mut() {
+            bullet.trail.push(bullet.pos);
+            bullet.prev_pos = bullet.pos;
+            bullet.pos = integrate(bullet.pos, bullet.vel, dt);
+            bullet.lifetime -= dt;
+            if bullet.lifetime <= 0. { bullet.active = false; }
+            bullet.pos = wrap_world(bullet.pos, &self.bounds);
+        }
+
+        // Asteroids (entering ones fly in from off-screen before they start wrapping)
+        for asteroid in self.asteroids.iter_mut() {
+            asteroid.prev_pos = asteroid.pos;
+            asteroid.pos = integrate(asteroid.pos, asteroid.vel, dt);
+            asteroid.rotation = update_rotation(asteroid.rotation, asteroid.spin, dt);
+            asteroid.vel = apply_zones(asteroid.pos, asteroid.vel, &self.zones, size);
+            if let Some(well) = &self.gravity {
+                asteroid.vel = apply_gravity(asteroid.pos, asteroid.vel, well, size, dt);
+            }
+            if asteroid.entering {
+                let r = asteroid.radius;
+                let p = asteroid.pos;
+                let inside = p.x >= r && p.x <= w - r && p.y >= r && p.y <= h - r;
+                // Knocked off course (e.g. by a push zone): give up and wrap normally
+                let strayed = p.x < -3. * r || p.x > w + 3. * r || p.y < -3. * r || p.y > h + 3. * r;
+                if inside || strayed {
+                    asteroid.entering = false;
+                }
+            } else {
+                let (pos, wrapped) = wrap_pos_checked(asteroid.pos, w, h);
+                asteroid.pos = pos;
+                if wrapped {
+                    asteroid.wrap_count += 1;
+                    // A straggler that keeps circling would stall the level; retire it
+                    if self.max_wraps.is_some_and(|max| asteroid.wrap_count > max) {
+                        asteroid.active = false;
+                    }
+                }
+            }
+        }
+
+        // UFOs
+        for ufo in self.ufos.iter_mut() {
+            ufo.prev_pos = ufo.pos;
+            ufo.pos = integrate(ufo.pos, ufo.vel, dt);
+            if let Some(well) = &self.gravity {
+                ufo.vel = apply_gravity(ufo.pos, ufo.vel, well, size, dt);
+            }
+            ufo.pos = wrap_world(ufo.pos, &self.bounds);
+            ufo.change_dir_timer += dt;
+            ufo.shoot_timer += dt;
+        }
+
+        // Power-ups drift and wrap
+        for powerup in self.powerups.iter_mut() {
+            powerup.prev_pos = powerup.pos;
+            powerup.pos = integrate(powerup.pos, powerup.vel, dt);
+            powerup.pos = wrap_world(powerup.pos, &self.bounds);
+        }
+
+        // Bosses sweep side to side, then hold still to fire
+        for boss in self.bosses.iter_mut() {
+            boss.prev_pos = boss.pos;
+            let (phase, phase_timer) = advance_phase(boss.phase, boss.phase_timer, dt);
+            if phase != boss.phase {
+                boss.shoot_timer = 0.;
+            }
+            boss.phase = phase;
+            boss.phase_timer = phase_timer;
+            match boss.phase {
+                BossPhase::Sweeping => {
+                    boss.pos = integrate(boss.pos, boss.vel, dt);
+                    if (boss.pos.x < boss.radius && boss.vel.x < 0.) || (boss.pos.x > w - boss.radius && boss.vel.x > 0.) {
+                        boss.vel.x = -boss.vel.x;
+                    }
+                }
+                BossPhase::Firing => boss.shoot_timer += dt,
+            }
+        }
+    }
+}
+
+/// Position of the ship in play closest to `pos`; enemies aim at whoever is nearest.
+pub fn nearest_player_pos(players: &[Player], pos: Vec2) -> Vec2 {
+    players
+        .iter()
+        .filter(|p| p.active)
+        .map(|p| p.pos)
+        .min_by(|a, b| a.distance_squared(pos).total_cmp(&b.distance_squared(pos)))
+        .unwrap_or(pos)
+}
+
+/// Where a UFO at `ufo_pos` shoots. One shot in three (`roll == 0` out of 0..3) goes at an
+/// asteroid; otherwise it locks onto the nearest ship that can be hurt. Respawning,
+/// invulnerable ships are never targeted: the UFO picks on an asteroid instead, or holds
+/// fire (`None`) when there's nothing else to shoot at.
+pub fn choose_ufo_target(players: &[Player], ufo_pos: Vec2, asteroids: &[Asteroid], roll: u32) -> Option<Vec2> {
+    let asteroid = asteroids.iter().find(|a| a.active).map(|a| a.pos);
+    if roll == 0 && asteroid.is_some() {
+        return asteroid;
+    }
+    players
+        .iter()
+        .filter(|p| p.active && !p.invulnerable)
+        .map(|p| p.pos)
+        .min_by(|a, b| a.distance_squared(ufo_pos).total_cmp(&b.distance_squared(ufo_pos)))
+        .or(asteroid)
+}
+
+/// Turns `dir` by up to `max_jitter` radians; `roll` in -1.0..=1.0 picks how far and which way.
+pub fn jitter_aim(dir: Vec2, max_jitter: f32, roll: f32) -> Vec2 {
+    Vec2::from_angle(roll.clamp(-1.0, 1.0) * max_jitter).rotate(dir)
+}
+
+/// Whether a ship may shoot: fewer than `MAX_PLAYER_BULLETS` of its shots are
+/// still in flight and, for an auto-fire `repeat`, its cooldown has run out.
+pub fn can_fire(cooldown: f32, bullets_in_flight: usize, repeat: bool) -> bool {
+    (!repeat || cooldown <= 0.0) && bullets_in_flight < MAX_PLAYER_BULLETS
+}
+
+/// Fires a shot from the nose of `player` (ship `index`) if `can_fire` allows it,
+/// restarting its cooldown; it pierces while the ship's pierce pickup lasts.
+/// Returns where the shot left the ship.
+pub fn fire_bullet(player: &mut Player, index: usize, bullets: &mut Vec<Bullet>, repeat: bool) -> Option<Vec2> {
+    let in_flight = bullets.iter().filter(|b| b.active && b.owner == BulletOwner::Player(index)).count();
+    if !can_fire(player.fire_cooldown, in_flight, repeat) {
+        return None;
+    }
+    player.fire_cooldown = PLAYER_FIRE_COOLDOWN;
+    let direction = vec2(player.rotation.cos(), player.rotation.sin());
+    let muzzle = player.pos + direction * player.radius;
+    bullets.push(Bullet {
+        pierce: if player.pierce_timer > 0.0 { PIERCE_SHOTS } else { 0 },
+        ..Bullet::new(muzzle, direction * PLAYER_BULLET_SPEED, PLAYER_BULLET_LIFETIME, BulletOwner::Player(index))
+    });
+    Some(muzzle)
+}
+
+/// Whether a ship's hyperspace drive has recharged since its last jump.
+pub fn can_hyperspace(cooldown: f32) -> bool {
+    cooldown <= 0.0
+}
+
+/// Whether a jump goes wrong; `roll` is uniform in 0.0..1.0.
+pub fn is_misjump(chance: f32, roll: f32) -> bool {
+    roll < chance
+}
+
+/// Player and UFO bullets that touch destroy each other.
+///
+/// Each bullet cancels at most one other. Returns the midpoint of every pair
+/// for the spark effect.
+pub fn cancel_bullets(bullets: &mut [Bullet]) -> Vec<Vec2> {
+    let mut sparks = Vec::new();
+    for i in 0..bullets.len() {
+        if !bullets[i].active || bullets[i].owner != BulletOwner::Ufo { continue; }
+        for j in 0..bullets.len() {
+            let (ufo_shot, player_shot) = (&bullets[i], &bullets[j]);
+            if !player_shot.active || !matches!(player_shot.owner, BulletOwner::Player(_)) { continue; }
+            if !check_collision(ufo_shot.pos, BULLET_RADIUS, player_shot.pos, BULLET_RADIUS) { continue; }
+            sparks.push((ufo_shot.pos + player_shot.pos) / 2.);
+            bullets[i].active = false;
+            bullets[j].active = false;
+            break;
+        }
+    }
+    sparks
+}
+
+/// Whether a bullet from `owner` can hit the player at index `target`.
+///
+/// Enemy bullets always can; a player's own shots never do, and a teammate's
+/// only with friendly fire on.
+pub fn bullet_hits_player(owner: BulletOwner, target: usize, friendly_fire: bool) -> bool {
+    match owner {
+        BulletOwner::Ufo => true,
+        BulletOwner::Player(shooter) => friendly_fire && shooter != target,
+    }
+}
+
+/// Result of the ship touching an asteroid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlayerImpact {
+    /// Nothing to do (bodies already moving apart).
+    None,
+    /// The ship is destroyed.
+    Lethal,
+    /// The ship bounced off and should be briefly stunned.
+    Bounced,
+}
+
+impl PlayerImpact {
+    pub fn lives_lost(&self) -> i32 {
+        match self {
+            PlayerImpact::Lethal => 1,
+            PlayerImpact::None | PlayerImpact::Bounced => 0,
+        }
+    }
+}
+
+/// Applies the configured collision response to a ship touching an asteroid.
+///
+/// In `Bounce` mode both are resolved as an elastic collision and the ship then
+/// loses part of its speed. In `Lethal` mode neither is changed here; the caller
+/// handles the lost life and the asteroid's destruction.
+pub fn player_asteroid_impact(response: CollisionResponse, player: &mut Player, asteroid: &mut Asteroid) -> PlayerImpact {
+    match response {
+        CollisionResponse::Lethal => PlayerImpact::Lethal,
+        CollisionResponse::Bounce => {
+            let mut ship = player.body();
+            let mut rock = asteroid.body();
+            if !resolve_elastic(&mut ship, &mut rock) {
+                return PlayerImpact::None;
+            }
+            player.pos = ship.pos;
+            player.vel = ship.vel * BOUNCE_SPEED_KEPT;
+            asteroid.pos = rock.pos;
+            asteroid.vel = rock.vel;
+            PlayerImpact::Bounced
+        }
+    }
+}
+
+/// A ship touching an asteroid this frame, with the response already applied.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AsteroidContact {
+    /// Index into `GameWorld::players`.
+    pub player: usize,
+    /// Index into `GameWorld::asteroids`.
+    pub asteroid: usize,
+    pub impact: PlayerImpact,
+}
+
+/// A bullet striking an asteroid this frame, with the hit already applied.
+#[derive(Clone)]
+pub struct AsteroidShot {
+    pub owner: BulletOwner,
+    /// Where the bullet was when it struck.
+    pub bullet_pos: Vec2,
+    /// Index into `GameWorld::asteroids`.
+    pub asteroid: usize,
+    /// The hit broke the asteroid (it's now inactive) rather than chipping it.
+    pub destroyed: bool,
+    /// Pieces a broken asteroid split into, for the caller to add to the field.
+    pub fragments: Vec<Asteroid>,
+}
+
+/// Resolves every bullet-vs-asteroid hit for one frame: each asteroid a bullet
+/// touches loses a hit point, nearest first, and a broken one is deactivated and
+/// split. A bullet stops at the first asteroid unless it has `pierce` left, which
+/// each further rock uses up; a piercing bullet never strikes the same rock, or
+/// the fragments it broke into, twice. The caller applies score, effects and the fragments.
+pub fn shoot_asteroids(bullets: &mut [Bullet], asteroids: &mut [Asteroid], shapes: &mut ShapeSource, rng: &mut GameRng) -> Vec<AsteroidShot> {
+    // Bucket the asteroids once so each bullet only tests the ones around it
+    let mut grid = SpatialGrid::new(COLLISION_CELL_SIZE);
+    for (id, asteroid) in asteroids.iter().enumerate() {
+        if asteroid.active {
+            grid.insert(id, asteroid.pos, asteroid.radius);
+        }
+    }
+    let mut shots = Vec::new();
+    for bullet in bullets.iter_mut().filter(|b| b.active) {
+        let mut touching: Vec<usize> = grid
+            .query_near(bullet.pos, 0.)
+            .filter(|&id| asteroids[id].active && !bullet.has_struck(asteroids[id].id))
+            .filter(|&id| check_collision(bullet.pos, 0., asteroids[id].pos, asteroids[id].radius))
+            .collect();
+        touching.sort_by(|&a, &b| {
+            let (da, db) = (asteroids[a].pos.distance_squared(bullet.prev_pos), asteroids[b].pos.distance_squared(bullet.prev_pos));
+            da.total_cmp(&db)
+        });
+        for id in touching {
+            let asteroid = &mut asteroids[id];
+            if bullet.pierce == 0 {
+                bullet.active = false;
+            } else {
+                bullet.pierce -= 1;
+                bullet.record_strike(asteroid.id);
+            }
+            let destroyed = asteroid.take_hit();
+            let fragments = if destroyed {
+                asteroid.active = false;
+                split_asteroid(asteroid, shapes, rng)
+            } else {
+                Vec::new()
+            };
+            shots.push(AsteroidShot { owner: bullet.owner, bullet_pos: bullet.pos, asteroid: id, destroyed, fragments });
+            if !bullet.active {
+                break;
+            }
+        }
+    }
+    shots
+}
+
+/// Resolves every ship-vs-asteroid contact for one frame.
+///
+/// `hit_this_frame` has one flag per player and is shared with the frame's other
+/// damage checks: a ship already hit is skipped, and a lethal contact sets its flag
+/// and destroys the asteroid, so overlapping several rocks costs a single life.
+/// Bounces don't count as hits. The caller applies lives, effects and debris.
+pub fn player_asteroid_collisions(
+    response: CollisionResponse,
+    players: &mut [Player],
+    asteroids: &mut [Asteroid],
+    hit_this_frame: &mut [bool],
+) -> Vec<AsteroidContact> {
+    let mut contacts = Vec::new();
+    for (asteroid_idx, asteroid) in asteroids.iter_mut().enumerate() {
+        for (player_idx, player) in players.iter_mut().enumerate() {
+            if !asteroid.active || asteroid.entering { break; }
+            if hit_this_frame[player_idx] || !player.active || player.invulnerable { continue; }
+            if !ship_hits_circle(player, asteroid.pos, asteroid.radius) { continue; }
+            let impact = player_asteroid_impact(response, player, asteroid);
+            if impact == PlayerImpact::None { continue; }
+            if impact == PlayerImpact::Lethal {
+                hit_this_frame[player_idx] = true;
+                asteroid.active = false;
+            }
+            contacts.push(AsteroidContact { player: player_idx, asteroid: asteroid_idx, impact });
+        }
+    }
+    contacts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{rock, test_world};
+
+    fn test_player() -> Player {
+        Player::new(vec2(400., 300.))
+    }
+
+    #[test]
+    fn test_fixed_step_accumulates() {
+        let mut step = FixedStep::new();
+        assert_eq!(step.advance(FIXED_DT * 0.5), 0);
+        assert!((step.alpha() - 0.5).abs() < 1e-4);
+        assert_eq!(step.advance(FIXED_DT * 0.75), 1);
+        assert!((step.alpha() - 0.25).abs() < 1e-3);
+        assert_eq!(step.advance(FIXED_DT * 2.0), 2);
+    }
+
+    #[test]
+    fn test_fixed_step_caps_steps() {
+        let mut step = FixedStep::new();
+        assert_eq!(step.advance(10.0), MAX_STEPS_PER_FRAME);
+        assert!(step.alpha() <= 1.0);
+    }
+
+    #[test]
+    fn test_integrate_is_frame_rate_independent() {
+        let (start, vel) = (vec2(10., 20.), vec2(3., -1.5));
+        // One second of motion, however it is sliced
+        for steps in [30, 60, 144, 240] {
+            let dt = 1.0 / steps as f32;
+            let mut pos = start;
+            for _ in 0..steps {
+                pos = integrate(pos, vel, dt);
+            }
+            assert!(pos.distance(start + vel * TARGET_FPS) < 1e-3, "{} steps ended at {}", steps, pos);
+        }
+        // One fixed step moves by exactly one velocity
+        assert_eq!(integrate(start, vel, FIXED_DT), start + vel);
+    }
+
+    #[test]
+    fn test_lerp_wrapped() {
+        let bounds = vec2(800., 600.);
+        assert_eq!(lerp_wrapped(vec2(0., 0.), vec2(10., 0.), 0.5, bounds), vec2(5., 0.));
+        // Wrapped from the right edge to the left: snap to current
+        assert_eq!(lerp_wrapped(vec2(799., 10.), vec2(1., 10.), 0.5, bounds), vec2(1., 10.));
+    }
+
+    #[test]
+    fn test_update_world_records_previous_positions() {
+        let mut world = test_world();
+        world.asteroids.push(rock(vec2(100., 100.), vec2(2., 0.), 30.));
+        world.update_world(FIXED_DT);
+        assert_eq!(world.asteroids[0].prev_pos, vec2(100., 100.));
+        assert!((world.asteroids[0].pos.x - 102.).abs() < 1e-4);
+        let mid = lerp_wrapped(world.asteroids[0].prev_pos, world.asteroids[0].pos, 0.5, world.bounds.size());
+        assert!((mid.x - 101.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_update_world_wraps_at_world_size() {
+        let mut world = GameWorld::new(test_player(), World::new(320., 200.), 1.0);
+        world.players[0].pos = vec2(318., 100.);
+        world.players[0].vel = vec2(4., 0.);
+        world.update_world(FIXED_DT);
+        assert_eq!(world.players[0].pos, vec2(0., 100.));
+    }
+
+    #[test]
+    fn test_update_world_clamps_large_dt() {
+        let mut world = test_world();
+        world.players[0].vel = vec2(2., 0.);
+        world.update_world(2.0);
+        let moved = world.players[0].pos.x - 400.;
+        let max_moved = 2. * MAX_FRAME_DT * TARGET_FPS;
+        assert!((moved - max_moved).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_bounce_changes_velocity_without_losing_a_life() {
+        let mut player = test_player();
+        player.vel = vec2(3., 0.);
+        let mut asteroid = rock(vec2(440., 300.), vec2(-1., 0.), 30.);
+        let mut lives = 3;
+
+        let impact = player_asteroid_impact(CollisionResponse::Bounce, &mut player, &mut asteroid);
+        lives -= impact.lives_lost();
+        assert_eq!(impact, PlayerImpact::Bounced);
+        assert_eq!(lives, 3);
+        // Knocked back the way it came, and no longer overlapping
+        assert!(player.vel.x < 0.);
+        assert!(player.pos.distance(asteroid.pos) >= player.radius + asteroid.radius - 1e-3);
+        assert!(asteroid.active);
+
+        let impact = player_asteroid_impact(CollisionResponse::Lethal, &mut player, &mut asteroid);
+        lives -= impact.lives_lost();
+        assert_eq!(lives, 2);
+    }
+
+    #[test]
+    fn test_asteroid_despawns_after_max_wraps() {
+        let edge_rock = || rock(vec2(795., 100.), vec2(10., 0.), 20.);
+        // Each step carries the rock over the right edge; put it back there afterwards
+        let wrap_once = |world: &mut GameWorld| {
+            world.update_world(FIXED_DT);
+            world.asteroids[0].pos.x = 795.;
+        };
+
+        let mut world = test_world();
+        world.max_wraps = Some(2);
+        world.asteroids.push(edge_rock());
+        wrap_once(&mut world);
+        wrap_once(&mut world);
+        assert_eq!(world.asteroids[0].wrap_count, 2);
+        assert!(world.asteroids[0].active);
+        wrap_once(&mut world);
+        assert!(!world.asteroids[0].active);
+
+        // Unlimited by default
+        let mut world = test_world();
+        world.asteroids.push(edge_rock());
+        for _ in 0..5 {
+            wrap_once(&mut world);
+        }
+        assert_eq!(world.asteroids[0].wrap_count, 5);
+        assert!(world.asteroids[0].active);
+    }
+
+    #[test]
+    fn test_coop_players_move_independently() {
+        let mut world = test_world();
+        world.respawn_team(true);
+        assert_eq!(world.players.len(), 2);
+        assert_eq!(world.players[1].pos.x - world.players[0].pos.x, COOP_SPAWN_GAP);
+
+        world.players[0].vel = vec2(2., 0.);
+        world.players[1].vel = vec2(0., -3.);
+        world.update_world(FIXED_DT);
+        assert_eq!(world.players[0].pos, vec2(362., 300.));
+        assert_eq!(world.players[1].pos, vec2(440., 297.));
+
+        world.respawn_team(false);
+        assert_eq!(world.players.len(), 1);
+        assert_eq!(world.players[0].pos, vec2(400., 300.));
+    }
+
+    #[test]
+    fn test_move_team_to_safety_only_moves_crowded_ships() {
+        let mut world = test_world();
+        world.respawn_team(true);
+        let left = world.players[0].pos;
+        world.asteroids.push(rock(world.players[1].pos, Vec2::ZERO, 30.));
+        world.move_team_to_safety(20.);
+        assert_eq!(world.players[0].pos, left);
+        assert!(hazard_clearance(world.players[1].pos, &world.asteroids, &world.ufos, &world.bounds) >= 20.);
+    }
+
+    #[test]
+    fn test_jitter_aim_stays_within_bound() {
+        let dir = vec2(1.0, 0.0);
+        for roll in [-2.0, -1.0, -0.5, 0.0, 0.3, 1.0, 2.0] {
+            let aimed = jitter_aim(dir, 0.35, roll);
+            assert!((aimed.length() - 1.0).abs() < 1e-5);
+            assert!(dir.angle_between(aimed).abs() <= 0.35 + 1e-5);
+        }
+        assert!((jitter_aim(dir, 0.35, 1.0).to_angle() - 0.35).abs() < 1e-5);
+        assert_eq!(jitter_aim(dir, 0.35, 0.0), dir);
+    }
+
+    #[test]
+    fn test_can_fire_waits_for_cooldown_and_bullet_cap() {
+        assert!(can_fire(0.0, 0, true));
+        assert!(!can_fire(0.05, 0, true));
+        assert!(can_fire(-0.01, MAX_PLAYER_BULLETS - 1, true));
+        assert!(!can_fire(0.0, MAX_PLAYER_BULLETS, true));
+        // Fresh presses ignore the auto-fire cadence but not the bullet cap
+        assert!(can_fire(0.05, 0, false));
+        assert!(!can_fire(0.05, MAX_PLAYER_BULLETS, false));
+    }
+
+    #[test]
+    fn test_hyperspace_cooldown_and_misjump_roll() {
+        assert!(can_hyperspace(0.0));
+        assert!(!can_hyperspace(HYPERSPACE_COOLDOWN));
+        assert!(!can_hyperspace(0.01));
+        assert!(is_misjump(0.05, 0.0));
+        assert!(is_misjump(0.05, 0.049));
+        assert!(!is_misjump(0.05, 0.05));
+        assert!(!is_misjump(0.0, 0.0));
+    }
+
+    fn bullet(x: f32, owner: BulletOwner) -> Bullet {
+        Bullet::new(vec2(x, 100.), Vec2::ZERO, 1.0, owner)
+    }
+
+    fn still_rock(x: f32, radius: f32, hp: u8) -> Asteroid {
+        Asteroid { hp, max_hp: hp, ..rock(vec2(x, 100.), Vec2::ZERO, radius) }
+    }
+
+    /// Walks `bullet` along y=100 from `from` to `to` in 5 px steps, resolving hits at each.
+    fn sweep_bullet(bullet: &mut Bullet, asteroids: &mut [Asteroid], from: f32, to: f32) -> Vec<AsteroidShot> {
+        let (mut shapes, mut rng) = (ShapeSource::new(None), GameRng::new(1566));
+        let mut shots = Vec::new();
+        let mut x = from;
+        while x <= to && bullet.active {
+            bullet.prev_pos = bullet.pos;
+            bullet.pos = vec2(x, 100.);
+            shots.extend(shoot_asteroids(std::slice::from_mut(bullet), asteroids, &mut shapes, &mut rng));
+            x += 5.;
+        }
+        shots
+    }
+
+    #[test]
+    fn test_piercing_bullet_breaks_up_to_pierce_plus_one_rocks() {
+        let mut rocks: Vec<Asteroid> = [100., 130., 160., 190.].into_iter().map(|x| still_rock(x, 10., 1)).collect();
+        let mut shot = bullet(80., BulletOwner::Player(0));
+        shot.pierce = 2;
+        let shots = sweep_bullet(&mut shot, &mut rocks, 80., 220.);
+
+        assert_eq!(shots.iter().map(|s| s.asteroid).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert!(shots.iter().all(|s| s.destroyed));
+        assert!(!shot.active);
+        assert!(rocks[3].active);
+
+        // Without pierce only the first rock goes
+        let mut rocks: Vec<Asteroid> = [100., 130.].into_iter().map(|x| still_rock(x, 10., 1)).collect();
+        let mut plain = bullet(80., BulletOwner::Player(0));
+        assert_eq!(sweep_bullet(&mut plain, &mut rocks, 80., 160.).len(), 1);
+        assert!(rocks[1].active);
+    }
+
+    #[test]
+    fn test_piercing_bullet_chips_a_rock_only_once_on_the_way_through() {
+        let mut rocks = vec![still_rock(150., 30., 3), still_rock(220., 10., 1)];
+        let mut shot = bullet(100., BulletOwner::Player(0));
+        shot.pierce = 1;
+        let shots = sweep_bullet(&mut shot, &mut rocks, 100., 260.);
+
+        assert_eq!(shots.len(), 2);
+        assert!(!shots[0].destroyed && shots[1].destroyed);
+        assert_eq!(rocks[0].hp, 2);
+        assert!(!shot.active);
+    }
+
+    #[test]
+    fn test_piercing_bullet_chasing_a_moving_rock_strikes_it_once() {
+        let (mut shapes, mut rng) = (ShapeSource::new(None), GameRng::new(1566));
+        let mut rocks = vec![Asteroid { vel: vec2(4., 0.), ..still_rock(150., 30., 3) }];
+        let mut shot = bullet(100., BulletOwner::Player(0));
+        shot.pierce = 2;
+        let mut hits = 0;
+        // The bullet gains only a pixel a step, so it spends many steps inside the rock
+        for _ in 0..150 {
+            shot.prev_pos = shot.pos;
+            shot.pos.x += 5.;
+            rocks[0].pos.x += 4.;
+            hits += shoot_asteroids(std::slice::from_mut(&mut shot), &mut rocks, &mut shapes, &mut rng).len();
+        }
+        assert_eq!(hits, 1);
+        assert_eq!(rocks[0].hp, 2);
+        assert_eq!(shot.pierce, 1);
+
+        // Fragments of a rock it broke are passed through too
+        let mut rocks = vec![still_rock(150., 20., 1)];
+        let mut shot = bullet(150., BulletOwner::Player(0));
+        shot.pierce = 2;
+        let shots = shoot_asteroids(std::slice::from_mut(&mut shot), &mut rocks, &mut shapes, &mut rng);
+        let mut fragments: Vec<Asteroid> = shots.into_iter().flat_map(|s| s.fragments).collect();
+        assert!(!fragments.is_empty());
+        assert!(shoot_asteroids(std::slice::from_mut(&mut shot), &mut fragments, &mut shapes, &mut rng).is_empty());
+    }
+
+    #[test]
+    fn test_player_and_ufo_bullets_cancel_in_pairs() {
+        let mut bullets = vec![
+            bullet(100., BulletOwner::Player(0)),
+            bullet(104., BulletOwner::Ufo),
+            bullet(105., BulletOwner::Ufo),
+            bullet(300., BulletOwner::Player(1)),
+        ];
+        let sparks = cancel_bullets(&mut bullets);
+        assert_eq!(sparks, vec![vec2(102., 100.)]);
+        let active: Vec<bool> = bullets.iter().map(|b| b.active).collect();
+        // One UFO shot is used up by the single nearby player shot; the far one is untouched
+        assert_eq!(active, vec![false, false, true, true]);
+    }
+
+    #[test]
+    fn test_same_side_bullets_pass_through_each_other() {
+        let mut bullets = vec![
+            bullet(100., BulletOwner::Player(0)),
+            bullet(101., BulletOwner::Player(1)),
+            bullet(200., BulletOwner::Ufo),
+            bullet(201., BulletOwner::Ufo),
+        ];
+        assert!(cancel_bullets(&mut bullets).is_empty());
+        assert!(bullets.iter().all(|b| b.active));
+    }
+
+    #[test]
+    fn test_friendly_fire_only_hits_teammates_when_enabled() {
+        assert!(bullet_hits_player(BulletOwner::Ufo, 0, false));
+        assert!(!bullet_hits_player(BulletOwner::Player(0), 1, false));
+        assert!(bullet_hits_player(BulletOwner::Player(0), 1, true));
+        // Never your own shots
+        assert!(!bullet_hits_player(BulletOwner::Player(1), 1, true));
+
+        // Enemies target the closer ship
+        let mut world = test_world();
+        world.respawn_team(true);
+        assert_eq!(nearest_player_pos(&world.players, vec2(0., 300.)), world.players[0].pos);
+        assert_eq!(nearest_player_pos(&world.players, vec2(800., 300.)), world.players[1].pos);
+    }
+
+    #[test]
+    fn test_ufos_leave_invulnerable_ships_alone() {
+        let rocks = [rock(vec2(100., 100.), Vec2::ZERO, 30.)];
+        let mut ships = [test_player()];
+        ships[0].invulnerable = true;
+        for roll in 0..3 {
+            assert_eq!(choose_ufo_target(&ships, vec2(0., 0.), &rocks, roll), Some(rocks[0].pos));
+        }
+        // Nothing else to shoot at: hold fire
+        assert_eq!(choose_ufo_target(&ships, vec2(0., 0.), &[], 1), None);
+        // Once vulnerable the ship is fair game, apart from the occasional shot at a rock
+        ships[0].invulnerable = false;
+        assert_eq!(choose_ufo_target(&ships, vec2(0., 0.), &rocks, 1), Some(ships[0].pos));
+        assert_eq!(choose_ufo_target(&ships, vec2(0., 0.), &rocks, 0), Some(rocks[0].pos));
+    }
+
+    #[test]
+    fn test_overlapping_asteroids_cost_one_life() {
+        let rock_at = |x: f32| rock(vec2(x, 300.), Vec2::ZERO, 30.);
+        let mut players = vec![test_player()];
+        let mut asteroids = vec![rock_at(380.), rock_at(420.)];
+        let mut hit_this_frame = vec![false];
+
+        let contacts = player_asteroid_collisions(CollisionResponse::Lethal, &mut players, &mut asteroids, &mut hit_this_frame);
+        let lives_lost: i32 = contacts.iter().map(|c| c.impact.lives_lost()).sum();
+        assert_eq!(lives_lost, 1);
+        assert_eq!(contacts, vec![AsteroidContact { player: 0, asteroid: 0, impact: PlayerImpact::Lethal }]);
+        assert!(!asteroids[0].active);
+        assert!(asteroids[1].active);
+        assert!(hit_this_frame[0]);
+
+        // A ship already hit earlier in the frame (e.g. by a UFO bullet) takes nothing more
+        let mut asteroids = vec![rock_at(380.)];
+        let contacts = player_asteroid_collisions(CollisionResponse::Lethal, &mut players, &mut asteroids, &mut hit_this_frame);
+        assert!(contacts.is_empty());
+        assert!(asteroids[0].active);
+    }
+}